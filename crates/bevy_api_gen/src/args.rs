@@ -1,7 +1,11 @@
+use std::ffi::OsString;
+
 use cargo_metadata::camino::Utf8PathBuf;
-use clap::{Parser, Subcommand};
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 
+use crate::{Config, GenericsConfig, DEFAULT_CONFIG_FILE};
+
 #[derive(Parser, Deserialize, Serialize)]
 #[command(
     version,
@@ -54,6 +58,170 @@ pub struct Args {
     /// additional template context in the form of json, provided to the templates under an 'args' key
     #[arg(global = true, long)]
     pub template_args: Option<String>,
+
+    /// The scripting language to generate bindings for, picks the set of built-in templates to use
+    #[arg(global = true, long, value_enum, default_value_t = crate::TemplateLanguage::Lua)]
+    pub language: crate::TemplateLanguage,
+
+    /// Path to a `bevy_api_gen.toml` config file, arguments not given on the command line are taken from it.
+    ///
+    /// Defaults to `bevy_api_gen.toml` in the current directory if it exists
+    #[arg(global = true, long, value_name = "FILE")]
+    pub config: Option<Utf8PathBuf>,
+
+    /// The contents of the config file
+    #[arg(skip)]
+    pub(crate) file_config: Config,
+}
+
+impl Args {
+    /// Parses the arguments, filling in the ones not given on the command line from the config file if there is one
+    pub fn parse_with_config<I, T>(itr: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = Self::command().get_matches_from(itr);
+        let mut args = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+        let config_path = args.config.clone().or_else(|| {
+            let default = Utf8PathBuf::from(DEFAULT_CONFIG_FILE);
+            default.is_file().then_some(default)
+        });
+        if let Some(path) = config_path {
+            args.apply_config(Config::from_file(&path), &matches);
+        }
+
+        // the deprecated generics file is an alias for the instantiations in the config file
+        if let Command::Generate {
+            generics: Some(path),
+            ..
+        } = &args.cmd
+        {
+            args.file_config
+                .instantiations
+                .extend(GenericsConfig::from_file(path).instantiations);
+        }
+        args
+    }
+
+    /// Removes the crates skipped in the config file from the crates the plugin runs on, `None` meaning all workspace crates
+    pub fn filter_generated_crates(
+        &self,
+        include_crates: Option<Vec<String>>,
+        workspace_crates: &[String],
+    ) -> Option<Vec<String>> {
+        let config = &self.file_config;
+        if config.generate_unlisted_crates && config.crates.values().all(|c| c.generate) {
+            return include_crates;
+        }
+        Some(
+            include_crates
+                .unwrap_or_else(|| workspace_crates.to_vec())
+                .into_iter()
+                .filter(|c| config.generates_crate(c))
+                .collect(),
+        )
+    }
+
+    fn apply_config(&mut self, config: Config, matches: &ArgMatches) {
+        let (_, sub_matches) = matches
+            .subcommand()
+            .expect("A subcommand is required to be present");
+        // global arguments may be given before or after the subcommand
+        let is_default = |id: &str| {
+            [matches, sub_matches]
+                .iter()
+                .all(|m| m.value_source(id) != Some(ValueSource::CommandLine))
+        };
+        let is_default_in_subcommand =
+            |id: &str| sub_matches.value_source(id) != Some(ValueSource::CommandLine);
+
+        if is_default("features") && !config.features.is_empty() {
+            self.features = config.features.clone();
+        }
+        if is_default("no_default_features") {
+            self.no_default_features = config.no_default_features;
+        }
+        if is_default("workspace_root") && config.workspace_root.is_some() {
+            self.workspace_root = config.workspace_root.clone();
+        }
+        if let Some(language) = config.language.filter(|_| is_default("language")) {
+            self.language = language;
+        }
+
+        // the template args on the command line take precedence over the ones in the file
+        let mut template_args = config.template_args();
+        if let Some(args) = &self.template_args {
+            let args = serde_json::from_str::<serde_json::Value>(args)
+                .expect("Could not parse template args as json");
+            template_args.extend(
+                args.as_object()
+                    .expect("Expected template args to be a json object")
+                    .clone(),
+            );
+        }
+        if !template_args.is_empty() {
+            self.template_args = Some(serde_json::Value::Object(template_args).to_string());
+        }
+
+        match &mut self.cmd {
+            Command::Generate {
+                output,
+                templates,
+                include_private,
+                meta,
+                meta_output,
+                ignored_types,
+                ..
+            } => {
+                if let Some(dir) = config
+                    .output
+                    .dir
+                    .clone()
+                    .filter(|_| is_default_in_subcommand("output"))
+                {
+                    *output = dir;
+                }
+                if templates.is_none() {
+                    *templates = config.output.templates.clone();
+                }
+                if is_default_in_subcommand("include_private") {
+                    *include_private = config.include_private;
+                }
+                if meta.is_none() && !config.output.meta.is_empty() {
+                    *meta = Some(config.output.meta.clone());
+                }
+                if meta_output.is_none() {
+                    *meta_output = config.output.meta_output.clone();
+                }
+                ignored_types.extend(config.ignored_types.iter().cloned());
+            }
+            Command::Collect {
+                output,
+                templates,
+                api_name,
+            } => {
+                if let Some(dir) = config
+                    .output
+                    .dir
+                    .clone()
+                    .filter(|_| is_default_in_subcommand("output"))
+                {
+                    *output = dir;
+                }
+                if templates.is_none() {
+                    *templates = config.output.templates.clone();
+                }
+                if api_name.is_none() {
+                    *api_name = config.output.api_name.clone();
+                }
+            }
+            _ => {}
+        }
+
+        self.file_config = config;
+    }
 }
 
 #[derive(clap::Args, Debug, Clone, Default, Serialize, Deserialize)]
@@ -167,6 +335,12 @@ pub enum Command {
         #[arg(long, action)]
         template_data_only: bool,
 
+        /// Deprecated, list the instantiations of generic types under `instantiations` in the config file instead.
+        ///
+        /// Optional path to a TOML file listing instantiations of generic types to generate proxies for, which are added to the ones in the config file.
+        #[arg(long, value_name = "FILE")]
+        generics: Option<Utf8PathBuf>,
+
         #[arg(
             long,
             default_value = default_ignored_types(),
@@ -188,8 +362,10 @@ pub enum Command {
 
         /// The name of the API, this will be passed to the `collect.rs` template, which by default will be used as the APIProvider name and the
         /// title of the documentation.
-        #[arg(short, long, value_name = "NAME", default_value = "LuaBevyAPIProvider")]
-        api_name: String,
+        ///
+        /// Defaults to `LuaBevyAPIProvider` for lua and `RhaiGeneratedBevyAPIProvider` for rhai
+        #[arg(short, long, value_name = "NAME")]
+        api_name: Option<String>,
     },
 }
 
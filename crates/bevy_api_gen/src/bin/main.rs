@@ -9,8 +9,7 @@ use std::{
 
 use bevy_api_gen::*;
 use cargo_metadata::camino::Utf8Path;
-use clap::Parser;
-use log::{debug, info};
+use log::{debug, info, warn};
 use strum::VariantNames;
 use tera::Context;
 
@@ -18,13 +17,20 @@ const BOOTSTRAP_DEPS: [&str; 2] = ["mlua", "bevy_reflect"];
 
 fn main() {
     // parse this here to early exit on wrong args
-    let args = Args::parse_from(env::args().skip(1));
+    let args = Args::parse_with_config(env::args().skip(1));
 
     if env::var("RUST_LOG").is_err() {
         env::set_var("RUST_LOG", args.verbose.get_rustlog_value());
     }
     env_logger::init();
 
+    if let bevy_api_gen::Command::Generate {
+        generics: Some(_), ..
+    } = &args.cmd
+    {
+        warn!("`--generics` is deprecated, list the instantiations under `instantiations` in `bevy_api_gen.toml` instead");
+    }
+
     let metadata = cargo_metadata::MetadataCommand::new()
         .no_deps()
         .other_options(["--all-features".to_string(), "--offline".to_string()])
@@ -48,6 +54,11 @@ fn main() {
         }
         _ => None,
     };
+    // crates skipped in the config file are still compiled, but the plugin does not run on them
+    let include_crates = match args.cmd.is_generate() {
+        true => args.filter_generated_crates(include_crates, &crates),
+        false => include_crates,
+    };
 
     let plugin_subdir = format!("plugin-{}", env!("RUSTC_CHANNEL"));
     let plugin_target_dir = metadata.target_directory.join(plugin_subdir);
@@ -64,14 +75,7 @@ fn main() {
 
     match args.cmd {
         bevy_api_gen::Command::Print { template } => {
-            println!(
-                "{}",
-                TEMPLATE_DIR
-                    .get_file(template.to_string())
-                    .unwrap()
-                    .contents_utf8()
-                    .unwrap()
-            );
+            println!("{}", args.language.template(template));
             return;
         }
         bevy_api_gen::Command::ListTemplates => {
@@ -85,7 +89,7 @@ fn main() {
             templates,
             api_name,
         } => {
-            let tera = configure_tera("no_crate", &templates);
+            let tera = configure_tera("no_crate", &templates, args.language);
             info!("Collecting from: {}", output);
             if !output.is_dir() {
                 panic!("Output is not a directory");
@@ -115,7 +119,7 @@ fn main() {
                         Crate { name, meta }
                     })
                     .collect(),
-                api_name,
+                api_name: api_name.unwrap_or_else(|| args.language.default_api_name().to_owned()),
             };
             let mut context =
                 Context::from_serialize(context).expect("Could not create template context");
@@ -61,7 +61,11 @@ impl rustc_driver::Callbacks for BevyAnalyzerCallbacks {
 
         gcx.enter(|tcx| {
             // tera environment for import processor
-            let tera = crate::configure_tera(tcx.crate_name(LOCAL_CRATE).as_str(), &templates_dir);
+            let tera = crate::configure_tera(
+                tcx.crate_name(LOCAL_CRATE).as_str(),
+                &templates_dir,
+                self.args.language,
+            );
 
             let mut ctxt = crate::BevyCtxt::new(
                 tcx,
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::InstantiationConfig;
+
+/// The name of the config file picked up from the current directory if `--config` is not given
+pub(crate) const DEFAULT_CONFIG_FILE: &str = "bevy_api_gen.toml";
+
+/// The contents of a `bevy_api_gen.toml` file, describes a codegen run so it can be repeated without passing the same flags every time.
+///
+/// Values given on the command line take precedence over the ones in the file.
+/// ```toml
+/// features = ["bevy_render"]
+///
+/// [output]
+/// dir = "target/codegen/output"
+/// api_name = "LuaBevyAPIProvider"
+///
+/// [crates.bevy_render]
+/// feature = "api_render"
+/// deny_types = ["Msaa"]
+///
+/// [crates.bevy_render.types.Color]
+/// rename = "RenderColor"
+/// deny_methods = ["as_rgba_u32"]
+/// ```
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// The scripting language to generate bindings for
+    pub(crate) language: Option<crate::TemplateLanguage>,
+    /// Features to enable when running cargo
+    pub(crate) features: Vec<String>,
+    /// Disable default features when running cargo
+    pub(crate) no_default_features: bool,
+    /// The workspace root used to calculate the crates active with the given features
+    pub(crate) workspace_root: Option<String>,
+    /// If true private types are included in the generated code
+    pub(crate) include_private: bool,
+    /// If false, only crates with an entry in `crates` are generated
+    #[serde(default = "default_true")]
+    pub(crate) generate_unlisted_crates: bool,
+    /// Types to ignore in addition to the ones given via `--ignored-types`, in the form `crate_name::TypeName`
+    pub(crate) ignored_types: Vec<String>,
+    /// Paths of traits whose implementations are exposed in addition to the built-in ones, i.e. `std::hash::Hash`
+    pub(crate) extra_traits: Vec<String>,
+    /// Additional template context, merged with the one given via `--template-args`
+    pub(crate) template_args: Option<toml::Table>,
+    pub(crate) output: OutputConfig,
+    /// Per crate configuration keyed by crate name
+    pub(crate) crates: BTreeMap<String, CrateConfig>,
+    /// Instantiations of generic types to generate proxies for, see [`InstantiationConfig`]
+    pub(crate) instantiations: Vec<InstantiationConfig>,
+}
+
+/// Where and how the generated files are written, relative paths are resolved against the directory of the config file
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct OutputConfig {
+    /// The output and artifact directory
+    pub(crate) dir: Option<Utf8PathBuf>,
+    /// The directory in which to look for templates
+    pub(crate) templates: Option<Utf8PathBuf>,
+    /// Additional directories to search for meta files in
+    pub(crate) meta: Vec<Utf8PathBuf>,
+    /// The directory to write meta files to
+    pub(crate) meta_output: Option<Utf8PathBuf>,
+    /// The name of the API provider generated by `collect`
+    pub(crate) api_name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct CrateConfig {
+    /// If false, the crate is still compiled but no bindings are generated for it
+    pub(crate) generate: bool,
+    /// The cargo feature the generated bindings of this crate are gated behind, passed to the templates in the `crate_features` template argument
+    pub(crate) feature: Option<String>,
+    /// If given, only the types with these names are generated
+    pub(crate) allow_types: Option<Vec<String>>,
+    /// Names of types which are not generated
+    pub(crate) deny_types: Vec<String>,
+    /// Names of methods which are not generated on any type in this crate
+    pub(crate) deny_methods: Vec<String>,
+    /// Per type configuration keyed by type name, or by the name of the proxy for instantiations of generic types
+    pub(crate) types: BTreeMap<String, TypeConfig>,
+}
+
+impl Default for CrateConfig {
+    fn default() -> Self {
+        Self {
+            generate: true,
+            feature: None,
+            allow_types: None,
+            deny_types: Vec::default(),
+            deny_methods: Vec::default(),
+            types: BTreeMap::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct TypeConfig {
+    /// The name of the generated proxy, the type is then referred to through an alias with this name
+    pub(crate) rename: Option<String>,
+    /// If given, only the methods with these names are generated
+    pub(crate) allow_methods: Option<Vec<String>>,
+    /// Names of methods which are not generated
+    pub(crate) deny_methods: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Config {
+    pub(crate) fn from_file(path: &Utf8Path) -> Self {
+        let contents = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("Could not read config file: `{path}`, {e}"));
+        let mut config: Self = toml::from_str(&contents)
+            .unwrap_or_else(|e| panic!("Could not parse config file: `{path}`, {e}"));
+        if let Some(dir) = path.parent() {
+            config.output.resolve_paths(dir);
+        }
+        config
+    }
+
+    /// Returns true if bindings should be generated for the given crate
+    pub(crate) fn generates_crate(&self, crate_name: &str) -> bool {
+        match self.crates.get(crate_name) {
+            Some(crate_config) => crate_config.generate,
+            None => self.generate_unlisted_crates,
+        }
+    }
+
+    /// Returns true if the type with the given name in the given crate passes the allow and deny lists of the crate
+    pub(crate) fn allows_type(&self, crate_name: &str, type_name: &str) -> bool {
+        self.crates.get(crate_name).map_or(true, |crate_config| {
+            crate_config
+                .allow_types
+                .as_ref()
+                .map_or(true, |allowed| allowed.iter().any(|t| t == type_name))
+                && !crate_config.deny_types.iter().any(|t| t == type_name)
+        })
+    }
+
+    /// Returns true if the method with the given name passes the allow and deny lists of its type and crate
+    pub(crate) fn allows_method(&self, crate_name: &str, type_name: &str, method: &str) -> bool {
+        let Some(crate_config) = self.crates.get(crate_name) else {
+            return true;
+        };
+        if crate_config.deny_methods.iter().any(|m| m == method) {
+            return false;
+        }
+        crate_config
+            .types
+            .get(type_name)
+            .map_or(true, |type_config| {
+                type_config
+                    .allow_methods
+                    .as_ref()
+                    .map_or(true, |allowed| allowed.iter().any(|m| m == method))
+                    && !type_config.deny_methods.iter().any(|m| m == method)
+            })
+    }
+
+    /// The new name of the given type, if it is renamed
+    pub(crate) fn rename(&self, crate_name: &str, type_name: &str) -> Option<String> {
+        self.crates
+            .get(crate_name)?
+            .types
+            .get(type_name)?
+            .rename
+            .clone()
+    }
+
+    /// The template args given in the config file, with the features of each crate under the `crate_features` key
+    pub(crate) fn template_args(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut args = self
+            .template_args
+            .as_ref()
+            .map(|table| {
+                serde_json::to_value(table)
+                    .expect("Could not convert template args to json")
+                    .as_object()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default();
+
+        let crate_features = self
+            .crates
+            .iter()
+            .filter_map(|(name, crate_config)| {
+                crate_config
+                    .feature
+                    .as_ref()
+                    .map(|f| (name.to_owned(), serde_json::Value::String(f.to_owned())))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        if !crate_features.is_empty() {
+            args.entry("crate_features")
+                .or_insert_with(|| serde_json::Value::Object(Default::default()))
+                .as_object_mut()
+                .expect("Expected `crate_features` template argument to be a table")
+                .extend(crate_features);
+        }
+        args
+    }
+}
+
+impl OutputConfig {
+    fn resolve_paths(&mut self, dir: &Utf8Path) {
+        let resolve = |path: &mut Utf8PathBuf| {
+            if path.is_relative() {
+                *path = dir.join(&*path);
+            }
+        };
+        self.dir.iter_mut().for_each(resolve);
+        self.templates.iter_mut().for_each(resolve);
+        self.meta.iter_mut().for_each(resolve);
+        self.meta_output.iter_mut().for_each(resolve);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        toml::from_str(
+            r#"
+            generate_unlisted_crates = false
+
+            [template_args]
+            self_is_bevy_script_api = true
+            crate_features = { bevy_core = "api_core" }
+
+            [crates.bevy_render]
+            feature = "api_render"
+            deny_types = ["Msaa"]
+            deny_methods = ["clone_from"]
+
+            [crates.bevy_render.types.Color]
+            rename = "RenderColor"
+            allow_methods = ["rgb", "clone_from"]
+
+            [crates.bevy_time]
+            generate = false
+            allow_types = ["Time"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn crate_and_type_filters() {
+        let config = config();
+        assert!(config.generates_crate("bevy_render"));
+        assert!(!config.generates_crate("bevy_time"));
+        assert!(!config.generates_crate("bevy_ui"));
+
+        assert!(config.allows_type("bevy_render", "Color"));
+        assert!(!config.allows_type("bevy_render", "Msaa"));
+        assert!(config.allows_type("bevy_time", "Time"));
+        assert!(!config.allows_type("bevy_time", "Timer"));
+        assert!(config.allows_type("bevy_ui", "Style"));
+    }
+
+    #[test]
+    fn method_filters_and_renames() {
+        let config = config();
+        assert!(config.allows_method("bevy_render", "Color", "rgb"));
+        assert!(!config.allows_method("bevy_render", "Color", "rgba"));
+        assert!(!config.allows_method("bevy_render", "Color", "clone_from"));
+        assert!(config.allows_method("bevy_render", "Camera", "target_info"));
+        assert!(!config.allows_method("bevy_render", "Camera", "clone_from"));
+
+        assert_eq!(
+            config.rename("bevy_render", "Color").as_deref(),
+            Some("RenderColor")
+        );
+        assert_eq!(config.rename("bevy_render", "Camera"), None);
+    }
+
+    #[test]
+    fn template_args_include_crate_features() {
+        let args = serde_json::Value::Object(config().template_args());
+        assert_eq!(
+            args,
+            serde_json::json!({
+                "self_is_bevy_script_api": true,
+                "crate_features": { "bevy_core": "api_core", "bevy_render": "api_render" }
+            })
+        );
+    }
+
+    #[test]
+    fn relative_paths_are_resolved_against_config_dir() {
+        let mut output: OutputConfig = toml::from_str(
+            r#"
+            dir = "target/output"
+            meta = ["/tmp/meta"]
+            "#,
+        )
+        .unwrap();
+        output.resolve_paths(Utf8Path::new("/project"));
+        assert_eq!(output.dir.unwrap(), "/project/target/output");
+        assert_eq!(output.meta, vec![Utf8PathBuf::from("/tmp/meta")]);
+    }
+}
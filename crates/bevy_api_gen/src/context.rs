@@ -3,8 +3,8 @@ use std::collections::HashMap;
 use cargo_metadata::camino::Utf8PathBuf;
 use indexmap::IndexMap;
 use log::debug;
-use rustc_hir::def_id::DefId;
-use rustc_middle::ty::{AdtDef, TyCtxt};
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::ty::{AdtDef, GenericArgsRef, Ty, TyCtxt, TyKind};
 use serde::Serialize;
 
 use crate::{ImportPathFinder, MetaLoader, TemplateContext};
@@ -12,7 +12,8 @@ use crate::{ImportPathFinder, MetaLoader, TemplateContext};
 pub(crate) struct BevyCtxt<'tcx> {
     pub(crate) tcx: TyCtxt<'tcx>,
     pub(crate) meta_loader: MetaLoader,
-    pub(crate) reflect_types: IndexMap<DefId, ReflectType<'tcx>>,
+    /// The types to generate proxies for, non-generic types are keyed by their identity type
+    pub(crate) reflect_types: IndexMap<Ty<'tcx>, ReflectType<'tcx>>,
     pub(crate) cached_traits: CachedTraits,
     pub(crate) path_finder: ImportPathFinder<'tcx>,
 
@@ -60,7 +61,11 @@ pub(crate) struct ReflectType<'tcx> {
     /// Information about the ADT structure, fields, and variants
     pub(crate) variant_data: Option<AdtDef<'tcx>>,
     /// Functions passing criteria to be proxied
-    pub(crate) valid_functions: Option<Vec<FunctionContext>>,
+    pub(crate) valid_functions: Option<Vec<FunctionContext<'tcx>>>,
+    /// The name of the proxy if this is an instantiation of a generic type listed in the config file
+    pub(crate) instantiation_ident: Option<String>,
+    /// The name of the proxy if the type is renamed in the config file
+    pub(crate) rename: Option<String>,
 
     /// Mapping from fields to the reflection strategy
     field_reflection_types: IndexMap<DefId, ReflectionStrategy>,
@@ -81,6 +86,135 @@ impl ReflectType<'_> {
     }
 }
 
+/// The name of the proxied type, instantiations of generic types use the name from the config file and renamed types their new name
+pub(crate) fn proxy_ident<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ty: Ty<'tcx>,
+    reflect_ty: &ReflectType<'tcx>,
+) -> String {
+    alias_ident(reflect_ty).unwrap_or_else(|| {
+        tcx.item_name(ty.ty_adt_def().unwrap().did())
+            .to_ident_string()
+    })
+}
+
+/// The name of the alias the proxied type is referred to through, if it is an instantiation of a generic type or renamed
+pub(crate) fn alias_ident(reflect_ty: &ReflectType<'_>) -> Option<String> {
+    reflect_ty
+        .instantiation_ident
+        .clone()
+        .or_else(|| reflect_ty.rename.clone())
+}
+
+/// A key identifying an instantiation of a generic type which is the same in every crate,
+/// made up of the def path hashes of the ADTs inside it
+pub(crate) fn instantiation_key<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> String {
+    match ty.kind() {
+        TyKind::Adt(adt_def, args) => {
+            let def_path_hash = tcx.def_path_hash(adt_def.did());
+            let mut key = format!(
+                "{}:{}",
+                def_path_hash.stable_crate_id().as_u64(),
+                def_path_hash.local_hash().as_u64()
+            );
+            let args = args
+                .types()
+                .map(|ty| instantiation_key(tcx, ty))
+                .collect::<Vec<_>>();
+            if !args.is_empty() {
+                key.push('<');
+                key.push_str(&args.join(","));
+                key.push('>');
+            }
+            key
+        }
+        _ => ty.to_string(),
+    }
+}
+
+/// Finds the proxy generated for an instantiation of a generic type, either in the local crate or in the meta files of the crates defining the types inside it.
+/// Returns the name of the crate and of the proxy
+pub(crate) fn find_instantiation<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    reflect_types: &IndexMap<Ty<'tcx>, ReflectType<'tcx>>,
+    meta_loader: &MetaLoader,
+    ty: Ty<'tcx>,
+) -> Option<(String, String)> {
+    let ty = tcx.erase_regions(ty);
+    match ty.kind() {
+        TyKind::Adt(_, args) if args.types().next().is_some() => {}
+        _ => return None,
+    }
+
+    if let Some(ident) = reflect_types
+        .get(&ty)
+        .and_then(|reflect_ty| reflect_ty.instantiation_ident.clone())
+    {
+        return Some((tcx.crate_name(LOCAL_CRATE).to_ident_string(), ident));
+    }
+
+    let key = instantiation_key(tcx, ty);
+    let mut crates = ty
+        .walk()
+        .filter_map(|arg| arg.as_type())
+        .filter_map(|ty| ty.ty_adt_def())
+        .filter(|adt_def| !adt_def.did().is_local())
+        .map(|adt_def| tcx.crate_name(adt_def.did().krate).to_ident_string())
+        .collect::<Vec<_>>();
+    crates.dedup();
+
+    crates.into_iter().find_map(|crate_name| {
+        let ident = meta_loader
+            .meta_for(&crate_name)?
+            .instantiation_ident(&key)?
+            .to_owned();
+        Some((crate_name, ident))
+    })
+}
+
+/// Finds the new name of a non-generic type renamed in the config file, either in the local crate or in the meta file of the crate which generated its proxy.
+/// Returns the name of the crate and of the proxy
+pub(crate) fn find_rename<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    reflect_types: &IndexMap<Ty<'tcx>, ReflectType<'tcx>>,
+    meta_loader: &MetaLoader,
+    ty: Ty<'tcx>,
+) -> Option<(String, String)> {
+    let ty = tcx.erase_regions(ty);
+    let did = match ty.kind() {
+        TyKind::Adt(adt_def, args) if args.types().next().is_none() => adt_def.did(),
+        _ => return None,
+    };
+
+    if let Some(reflect_ty) = reflect_types.get(&ty) {
+        return reflect_ty
+            .rename
+            .clone()
+            .map(|rename| (tcx.crate_name(LOCAL_CRATE).to_ident_string(), rename));
+    }
+
+    // the proxy is generated either by the crate defining the type or by `bevy_reflect`
+    let crate_name = tcx.crate_name(did.krate).to_ident_string();
+    let meta_sources = if tcx.crate_name(LOCAL_CRATE).as_str() == "bevy_reflect" {
+        vec![crate_name]
+    } else {
+        vec![crate_name, "bevy_reflect".to_string()]
+    };
+    let def_path_hash = tcx.def_path_hash(did);
+    meta_sources.into_iter().find_map(|crate_name| {
+        let ident = meta_loader
+            .meta_for(&crate_name)?
+            .proxy_ident(def_path_hash)?
+            .to_owned();
+        (ident != tcx.item_name(did).as_str()).then_some((crate_name, ident))
+    })
+}
+
+/// The path to the alias of an instantiation of a generic type or a renamed type, relative to a generated module
+pub(crate) fn instantiation_alias_path(crate_name: &str, ident: &str) -> String {
+    format!("super::{crate_name}::instantiations::{ident}")
+}
+
 pub(crate) const DEF_PATHS_FROM_LUA: [&str; 2] = ["value::FromLuaMulti", "mlua::FromLuaMulti"];
 pub(crate) const DEF_PATHS_INTO_LUA: [&str; 2] = ["value::IntoLuaMulti", "mlua::IntoLuaMulti"];
 pub(crate) const DEF_PATHS_REFLECT: [&str; 2] = ["bevy_reflect::Reflect", "reflect::Reflect"];
@@ -148,8 +282,10 @@ impl CachedTraits {
 }
 
 #[derive(Clone, Debug)]
-pub(crate) struct FunctionContext {
+pub(crate) struct FunctionContext<'tcx> {
     pub(crate) def_id: DefId,
+    /// The generic arguments the function is instantiated with, the identity arguments for non-generic types
+    pub(crate) args: GenericArgsRef<'tcx>,
     pub(crate) has_self: bool,
     pub(crate) is_unsafe: bool,
     pub(crate) trait_did: Option<DefId>,
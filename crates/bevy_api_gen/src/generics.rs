@@ -0,0 +1,162 @@
+use cargo_metadata::camino::Utf8Path;
+use serde::{Deserialize, Serialize};
+
+/// The contents of the file passed via the deprecated `--generics` flag, lists the instantiations of generic types to generate proxies for.
+/// The same list is normally given under `instantiations` in the config file
+/// ```toml
+/// [[instantiations]]
+/// type = "bevy_asset::Handle<bevy_render::texture::Image>"
+///
+/// [[instantiations]]
+/// type = "alloc::vec::Vec<bevy_ecs::entity::Entity>"
+/// name = "EntityList"
+/// ```
+#[derive(Deserialize, Default, Debug)]
+pub(crate) struct GenericsConfig {
+    #[serde(default)]
+    pub(crate) instantiations: Vec<InstantiationConfig>,
+}
+
+impl GenericsConfig {
+    pub(crate) fn from_file(path: &Utf8Path) -> Self {
+        let contents = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("Could not read generics file: `{path}`, {e}"));
+        toml::from_str(&contents)
+            .unwrap_or_else(|e| panic!("Could not parse generics file: `{path}`, {e}"))
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub(crate) struct InstantiationConfig {
+    /// The instantiated type, each type inside must be given by its full path i.e. `bevy_asset::Handle<bevy_render::texture::Image>`
+    #[serde(rename = "type")]
+    pub(crate) ty: String,
+    /// The name of the generated proxy, defaults to the identifiers of the types inside joined together i.e. `HandleImage`
+    pub(crate) name: Option<String>,
+}
+
+impl InstantiationConfig {
+    pub(crate) fn pattern(&self) -> Result<TypePattern, String> {
+        TypePattern::parse(&self.ty)
+    }
+
+    pub(crate) fn ident(&self, pattern: &TypePattern) -> String {
+        self.name.clone().unwrap_or_else(|| pattern.default_ident())
+    }
+}
+
+/// A type as written in an instantiation, with the paths not yet resolved to types in the crate
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TypePattern {
+    /// The path to the type without any generic arguments i.e. `bevy_asset::Handle`
+    pub(crate) path: String,
+    pub(crate) args: Vec<TypePattern>,
+}
+
+impl TypePattern {
+    pub(crate) fn parse(ty: &str) -> Result<Self, String> {
+        let parsed = syn::parse_str::<syn::Type>(ty)
+            .map_err(|e| format!("Could not parse type: `{ty}`, {e}"))?;
+        Self::from_syn(&parsed).map_err(|e| format!("Unsupported type: `{ty}`, {e}"))
+    }
+
+    fn from_syn(ty: &syn::Type) -> Result<Self, &'static str> {
+        let path = match ty {
+            syn::Type::Path(syn::TypePath { qself: None, path }) => path,
+            _ => return Err("only paths to types are supported"),
+        };
+
+        let mut segments = Vec::with_capacity(path.segments.len());
+        let mut args = Vec::default();
+        for (idx, segment) in path.segments.iter().enumerate() {
+            segments.push(segment.ident.to_string());
+            match &segment.arguments {
+                syn::PathArguments::None => {}
+                syn::PathArguments::AngleBracketed(generics) if idx == path.segments.len() - 1 => {
+                    for arg in &generics.args {
+                        match arg {
+                            syn::GenericArgument::Type(ty) => args.push(Self::from_syn(ty)?),
+                            _ => return Err("only type arguments are supported"),
+                        }
+                    }
+                }
+                _ => return Err("generic arguments are only supported on the last path segment"),
+            }
+        }
+
+        Ok(Self {
+            path: segments.join("::"),
+            args,
+        })
+    }
+
+    /// The identifiers of this type and its arguments joined together in upper camel case i.e. `HandleImage`
+    pub(crate) fn default_ident(&self) -> String {
+        let ident = self.path.rsplit("::").next().unwrap_or_default();
+        let mut chars = ident.chars();
+        let mut out = chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default();
+        for arg in &self.args {
+            out.push_str(&arg.default_ident());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_nested_instantiation() {
+        let pattern = TypePattern::parse(
+            "std::option::Option<bevy_asset::Handle<bevy_render::texture::Image>>",
+        )
+        .unwrap();
+        assert_eq!(
+            pattern,
+            TypePattern {
+                path: "std::option::Option".to_owned(),
+                args: vec![TypePattern {
+                    path: "bevy_asset::Handle".to_owned(),
+                    args: vec![TypePattern {
+                        path: "bevy_render::texture::Image".to_owned(),
+                        args: vec![],
+                    }],
+                }],
+            }
+        );
+        assert_eq!(pattern.default_ident(), "OptionHandleImage");
+    }
+
+    #[test]
+    fn parse_unsupported_types() {
+        assert!(TypePattern::parse("&bevy_ecs::entity::Entity").is_err());
+        assert!(TypePattern::parse("[f32; 3]").is_err());
+        assert!(TypePattern::parse("my_crate::Stat<'static, u32>").is_err());
+        assert!(TypePattern::parse("my_crate::module<u32>::Stat").is_err());
+    }
+
+    #[test]
+    fn instantiation_idents() {
+        let config: GenericsConfig = toml::from_str(
+            r#"
+            [[instantiations]]
+            type = "alloc::vec::Vec<u32>"
+
+            [[instantiations]]
+            type = "alloc::vec::Vec<bevy_ecs::entity::Entity>"
+            name = "EntityList"
+            "#,
+        )
+        .unwrap();
+        let idents = config
+            .instantiations
+            .iter()
+            .map(|i| i.ident(&i.pattern().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(idents, vec!["VecU32", "EntityList"]);
+    }
+}
@@ -15,8 +15,10 @@ extern crate rustc_trait_selection;
 
 mod args;
 mod callback;
+mod config;
 mod context;
 mod feature_graph;
+mod generics;
 mod import_path;
 mod meta;
 mod modifying_file_loader;
@@ -26,7 +28,9 @@ mod template;
 
 // pub(crate) use args::*;
 pub(crate) use callback::*;
+pub(crate) use config::*;
 pub(crate) use context::*;
+pub(crate) use generics::*;
 pub(crate) use import_path::*;
 pub(crate) use meta::*;
 pub(crate) use passes::*;
@@ -37,5 +41,6 @@ pub use feature_graph::*;
 pub use meta::MetaLoader;
 pub use plugin::BevyAnalyzer;
 pub use template::{
-    configure_tera, extend_context_with_args, Collect, Crate, TemplateKind, TEMPLATE_DIR,
+    configure_tera, extend_context_with_args, Collect, Crate, TemplateKind, TemplateLanguage,
+    TEMPLATE_DIR,
 };
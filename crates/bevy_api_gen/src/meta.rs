@@ -14,7 +14,7 @@ use crate::WorkspaceMeta;
 
 /// The version of the meta file format, we can use this to show errors if incompatibile formats are used
 /// or to convert older formats to newer ones automatically
-pub(crate) const META_VERSION: &str = "1";
+pub(crate) const META_VERSION: &str = "2";
 
 /// Similar to .rmeta files but for the code generator, each crate is analysed separately but we need to share some information
 /// between crates to be able to properly identify links between crates
@@ -30,10 +30,27 @@ pub struct Meta {
 impl Meta {
     /// Returns true if the crate generated a proxy with the given DefPathHash (for the ADT)
     pub(crate) fn contains_def_path_hash(&self, did: DefPathHash) -> bool {
-        self.proxies.iter().any(|meta| {
-            meta.stable_crate_id == did.stable_crate_id().as_u64()
-                && meta.local_hash_id == did.local_hash().as_u64()
-        })
+        self.proxy_ident(did).is_some()
+    }
+
+    /// Returns the name of the proxy the crate generated for the ADT with the given DefPathHash, which differs from the name of the ADT if it was renamed
+    pub(crate) fn proxy_ident(&self, did: DefPathHash) -> Option<&str> {
+        self.proxies
+            .iter()
+            .find(|meta| {
+                meta.instantiation.is_none()
+                    && meta.stable_crate_id == did.stable_crate_id().as_u64()
+                    && meta.local_hash_id == did.local_hash().as_u64()
+            })
+            .map(|meta| meta.ident.as_str())
+    }
+
+    /// Returns the name of the proxy the crate generated for the instantiation of a generic type with the given key
+    pub(crate) fn instantiation_ident(&self, instantiation_key: &str) -> Option<&str> {
+        self.proxies
+            .iter()
+            .find(|meta| meta.instantiation.as_deref() == Some(instantiation_key))
+            .map(|meta| meta.ident.as_str())
     }
 }
 
@@ -42,6 +59,9 @@ pub(crate) struct ProxyMeta {
     pub(crate) ident: String,
     pub(crate) stable_crate_id: u64,
     pub(crate) local_hash_id: u64,
+    /// The instantiation key if the proxy is for an instantiation of a generic type, the hashes are then those of the generic ADT
+    #[serde(default)]
+    pub(crate) instantiation: Option<String>,
 }
 
 /// Manages deserialisation and retrieval of meta files
@@ -8,7 +8,7 @@ use crate::{
 };
 
 /// Finds and caches relevant traits, if they cannot be found throws an ICE
-pub(crate) fn cache_traits(ctxt: &mut BevyCtxt<'_>, _args: &Args) -> bool {
+pub(crate) fn cache_traits(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bool {
     let tcx = &ctxt.tcx;
 
     for trait_did in tcx.all_traits() {
@@ -31,8 +31,8 @@ pub(crate) fn cache_traits(ctxt: &mut BevyCtxt<'_>, _args: &Args) -> bool {
             ctxt.cached_traits
                 .std_source_traits
                 .insert(def_path_str.to_string(), trait_did);
-        } else if STD_SOURCE_TRAITS.contains(&def_path_str.as_str()) {
-            trace!("found misc trait def id: {trait_did:?}");
+        } else if args.file_config.extra_traits.contains(&def_path_str) {
+            trace!("found extra trait def id: {trait_did:?}");
             ctxt.cached_traits
                 .std_source_traits
                 .insert(def_path_str.to_string(), trait_did);
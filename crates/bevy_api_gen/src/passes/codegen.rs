@@ -17,7 +17,11 @@ pub(crate) fn codegen(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bool {
         _ => return true,
     };
 
-    let tera = configure_tera(ctxt.tcx.crate_name(LOCAL_CRATE).as_str(), templates);
+    let tera = configure_tera(
+        ctxt.tcx.crate_name(LOCAL_CRATE).as_str(),
+        templates,
+        args.language,
+    );
 
     // perform code gen using templates
     fs::create_dir_all(output).unwrap();
@@ -45,25 +49,30 @@ pub(crate) fn codegen(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bool {
 mod tests {
     use std::collections::HashSet;
 
-    use strum::VariantNames;
+    use strum::{VariantArray, VariantNames};
 
-    use crate::TEMPLATE_DIR;
+    use crate::{TemplateLanguage, TEMPLATE_DIR};
 
     use super::*;
 
     #[test]
     fn test_templates_exist() {
-        let template_files: HashSet<&str> = TEMPLATE_DIR
-            .files()
-            .map(|file| file.path().file_name().unwrap().to_str().unwrap())
-            .collect();
+        for language in <TemplateLanguage as VariantArray>::VARIANTS {
+            let template_files: HashSet<&str> = TEMPLATE_DIR
+                .get_dir(language.to_string())
+                .unwrap_or_else(|| panic!("Missing template directory for: {}", language))
+                .files()
+                .map(|file| file.path().file_name().unwrap().to_str().unwrap())
+                .collect();
 
-        TemplateKind::VARIANTS.iter().for_each(|f| {
-            assert!(
-                template_files.contains(f),
-                "Template file not in variants: {}",
-                f
-            );
-        });
+            TemplateKind::VARIANTS.iter().for_each(|f| {
+                assert!(
+                    template_files.contains(f),
+                    "Template file not in variants: {}/{}",
+                    language,
+                    f
+                );
+            });
+        }
     }
 }
@@ -5,25 +5,42 @@ use rustc_hir::{
     def_id::{DefId, LOCAL_CRATE},
     Unsafety,
 };
-use rustc_infer::infer::TyCtxtInferExt;
-use rustc_middle::ty::{AdtKind, AssocKind, FieldDef, FnSig, ParamEnv, Ty, TyCtxt, TyKind};
-use rustc_span::Symbol;
-use rustc_trait_selection::infer::InferCtxtExt;
+use rustc_infer::{
+    infer::TyCtxtInferExt,
+    traits::{Obligation, ObligationCause},
+};
+use rustc_middle::ty::{
+    AdtKind, AssocKind, FieldDef, FnSig, GenericArgs, GenericArgsRef, ParamEnv, Ty, TyCtxt, TyKind,
+    TypeVisitableExt,
+};
+use rustc_span::{Symbol, DUMMY_SP};
+use rustc_trait_selection::{infer::InferCtxtExt, traits::ObligationCtxt};
 
 use crate::{
-    Args, BevyCtxt, CachedTraits, FunctionContext, MetaLoader, ReflectType, ReflectionStrategy,
+    find_instantiation, Args, BevyCtxt, CachedTraits, FunctionContext, MetaLoader, ReflectType,
+    ReflectionStrategy,
 };
 
 /// Finds all methods and fields which can be wrapped on a proxy, stores them in sorted order.
-pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) -> bool {
+pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bool {
     // we need to find all the methods and fields for which we want to generate lua bindings
     // we have to filter some out
     // go through all impls on the types (traits and non-traits) and pick signatures we're happy with
 
     // borrow checker fucky wucky
     let reflect_types = ctxt.reflect_types.keys().cloned().collect::<Vec<_>>();
-    for def_id in reflect_types {
-        let adt_def = ctxt.tcx.adt_def(def_id);
+    for ty in reflect_types {
+        let TyKind::Adt(adt_def, ty_args) = *ty.kind() else {
+            panic!("Unexpected type, all `Reflect` implementing types should be ADTs: {ty:?}");
+        };
+        let def_id = adt_def.did();
+        let is_instantiation = ctxt.reflect_types[&ty].instantiation_ident.is_some();
+        // the config file refers to instantiations by the name of their proxy
+        let config_type_name = ctxt.reflect_types[&ty]
+            .instantiation_ident
+            .clone()
+            .unwrap_or_else(|| ctxt.tcx.item_name(def_id).to_ident_string());
+        let crate_name = ctxt.tcx.crate_name(LOCAL_CRATE);
 
         match adt_def.adt_kind() {
             AdtKind::Enum => {
@@ -35,7 +52,8 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
                         todo!();
                     }
 
-                    process_fields(ctxt.tcx, &ctxt.meta_loader, &ctxt.reflect_types, &ctxt.cached_traits, variant.fields.iter(), ctxt.tcx.param_env(variant.def_id))
+                    let param_env = if is_instantiation { ParamEnv::reveal_all() } else { ctxt.tcx.param_env(variant.def_id) };
+                    process_fields(ctxt.tcx, &ctxt.meta_loader, &ctxt.reflect_types, &ctxt.cached_traits, variant.fields.iter(), ty_args, param_env)
                 }).collect::<Vec<_>>();
 
                 strats.iter().for_each(|(f_did, strat)| match strat {
@@ -44,19 +62,20 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
                     _ => {}
                 });
 
-                let ty_ctxt = ctxt.reflect_types.get_mut(&def_id).unwrap();
+                let ty_ctxt = ctxt.reflect_types.get_mut(&ty).unwrap();
                 ty_ctxt.variant_data = Some(adt_def);
                 ty_ctxt.set_field_reflection_strategies(strats.into_iter());
 
             },
             AdtKind::Struct => {
-                let fields = process_fields(ctxt.tcx, &ctxt.meta_loader, &ctxt.reflect_types,&ctxt.cached_traits, adt_def.all_fields(), ctxt.tcx.param_env(def_id));
+                let param_env = if is_instantiation { ParamEnv::reveal_all() } else { ctxt.tcx.param_env(def_id) };
+                let fields = process_fields(ctxt.tcx, &ctxt.meta_loader, &ctxt.reflect_types,&ctxt.cached_traits, adt_def.all_fields(), ty_args, param_env);
                 fields.iter().for_each(|(f_did, strat)| match strat {
                     ReflectionStrategy::Reflection => report_field_not_supported(ctxt.tcx, *f_did, def_id, None, "type is neither a proxy nor a type expressible as lua primitive"),
                     ReflectionStrategy::Filtered => report_field_not_supported(ctxt.tcx, *f_did, def_id, None, "field has a 'reflect(ignore)' attribute"),
                     _ => {}
                 });
-                let ty_ctxt = ctxt.reflect_types.get_mut(&def_id).unwrap();
+                let ty_ctxt = ctxt.reflect_types.get_mut(&ty).unwrap();
                 assert!(ty_ctxt.variant_data.is_none(), "variant data already set!");
                 ty_ctxt.variant_data = Some(adt_def);
                 ty_ctxt.set_field_reflection_strategies(fields.into_iter());
@@ -66,7 +85,7 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
 
         // borrow checker fucky wucky pt2
         let trait_impls_for_ty = {
-            let ty_ctxt = ctxt.reflect_types.get(&def_id).unwrap();
+            let ty_ctxt = ctxt.reflect_types.get(&ty).unwrap();
             ty_ctxt.trait_impls.as_ref()
                 .expect("A type was not processed correctly in a previous pass, missing trait impl info")
                 .values()
@@ -75,7 +94,7 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
         };
 
         // should we not find functions set default value for future passes
-        let ty_ctxt = ctxt.reflect_types.get_mut(&def_id).unwrap();
+        let ty_ctxt = ctxt.reflect_types.get_mut(&ty).unwrap();
         assert!(
             ty_ctxt.valid_functions.is_none(),
             "valid functions already set!"
@@ -95,6 +114,16 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
         all_impls.sort_by_cached_key(|a| ctxt.tcx.def_path_hash(**a));
 
         for impl_did in all_impls {
+            // impls on generic types may only apply to some instantiations, i.e. `impl Vec<u8>`
+            let impl_args = if is_instantiation {
+                match impl_args_for_instantiation(ctxt.tcx, *impl_did, ty) {
+                    Some(impl_args) => Some(impl_args),
+                    None => continue,
+                }
+            } else {
+                None
+            };
+
             let functions = ctxt
                 .tcx
                 .associated_items(impl_did)
@@ -117,10 +146,47 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
                         ctxt.tcx.item_name(def_id)
                     );
 
-                    let param_env = ctxt.tcx.param_env(fn_did);
+                    if !args.file_config.allows_method(
+                        crate_name.as_str(),
+                        &config_type_name,
+                        &fn_name,
+                    ) {
+                        log::info!(
+                            "Skipping function: `{}` on type: `{}`, excluded in the config file",
+                            fn_name,
+                            ctxt.tcx.item_name(def_id)
+                        );
+                        return None;
+                    }
+
+                    let (param_env, fn_args) = match impl_args {
+                        Some(impl_args) => {
+                            let own_counts = ctxt.tcx.generics_of(fn_did).own_counts();
+                            if own_counts.types > 0 || own_counts.consts > 0 {
+                                log::info!(
+                                    "Skipping generic function: `{}` on instantiation: `{}`",
+                                    fn_name,
+                                    ty
+                                );
+                                return None;
+                            }
+                            // the only parameters left are the lifetimes of the function itself, which are erased
+                            let fn_args = GenericArgs::for_item(ctxt.tcx, fn_did, |param, _| {
+                                impl_args
+                                    .get(param.index as usize)
+                                    .copied()
+                                    .unwrap_or_else(|| ctxt.tcx.lifetimes.re_erased.into())
+                            });
+                            (ParamEnv::reveal_all(), fn_args)
+                        }
+                        None => (
+                            ctxt.tcx.param_env(fn_did),
+                            GenericArgs::identity_for_item(ctxt.tcx, fn_did),
+                        ),
+                    };
                     let sig: FnSig = ctxt.tcx.normalize_erasing_late_bound_regions(
                         param_env,
-                        ctxt.tcx.fn_sig(fn_did).instantiate_identity(),
+                        ctxt.tcx.fn_sig(fn_did).instantiate(ctxt.tcx, fn_args),
                     );
 
                     if let Some(unstability) = ctxt.tcx.lookup_stability(fn_did) {
@@ -204,6 +270,7 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
                     Some(FunctionContext {
                         is_unsafe,
                         def_id: fn_did,
+                        args: fn_args,
                         has_self,
                         trait_did,
                         reflection_strategies,
@@ -211,7 +278,7 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
                 })
                 .collect::<Vec<_>>();
 
-            let ty_ctxt = ctxt.reflect_types.get_mut(&def_id).unwrap();
+            let ty_ctxt = ctxt.reflect_types.get_mut(&ty).unwrap();
             // must exist since we set default above
             ty_ctxt.valid_functions.as_mut().unwrap().extend(functions);
         }
@@ -220,6 +287,37 @@ pub(crate) fn find_methods_and_fields(ctxt: &mut BevyCtxt<'_>, _args: &Args) ->
     true
 }
 
+/// Finds the generic arguments of an impl block which make its self type equal to the given instantiation,
+/// returns None if the impl does not apply to the instantiation
+fn impl_args_for_instantiation<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    impl_did: DefId,
+    ty: Ty<'tcx>,
+) -> Option<GenericArgsRef<'tcx>> {
+    let infcx = tcx.infer_ctxt().build();
+    let ocx = ObligationCtxt::new(&infcx);
+    let param_env = ParamEnv::reveal_all();
+    let impl_args = infcx.fresh_args_for_item(DUMMY_SP, impl_did);
+    let self_ty = tcx.type_of(impl_did).instantiate(tcx, impl_args);
+    let self_ty = ocx.normalize(&ObligationCause::dummy(), param_env, self_ty);
+    ocx.eq(&ObligationCause::dummy(), param_env, self_ty, ty)
+        .ok()?;
+
+    let impl_predicates = tcx.predicates_of(impl_did).instantiate(tcx, impl_args);
+    ocx.register_obligations(
+        impl_predicates
+            .predicates
+            .into_iter()
+            .map(|predicate| Obligation::new(tcx, ObligationCause::dummy(), param_env, predicate)),
+    );
+    if !ocx.select_all_or_error().is_empty() {
+        return None;
+    }
+
+    let impl_args = infcx.resolve_vars_if_possible(impl_args);
+    (!impl_args.has_non_region_infer()).then(|| tcx.erase_regions(impl_args))
+}
+
 fn report_fn_arg_not_supported(tcx: TyCtxt, f_did: DefId, type_did: DefId, ty: Ty, reason: &str) {
     info!(
         "Ignoring function: `{}` on type: `{}` reason: `{}`, relevant type: `{}`",
@@ -255,9 +353,10 @@ fn report_field_not_supported(
 fn process_fields<'tcx, 'f, I: Iterator<Item = &'f FieldDef>>(
     tcx: TyCtxt<'tcx>,
     meta_loader: &MetaLoader,
-    reflect_types: &IndexMap<DefId, ReflectType<'tcx>>,
+    reflect_types: &IndexMap<Ty<'tcx>, ReflectType<'tcx>>,
     cached_traits: &CachedTraits,
     fields: I,
+    args: GenericArgsRef<'tcx>,
     param_env: ParamEnv<'tcx>,
 ) -> Vec<(DefId, ReflectionStrategy)> {
     fields
@@ -266,7 +365,7 @@ fn process_fields<'tcx, 'f, I: Iterator<Item = &'f FieldDef>>(
                 return (f.did, crate::ReflectionStrategy::Filtered);
             }
 
-            let field_ty = tcx.erase_regions(tcx.type_of(f.did).instantiate_identity());
+            let field_ty = tcx.erase_regions(f.ty(tcx, args));
             if type_is_supported_as_non_proxy_arg(tcx, param_env, cached_traits, field_ty)
                 && type_is_supported_as_non_proxy_return_val(
                     tcx,
@@ -302,9 +401,9 @@ fn has_reflect_ignore_attr(attrs: &[Attribute]) -> bool {
 /// Returns true if this type can be used in argument position by checking if it's a top level proxy arg
 fn type_is_supported_as_proxy_arg<'tcx>(
     tcx: TyCtxt<'tcx>,
-    reflect_types: &IndexMap<DefId, ReflectType<'tcx>>,
+    reflect_types: &IndexMap<Ty<'tcx>, ReflectType<'tcx>>,
     meta_loader: &MetaLoader,
-    ty: Ty,
+    ty: Ty<'tcx>,
 ) -> bool {
     log::trace!("Checking type is supported as proxy arg: '{}'", ty);
     type_is_adt_and_reflectable(tcx, reflect_types, meta_loader, ty.peel_refs())
@@ -313,9 +412,9 @@ fn type_is_supported_as_proxy_arg<'tcx>(
 /// Returns true if this type can be used in return position by checking if it's a top level proxy arg without references
 fn type_is_supported_as_proxy_return_val<'tcx>(
     tcx: TyCtxt<'tcx>,
-    reflect_types: &IndexMap<DefId, ReflectType<'tcx>>,
+    reflect_types: &IndexMap<Ty<'tcx>, ReflectType<'tcx>>,
     meta_loader: &MetaLoader,
-    ty: Ty,
+    ty: Ty<'tcx>,
 ) -> bool {
     log::trace!("Checking type is supported as proxy return val: '{}'", ty);
     type_is_adt_and_reflectable(tcx, reflect_types, meta_loader, ty)
@@ -324,14 +423,19 @@ fn type_is_supported_as_proxy_return_val<'tcx>(
 /// Check if the type is an ADT and is reflectable (i.e. a proxy is being generated for it in SOME crate that we know about from the meta files)
 fn type_is_adt_and_reflectable<'tcx>(
     tcx: TyCtxt<'tcx>,
-    reflect_types: &IndexMap<DefId, ReflectType<'tcx>>,
+    reflect_types: &IndexMap<Ty<'tcx>, ReflectType<'tcx>>,
     meta_loader: &MetaLoader,
-    ty: Ty,
+    ty: Ty<'tcx>,
 ) -> bool {
     ty.ty_adt_def().is_some_and(|adt_def| {
         let did = adt_def.did();
 
-        if reflect_types.contains_key(&did) {
+        if matches!(ty.kind(), TyKind::Adt(_, args) if args.types().next().is_some()) {
+            // instantiations of generic types are only proxied if listed in the config file
+            return find_instantiation(tcx, reflect_types, meta_loader, ty).is_some();
+        }
+
+        if reflect_types.contains_key(&ty) {
             // local types are easy to check
             return true;
         }
@@ -1,11 +1,21 @@
-use log::{debug, info};
-use rustc_hir::def_id::LOCAL_CRATE;
+use log::{debug, info, warn};
+use rustc_hir::{
+    def::DefKind,
+    def_id::{DefId, LOCAL_CRATE},
+};
+use rustc_middle::ty::{GenericArgs, GenericParamDefKind, ParamEnv, Ty, TyCtxt};
+use rustc_span::Symbol;
 
-use crate::{Args, BevyCtxt, ReflectType, DEF_PATHS_REFLECT};
+use super::find_methods_and_fields::impls_trait;
+use crate::{
+    proxy_ident, Args, BevyCtxt, ReflectType, TypePattern, DEF_PATHS_REFLECT,
+};
 
 /// Finds all reflect types which we can wrap in the crate as well as sorts the final list.
 pub(crate) fn find_reflect_types(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bool {
     let tcx = &ctxt.tcx;
+    let config = &args.file_config;
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
     let ignored_types = match &args.cmd {
         crate::Command::Generate { ignored_types, .. } => ignored_types,
         _ => return true,
@@ -44,7 +54,8 @@ pub(crate) fn find_reflect_types(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bool {
                 // only non parametrized simple types are allowed, i.e. "MyStruct" is allowed but "MyStruct<T>" isn't
                     generics.count() == 0 &&
                     self_ty.def().is_some_and(|did| {
-                            if ignored_types.contains(&format!("{}::{}",ctxt.tcx.crate_name(LOCAL_CRATE),ctxt.tcx.item_name(did))) {
+                            if ignored_types.contains(&format!("{}::{}",crate_name,ctxt.tcx.item_name(did)))
+                                || !config.allows_type(crate_name.as_str(), ctxt.tcx.item_name(did).as_str()) {
                                 info!("Ignoring type: {:?}", tcx.def_path_str(did));
                                 return false;
                             };
@@ -54,20 +65,190 @@ pub(crate) fn find_reflect_types(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bool {
                 .then(|| self_ty.def().unwrap())
             })
             .inspect(|impl_| debug!("On type: {:?}", tcx.item_name(*impl_)))
-            .map(|did| (did, ReflectType::default()));
+            .map(|did| {
+                let reflect_ty = ReflectType {
+                    rename: config.rename(crate_name.as_str(), tcx.item_name(did).as_str()),
+                    ..Default::default()
+                };
+                (tcx.type_of(did).instantiate_identity(), reflect_ty)
+            });
 
         ctxt.reflect_types.extend(reflect_adts_did);
     }
 
+    // also contains the instantiations of the deprecated generics file
+    for instantiation in &config.instantiations {
+        let pattern = match instantiation.pattern() {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                warn!("Ignoring instantiation: {e}");
+                continue;
+            }
+        };
+
+        // each instantiation is generated by exactly one crate, the one defining one of the types inside which can also name the others
+        let Some(ty) = resolve_type(*tcx, &pattern) else {
+            continue;
+        };
+        let adts = ty
+            .walk()
+            .filter_map(|arg| arg.as_type())
+            .filter_map(|ty| ty.ty_adt_def())
+            .collect::<Vec<_>>();
+        if !adts.iter().any(|adt_def| adt_def.did().is_local()) {
+            continue;
+        }
+
+        if !adts
+            .iter()
+            .all(|adt_def| tcx.visibility(adt_def.did()).is_public())
+            || !impls_trait(
+                *tcx,
+                ParamEnv::reveal_all(),
+                ty,
+                ctxt.cached_traits.bevy_reflect_reflect.unwrap(),
+            )
+        {
+            warn!(
+                "Ignoring instantiation: `{}`, it is not a public type implementing `Reflect`",
+                instantiation.ty
+            );
+            continue;
+        }
+
+        debug!("On instantiation: {:?}", ty);
+        ctxt.reflect_types.insert(
+            ty,
+            ReflectType {
+                instantiation_ident: Some(instantiation.ident(&pattern)),
+                ..Default::default()
+            },
+        );
+    }
+
+    // interning keeps the order of non-generic types the same as sorting by their item names
     ctxt.reflect_types
-        .sort_by_cached_key(|did, _| tcx.item_name(*did));
+        .sort_by_cached_key(|ty, reflect_ty| Symbol::intern(&proxy_ident(*tcx, *ty, reflect_ty)));
 
     if args.cmd.is_list_types() {
-        for did in ctxt.reflect_types.keys() {
-            println!("{:?}", tcx.def_path_str(did));
+        for (ty, reflect_ty) in ctxt.reflect_types.iter() {
+            if reflect_ty.instantiation_ident.is_some() {
+                println!("{:?}", ty.to_string());
+            } else {
+                println!("{:?}", tcx.def_path_str(ty.ty_adt_def().unwrap().did()));
+            }
         }
         return false;
     }
 
     true
 }
+
+/// Resolves an instantiated type from the config file to a type in the crate, returns None if any of the paths inside cannot be named from this crate
+fn resolve_type<'tcx>(tcx: TyCtxt<'tcx>, pattern: &TypePattern) -> Option<Ty<'tcx>> {
+    if pattern.args.is_empty() {
+        if let Some(ty) = primitive_type(tcx, &pattern.path) {
+            return Some(ty);
+        }
+    }
+
+    let did = resolve_path(tcx, &pattern.path)?;
+    let args = pattern
+        .args
+        .iter()
+        .map(|arg| resolve_type(tcx, arg))
+        .collect::<Option<Vec<_>>>()?;
+
+    let generics = tcx.generics_of(did);
+    let counts = generics.own_counts();
+    let required = generics
+        .params
+        .iter()
+        .filter(|param| {
+            matches!(
+                param.kind,
+                GenericParamDefKind::Type {
+                    has_default: false,
+                    ..
+                }
+            )
+        })
+        .count();
+    if counts.consts > 0 || args.len() < required || args.len() > counts.types {
+        warn!(
+            "Ignoring instantiation of: `{}`, expected between {required} and {} type arguments without any const generics",
+            pattern.path, counts.types
+        );
+        return None;
+    }
+
+    // lifetimes are erased and type parameters not given fall back to their defaults, i.e. the allocator of a `Vec`
+    let mut args = args.into_iter();
+    let args = GenericArgs::for_item(tcx, did, |param, previous| match param.kind {
+        GenericParamDefKind::Lifetime => tcx.lifetimes.re_erased.into(),
+        GenericParamDefKind::Type { .. } => args
+            .next()
+            .map(Into::into)
+            .unwrap_or_else(|| tcx.type_of(param.def_id).instantiate(tcx, previous).into()),
+        GenericParamDefKind::Const { .. } => unreachable!("const generics are rejected above"),
+    });
+
+    Some(Ty::new_adt(tcx, tcx.adt_def(did), args))
+}
+
+/// Resolves a full path to an ADT by walking the public module tree starting at the crate named by the first segment, re-exports are followed
+fn resolve_path(tcx: TyCtxt<'_>, path: &str) -> Option<DefId> {
+    let mut segments = path.split("::");
+    let crate_name = segments.next()?;
+    let mut did = if tcx.crate_name(LOCAL_CRATE).as_str() == crate_name {
+        LOCAL_CRATE.as_def_id()
+    } else {
+        tcx.crates(())
+            .iter()
+            .find(|krate| tcx.crate_name(**krate).as_str() == crate_name)?
+            .as_def_id()
+    };
+
+    for segment in segments {
+        if !matches!(tcx.def_kind(did), DefKind::Mod) {
+            return None;
+        }
+        let children = if did.is_local() {
+            tcx.module_children_local(did.expect_local())
+        } else {
+            tcx.module_children(did)
+        };
+        did = children
+            .iter()
+            .filter(|child| child.vis.is_public() && child.ident.as_str() == segment)
+            .find_map(|child| child.res.opt_def_id())?;
+    }
+
+    matches!(
+        tcx.def_kind(did),
+        DefKind::Struct | DefKind::Enum | DefKind::Union
+    )
+    .then_some(did)
+}
+
+fn primitive_type<'tcx>(tcx: TyCtxt<'tcx>, path: &str) -> Option<Ty<'tcx>> {
+    Some(match path {
+        "bool" => tcx.types.bool,
+        "char" => tcx.types.char,
+        "u8" => tcx.types.u8,
+        "u16" => tcx.types.u16,
+        "u32" => tcx.types.u32,
+        "u64" => tcx.types.u64,
+        "u128" => tcx.types.u128,
+        "usize" => tcx.types.usize,
+        "i8" => tcx.types.i8,
+        "i16" => tcx.types.i16,
+        "i32" => tcx.types.i32,
+        "i64" => tcx.types.i64,
+        "i128" => tcx.types.i128,
+        "isize" => tcx.types.isize,
+        "f32" => tcx.types.f32,
+        "f64" => tcx.types.f64,
+        _ => return None,
+    })
+}
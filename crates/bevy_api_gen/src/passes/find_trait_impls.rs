@@ -16,7 +16,7 @@ use crate::{Args, BevyCtxt};
 pub(crate) fn find_trait_impls(ctxt: &mut BevyCtxt<'_>, _args: &Args) -> bool {
     let tcx = &ctxt.tcx;
 
-    ctxt.reflect_types.retain(|reflect_ty_did, _| {
+    ctxt.reflect_types.retain(|reflect_ty, _| {
         // first filter out those without GetTypeRegistration traits
         // TODO: this is working partially,
         // some types even though they clearly implement GetTypeRegistration are not being picked up
@@ -34,20 +34,20 @@ pub(crate) fn find_trait_impls(ctxt: &mut BevyCtxt<'_>, _args: &Args) -> bool {
         let retaining = type_impl_of_trait(
             tcx,
             ctxt.cached_traits.mlua_from_lua_multi.unwrap(),
-            reflect_ty_did,
+            *reflect_ty,
         )
         .is_empty()
             || type_impl_of_trait(
                 tcx,
                 ctxt.cached_traits.mlua_into_lua_multi.unwrap(),
-                reflect_ty_did,
+                *reflect_ty,
             )
             .is_empty();
 
         if !retaining {
             trace!(
                 "Type: `{:?}` implements both from and into lua, removing from reflect types",
-                tcx.def_path_str(*reflect_ty_did)
+                reflect_ty
             );
         }
         retaining
@@ -63,11 +63,11 @@ pub(crate) fn find_trait_impls(ctxt: &mut BevyCtxt<'_>, _args: &Args) -> bool {
             .join(", ")
     );
 
-    for (reflect_ty_did, type_ctxt) in ctxt.reflect_types.iter_mut() {
+    for (reflect_ty, type_ctxt) in ctxt.reflect_types.iter_mut() {
         let mut impls = Vec::default();
 
         for trait_did in ctxt.cached_traits.std_source_traits.values() {
-            let matching_impls = type_impl_of_trait(tcx, *trait_did, reflect_ty_did);
+            let matching_impls = type_impl_of_trait(tcx, *trait_did, *reflect_ty);
             if !matching_impls.is_empty() {
                 impls.push((*trait_did, matching_impls));
             }
@@ -80,50 +80,45 @@ pub(crate) fn find_trait_impls(ctxt: &mut BevyCtxt<'_>, _args: &Args) -> bool {
 }
 
 /// Checks if a type implements a trait, returns all implementations with the generic args required
-fn type_impl_of_trait(
-    tcx: &rustc_middle::ty::TyCtxt<'_>,
+fn type_impl_of_trait<'tcx>(
+    tcx: &rustc_middle::ty::TyCtxt<'tcx>,
     trait_did: DefId,
-    reflect_ty_did: &rustc_hir::def_id::DefId,
+    ty: Ty<'tcx>,
 ) -> Vec<DefId> {
     log::trace!(
         "Finding impl for trait: {:?} on type: {:?}",
         tcx.def_path_str(trait_did),
-        tcx.def_path_str(*reflect_ty_did)
+        ty
     );
     let mut out = Vec::default();
 
-    tcx.for_each_relevant_impl(
-        trait_did,
-        tcx.type_of(reflect_ty_did).instantiate_identity(),
-        |impl_did| {
+    tcx.for_each_relevant_impl(trait_did, ty, |impl_did| {
+        trace!(
+            "Possible impl for trait: {:?} on type: {:?} found: {:?}",
+            tcx.def_path_str(trait_did),
+            ty,
+            impl_did,
+        );
+        //TODO: false negatives coming from this inference
+
+        let infcx = tcx.infer_ctxt().build();
+        let result = impl_matches(&infcx, ty, impl_did);
+        log::trace!("Result: {:#?}", result);
+        if result {
             trace!(
-                "Possible impl for trait: {:?} on type: {:?} found: {:?}",
-                tcx.def_path_str(trait_did),
-                tcx.def_path_str(reflect_ty_did),
-                impl_did,
+                "Type: `{}` implements trait: `{}`",
+                ty,
+                tcx.item_name(trait_did)
             );
-            //TODO: false negatives coming from this inference
-
-            let ty = tcx.type_of(reflect_ty_did).instantiate_identity();
-            let infcx = tcx.infer_ctxt().build();
-            let result = impl_matches(&infcx, ty, impl_did);
-            log::trace!("Result: {:#?}", result);
-            if result {
-                trace!(
-                    "Type: `{}` implements trait: `{}`",
-                    ty,
-                    tcx.item_name(trait_did)
-                );
-                out.push(impl_did)
-            } else {
-                trace!(
-                    "Type: `{}` does not implement trait: `{}`",
-                    ty,
-                    tcx.item_name(trait_did)
-                );
-            }
-        },
-    );
+            out.push(impl_did)
+        } else {
+            trace!(
+                "Type: `{}` does not implement trait: `{}`",
+                ty,
+                tcx.item_name(trait_did)
+            );
+        }
+    });
     out
 }
 
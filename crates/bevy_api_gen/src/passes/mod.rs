@@ -18,7 +18,7 @@ pub(crate) struct Pass {
 
 pub(crate) const FIND_REFLECT_TYPES: Pass = Pass {
     name: "Find Reflect Types",
-    description: "Finding all non-generic, public and reflectable types in the crate",
+    description: "Finding all non-generic, public and reflectable types in the crate, as well as the instantiations of generic types listed in the config file",
     cb: find_reflect_types::find_reflect_types,
 };
 
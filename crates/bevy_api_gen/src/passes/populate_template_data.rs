@@ -2,13 +2,18 @@ use std::fmt::Write;
 
 use log::trace;
 use rustc_ast::Attribute;
-use rustc_hir::def_id::{DefId, LOCAL_CRATE};
-use rustc_middle::ty::{FieldDef, ParamTy, Ty, TyKind, TypeFoldable};
+use rustc_hir::{
+    def::CtorKind,
+    def_id::{DefId, LOCAL_CRATE},
+};
+use rustc_middle::ty::{FieldDef, GenericArgsRef, ParamEnv, ParamTy, Ty, TyKind, TypeFoldable};
 use rustc_span::Symbol;
 
+use super::find_methods_and_fields::impls_trait;
 use crate::{
-    Arg, Args, BevyCtxt, Field, Function, FunctionContext, ImportPathFinder, Item, Output,
-    ReflectType, TemplateContext, Variant,
+    alias_ident, find_instantiation, find_rename, instantiation_alias_path, proxy_ident, Arg, Args,
+    BevyCtxt, Field, Function, FunctionContext, Item, Output, ReflectType, TemplateContext,
+    Variant,
 };
 /// Converts the BevyCtxt into simpler data that can be used in templates directly,
 /// Clears the BevyCtxt by clearing data structures after it uses them.
@@ -21,7 +26,14 @@ pub(crate) fn populate_template_data(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bo
     let debug_diagnostic = tcx.get_diagnostic_item(Symbol::intern("Debug")).unwrap();
     let display_diagnostic = tcx.get_diagnostic_item(Symbol::intern("Display")).unwrap();
 
-    for (reflect_ty_did, ty_ctxt) in ctxt.reflect_types.drain(..).collect::<Vec<_>>().into_iter() {
+    // the types are kept around until all items are built, so instantiations can be looked up when printing types
+    for (reflect_ty, ty_ctxt) in ctxt.reflect_types.clone().into_iter() {
+        let TyKind::Adt(adt_def, ty_args) = *reflect_ty.kind() else {
+            panic!(
+                "Unexpected type, all `Reflect` implementing types should be ADTs: {reflect_ty:?}"
+            );
+        };
+        let reflect_ty_did = adt_def.did();
         let fn_ctxts = ty_ctxt
             .valid_functions
             .as_ref()
@@ -29,7 +41,7 @@ pub(crate) fn populate_template_data(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bo
 
         let has_static_methods = fn_ctxts.iter().any(|fn_ctxt| !fn_ctxt.has_self);
 
-        let functions = process_functions(ctxt, fn_ctxts);
+        let functions = process_functions(ctxt, fn_ctxts, &ty_ctxt);
         let variant = ty_ctxt.variant_data.as_ref().unwrap();
 
         let is_tuple_struct = variant.is_struct()
@@ -44,16 +56,25 @@ pub(crate) fn populate_template_data(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bo
             .map(|variant| Variant {
                 docstrings: docstrings(ctxt.tcx.get_attrs_unchecked(variant.def_id)),
                 name: variant.name.to_ident_string().into(),
-                fields: process_fields(ctxt, variant.fields.iter(), &ty_ctxt),
+                is_tuple: variant.ctor_kind() == Some(CtorKind::Fn),
+                fields: process_fields(ctxt, variant.fields.iter(), ty_args, &ty_ctxt),
             })
             .collect::<Vec<_>>();
         let trait_impls = ty_ctxt.trait_impls.as_ref().unwrap();
+        let (import_path, instantiation) = match alias_ident(&ty_ctxt) {
+            Some(ident) => (
+                instantiation_alias_path(tcx.crate_name(LOCAL_CRATE).as_str(), &ident),
+                Some(TyPrinter::new(ctxt).print_instantiation(reflect_ty)),
+            ),
+            None => (import_path(ctxt, reflect_ty_did), None),
+        };
         let item = Item {
-            ident: tcx.item_name(reflect_ty_did).to_ident_string(),
-            import_path: import_path(ctxt, reflect_ty_did),
+            ident: proxy_ident(*tcx, reflect_ty, &ty_ctxt),
+            import_path,
+            instantiation,
             has_static_methods,
             functions,
-            is_enum: variants.len() > 1,
+            is_enum: variant.is_enum(),
             variants,
             is_tuple_struct,
             docstrings: docstrings(tcx.get_attrs_unchecked(reflect_ty_did)),
@@ -117,27 +138,44 @@ pub(crate) fn populate_template_data(ctxt: &mut BevyCtxt<'_>, args: &Args) -> bo
     true
 }
 
-pub(crate) fn process_fields<'f, I: Iterator<Item = &'f FieldDef>>(
-    ctxt: &BevyCtxt,
+pub(crate) fn process_fields<'tcx, 'f, I: Iterator<Item = &'f FieldDef>>(
+    ctxt: &BevyCtxt<'tcx>,
     fields: I,
-    ty_ctxt: &ReflectType,
+    args: GenericArgsRef<'tcx>,
+    ty_ctxt: &ReflectType<'tcx>,
 ) -> Vec<Field> {
     fields
-        .map(|field| Field {
-            docstrings: docstrings(ctxt.tcx.get_attrs_unchecked(field.did)),
-            ident: field.name.to_ident_string(),
-            ty: ty_to_string(ctxt, ctxt.tcx.type_of(field.did).skip_binder()),
-            reflection_strategy: *ty_ctxt
-                .get_field_reflection_strat(field.did)
-                .unwrap_or_else(|| panic!("{ty_ctxt:#?}")),
+        .map(|field| {
+            let ty = field.ty(ctxt.tcx, args);
+            Field {
+                docstrings: docstrings(ctxt.tcx.get_attrs_unchecked(field.did)),
+                ident: field.name.to_ident_string(),
+                ty: ty_to_string(ctxt, ty),
+                reflection_strategy: *ty_ctxt
+                    .get_field_reflection_strat(field.did)
+                    .unwrap_or_else(|| panic!("{ty_ctxt:#?}")),
+                impls_clone: impls_clone(
+                    ctxt,
+                    param_env(ctxt, ty_ctxt, ctxt.tcx.parent(field.did)),
+                    ty,
+                ),
+            }
         })
         .collect()
 }
 
-pub(crate) fn process_functions(ctxt: &BevyCtxt, fns: &[FunctionContext]) -> Vec<Function> {
+pub(crate) fn process_functions<'tcx>(
+    ctxt: &BevyCtxt<'tcx>,
+    fns: &[FunctionContext<'tcx>],
+    ty_ctxt: &ReflectType<'tcx>,
+) -> Vec<Function> {
     fns.iter()
         .map(|fn_ctxt| {
-            let fn_sig = ctxt.tcx.fn_sig(fn_ctxt.def_id).skip_binder().skip_binder();
+            let fn_sig = ctxt
+                .tcx
+                .fn_sig(fn_ctxt.def_id)
+                .instantiate(ctxt.tcx, fn_ctxt.args)
+                .skip_binder();
             let args = ctxt
                 .tcx
                 .fn_arg_names(fn_ctxt.def_id)
@@ -145,11 +183,16 @@ pub(crate) fn process_functions(ctxt: &BevyCtxt, fns: &[FunctionContext]) -> Vec
                 .zip(fn_sig.inputs())
                 .enumerate()
                 .map(|(idx, (ident, ty))| {
+                    let param_env = param_env(ctxt, ty_ctxt, fn_ctxt.def_id);
+                    let arg_impls_clone = impls_clone(
+                        ctxt,
+                        param_env,
+                        ctxt.tcx.normalize_erasing_regions(param_env, *ty),
+                    );
                     let (ident, ty) = if fn_ctxt.has_self && idx == 0 {
                         // self argument, we want to map to something like `&self` instead of `&Component`
                         // we do that by renaming every adt inside to "self"
-                        // this is a bit hacky but it works, might not work if we decide to support generics in the future
-                        // TODO: fix to work with generics
+                        // this is a bit hacky but it works, the arguments of instantiations are folded into "self" along with the type itself
                         let ty = ty.fold_with(&mut rustc_middle::ty::fold::BottomUpFolder {
                             tcx: ctxt.tcx,
                             ty_op: |ty| {
@@ -170,28 +213,26 @@ pub(crate) fn process_functions(ctxt: &BevyCtxt, fns: &[FunctionContext]) -> Vec
                         (ident.to_string().into(), *ty)
                     };
                     // remove projections like `<Struct as Trait>::AssocType`
-                    let ty = ty_to_string(
-                        ctxt,
-                        ctxt.tcx
-                            .normalize_erasing_regions(ctxt.tcx.param_env(fn_ctxt.def_id), ty),
-                    );
+                    let ty = ty_to_string(ctxt, ctxt.tcx.normalize_erasing_regions(param_env, ty));
                     Arg {
                         ident,
                         ty,
                         reflection_strategy: fn_ctxt.reflection_strategies[idx],
+                        impls_clone: arg_impls_clone,
                     }
                 })
                 .collect();
 
-            let ty = ty_to_string(
-                ctxt,
-                ctxt.tcx
-                    .normalize_erasing_regions(ctxt.tcx.param_env(fn_ctxt.def_id), fn_sig.output()),
-            );
+            let param_env = param_env(ctxt, ty_ctxt, fn_ctxt.def_id);
+            let output_ty = ctxt
+                .tcx
+                .normalize_erasing_regions(param_env, fn_sig.output());
+            let ty = ty_to_string(ctxt, output_ty);
 
             let output = Output {
                 ty,
                 reflection_strategy: *fn_ctxt.reflection_strategies.last().unwrap(),
+                impls_clone: impls_clone(ctxt, param_env, output_ty),
             };
 
             let is_unsafe = fn_ctxt.is_unsafe;
@@ -211,6 +252,34 @@ pub(crate) fn process_functions(ctxt: &BevyCtxt, fns: &[FunctionContext]) -> Vec
         .collect()
 }
 
+/// The environment in which types on the proxied type are checked,
+/// instantiations of generic types are fully concrete so they do not need the environment of the generic item
+fn param_env<'tcx>(
+    ctxt: &BevyCtxt<'tcx>,
+    ty_ctxt: &ReflectType<'tcx>,
+    did: DefId,
+) -> ParamEnv<'tcx> {
+    if ty_ctxt.instantiation_ident.is_some() {
+        ParamEnv::reveal_all()
+    } else {
+        ctxt.tcx.param_env(did)
+    }
+}
+
+/// Checks if the type, or the type behind any references, implements `Clone`
+fn impls_clone<'tcx>(ctxt: &BevyCtxt<'tcx>, param_env: ParamEnv<'tcx>, ty: Ty<'tcx>) -> bool {
+    let clone_diagnostic = ctxt
+        .tcx
+        .get_diagnostic_item(Symbol::intern("Clone"))
+        .unwrap();
+    impls_trait(
+        ctxt.tcx,
+        param_env,
+        ctxt.tcx.erase_regions(ty.peel_refs()),
+        clone_diagnostic,
+    )
+}
+
 /// extracts and normalizes docstrings in a given list of attributes
 pub(crate) fn docstrings(attrs: &[Attribute]) -> Vec<String> {
     attrs
@@ -238,29 +307,56 @@ pub(crate) fn import_path(ctxt: &BevyCtxt, def_id: DefId) -> String {
 /// Normalizes type import paths in types before printing them
 fn ty_to_string<'tcx>(ctxt: &BevyCtxt<'tcx>, ty: Ty<'tcx>) -> String {
     // walk through the type and replace all paths with their standardised import paths
-    TyPrinter::new().print(&ctxt.path_finder, ty)
+    TyPrinter::new(ctxt).print(ty)
 }
 
-struct TyPrinter {
+struct TyPrinter<'a, 'tcx> {
+    ctxt: &'a BevyCtxt<'tcx>,
     buffer: String,
 }
 
-impl TyPrinter {
-    pub fn new() -> Self {
+impl<'a, 'tcx> TyPrinter<'a, 'tcx> {
+    pub fn new(ctxt: &'a BevyCtxt<'tcx>) -> Self {
         TyPrinter {
+            ctxt,
             buffer: String::new(),
         }
     }
-    pub fn print(mut self, path_finder: &ImportPathFinder, ty: Ty<'_>) -> String {
-        self.build_str(path_finder, ty);
+
+    pub fn print(mut self, ty: Ty<'tcx>) -> String {
+        self.build_str(ty, true);
+        self.buffer
+    }
+
+    /// Prints the full type of an instantiation or a renamed type, without replacing the type itself with its alias
+    pub fn print_instantiation(mut self, ty: Ty<'tcx>) -> String {
+        self.build_str(ty, false);
         self.buffer
     }
 
-    fn build_str(&mut self, path_finder: &ImportPathFinder, ty: Ty<'_>) {
+    fn build_str(&mut self, ty: Ty<'tcx>, use_alias: bool) {
         match ty.kind() {
             TyKind::Adt(adt_def, args) => {
+                // instantiations of generic types with a proxy are printed as their alias, so they can be told apart from other instantiations,
+                // renamed types are printed as their alias so the proxy is named after it
+                if use_alias {
+                    let ctxt = self.ctxt;
+                    if let Some((crate_name, ident)) =
+                        find_instantiation(ctxt.tcx, &ctxt.reflect_types, &ctxt.meta_loader, ty)
+                            .or_else(|| {
+                                find_rename(ctxt.tcx, &ctxt.reflect_types, &ctxt.meta_loader, ty)
+                            })
+                    {
+                        self.buffer
+                            .push_str(&instantiation_alias_path(&crate_name, &ident));
+                        return;
+                    }
+                }
+
                 let did = adt_def.did();
-                let import_path = path_finder
+                let import_path = self
+                    .ctxt
+                    .path_finder
                     .find_import_paths(did)
                     .first()
                     .unwrap()
@@ -270,7 +366,7 @@ impl TyPrinter {
                     self.buffer.push('<');
                     for (idx, a) in args.iter().enumerate() {
                         match a.as_type() {
-                            Some(ty) => self.build_str(path_finder, ty),
+                            Some(ty) => self.build_str(ty, true),
                             None => _ = self.buffer.write_str(&a.to_string()),
                         }
                         if idx != args.len() - 1 {
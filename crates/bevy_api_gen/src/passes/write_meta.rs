@@ -1,18 +1,22 @@
 use rustc_hir::def_id::LOCAL_CRATE;
 
-use crate::{Args, BevyCtxt, Meta, ProxyMeta, META_VERSION};
+use crate::{instantiation_key, proxy_ident, Args, BevyCtxt, Meta, ProxyMeta, META_VERSION};
 
 /// Finds and caches relevant mlua traits, if they cannot be found throws an ICE
 pub(crate) fn write_meta(ctxt: &mut BevyCtxt<'_>, _args: &Args) -> bool {
     let tcx = &ctxt.tcx;
 
     let mut proxies = Vec::with_capacity(ctxt.reflect_types.len());
-    for proxy in ctxt.reflect_types.keys() {
-        let def_path_hash = tcx.def_path_hash(*proxy);
+    for (proxy, reflect_ty) in ctxt.reflect_types.iter() {
+        let def_path_hash = tcx.def_path_hash(proxy.ty_adt_def().unwrap().did());
         proxies.push(ProxyMeta {
-            ident: tcx.item_name(*proxy).to_ident_string(),
+            ident: proxy_ident(*tcx, *proxy, reflect_ty),
             stable_crate_id: def_path_hash.stable_crate_id().as_u64(),
             local_hash_id: def_path_hash.local_hash().as_u64(),
+            instantiation: reflect_ty
+                .instantiation_ident
+                .is_some()
+                .then(|| instantiation_key(*tcx, *proxy)),
         });
     }
     let will_generate = !proxies.is_empty();
@@ -1,4 +1,3 @@
-use clap::Parser;
 use log::debug;
 use rustc_plugin::{CrateFilter, RustcPlugin, RustcPluginArgs, Utf8Path};
 
@@ -22,7 +21,7 @@ impl RustcPlugin for BevyAnalyzer {
         debug!("Target dir: {}", target_dir);
 
         RustcPluginArgs {
-            args: crate::Args::parse_from(std::env::args().skip(1)),
+            args: crate::Args::parse_with_config(std::env::args().skip(1)),
             filter: CrateFilter::OnlyWorkspace,
         }
     }
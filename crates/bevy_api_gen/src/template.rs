@@ -34,6 +34,8 @@ pub enum TemplateKind {
     CrateArtifact,
     #[strum(to_string = "field.tera")]
     Field,
+    #[strum(to_string = "variants.tera")]
+    Variants,
     #[strum(to_string = "function.tera")]
     Function,
     #[strum(to_string = "item.tera")]
@@ -57,6 +59,60 @@ impl ValueEnum for TemplateKind {
     }
 }
 
+/// The scripting language targeted by the built-in templates,
+/// each language has a full set of templates in its own directory i.e. `templates/rhai/item.tera`
+#[derive(
+    Display,
+    EnumString,
+    VariantNames,
+    VariantArray,
+    IntoStaticStr,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Debug,
+    Serialize,
+    Deserialize,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum TemplateLanguage {
+    #[default]
+    Lua,
+    Rhai,
+}
+
+impl TemplateLanguage {
+    /// Retrieves the contents of the built-in template of the given kind for this language
+    pub fn template(self, kind: TemplateKind) -> &'static str {
+        TEMPLATE_DIR
+            .get_file(format!("{self}/{kind}"))
+            .expect("Missing template kind file in the binary")
+            .contents_utf8()
+            .unwrap()
+    }
+
+    /// The name of the API provider generated by `collect` if none is given
+    pub fn default_api_name(self) -> &'static str {
+        match self {
+            TemplateLanguage::Lua => "LuaBevyAPIProvider",
+            TemplateLanguage::Rhai => "RhaiGeneratedBevyAPIProvider",
+        }
+    }
+}
+
+impl ValueEnum for TemplateLanguage {
+    fn value_variants<'a>() -> &'a [Self] {
+        <Self as VariantArray>::VARIANTS
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let static_str: &'static str = self.into();
+        Some(clap::builder::PossibleValue::new(static_str))
+    }
+}
+
 #[derive(Serialize)]
 pub(crate) struct TemplateContext {
     pub(crate) dependencies: Vec<String>,
@@ -68,6 +124,9 @@ pub(crate) struct TemplateContext {
 pub(crate) struct Item {
     pub(crate) import_path: String,
     pub(crate) ident: String,
+    /// The full type if this is an instantiation of a generic type or a renamed type, `import_path` then points to an alias of it
+    /// i.e. `bevy::asset::Handle<bevy::render::texture::Image>`
+    pub(crate) instantiation: Option<String>,
     pub(crate) has_static_methods: bool,
     pub(crate) variants: Vec<Variant>,
     pub(crate) functions: Vec<Function>,
@@ -85,6 +144,8 @@ pub(crate) struct Variant {
     pub(crate) docstrings: Vec<String>,
     /// The name of the variant if it is an enum variant or None otherwise
     pub(crate) name: Option<String>,
+    /// true if the fields are unnamed, i.e. `Variant(f32, f32)`
+    pub(crate) is_tuple: bool,
     pub(crate) fields: Vec<Field>,
 }
 
@@ -94,6 +155,8 @@ pub(crate) struct Field {
     pub(crate) ident: String,
     pub(crate) ty: String,
     pub(crate) reflection_strategy: ReflectionStrategy,
+    /// true if the type, or the type behind a reference, implements `Clone`
+    pub(crate) impls_clone: bool,
 }
 
 #[derive(Serialize)]
@@ -117,12 +180,16 @@ pub(crate) struct Arg {
     /// i.e. `&Vec<MyTy>`
     pub(crate) ty: String,
     pub(crate) reflection_strategy: ReflectionStrategy,
+    /// true if the type, or the type behind a reference, implements `Clone`
+    pub(crate) impls_clone: bool,
 }
 
 #[derive(Serialize)]
 pub(crate) struct Output {
     pub(crate) ty: String,
     pub(crate) reflection_strategy: ReflectionStrategy,
+    /// true if the type, or the type behind a reference, implements `Clone`
+    pub(crate) impls_clone: bool,
 }
 
 #[derive(Serialize)]
@@ -140,26 +207,23 @@ pub struct Crate {
 pub fn configure_tera(
     crate_name: &str,
     user_templates_dir: &Option<cargo_metadata::camino::Utf8PathBuf>,
+    language: TemplateLanguage,
 ) -> Tera {
     // setup tera for loading templates
     let mut tera = tera::Tera::default();
     configure_tera_env(&mut tera, crate_name);
 
-    for template_filename in <TemplateKind as strum::VariantNames>::VARIANTS {
+    for template in <TemplateKind as strum::VariantArray>::VARIANTS {
+        let template_filename = template.to_string();
         // check if this template is overwritten by the user if so don't bother loading it
         if let Some(t) = &user_templates_dir {
-            let template_path = t.join(template_filename);
+            let template_path = t.join(&template_filename);
             if template_path.exists() {
                 continue;
             }
         }
 
-        let content = crate::TEMPLATE_DIR
-            .get_file(template_filename)
-            .expect("Missing template kind file in the binary")
-            .contents_utf8()
-            .unwrap();
-        tera.add_raw_template(template_filename, content)
+        tera.add_raw_template(&template_filename, language.template(*template))
             .expect("Could not load built-in template");
     }
 
@@ -314,7 +378,182 @@ pub(crate) fn configure_tera_env(tera: &mut Tera, crate_name: &str) {
             let case = expect_str(expect_arg(args, "case")?)?;
             Ok(Value::String(str.to_case(case_from_str(case)?)))
         },
-    )
+    );
+
+    // the type a rust type is exchanged as with rhai, empty if the type cannot cross into rhai
+    tera.register_filter("rhai_type", |val: &Value, _: &HashMap<String, Value>| {
+        let str = expect_str(val)?;
+        Ok(Value::String(rhai_type(str).unwrap_or_default()))
+    });
+
+    // keywords cannot be used as function or property names in rhai scripts
+    tera.register_filter("rhai_ident", |val: &Value, _: &HashMap<String, Value>| {
+        let str = expect_str(val)?;
+        Ok(Value::String(rhai_ident(str)))
+    });
+
+    // converts a variable of the `rhai_type` of `ty` to `ty`
+    tera.register_filter("from_rhai", |val: &Value, args: &HashMap<String, Value>| {
+        let str = expect_str(val)?;
+        let ty = expect_str(expect_arg(args, "ty")?)?;
+        Ok(Value::String(from_rhai(ty, str)))
+    });
+
+    // converts an expression of type `ty` to its `rhai_type`
+    tera.register_filter("to_rhai", |val: &Value, args: &HashMap<String, Value>| {
+        let str = expect_str(val)?;
+        let ty = expect_str(expect_arg(args, "ty")?)?;
+        Ok(Value::String(to_rhai(ty, str.trim())))
+    });
+}
+
+/// Rhai represents all integers as `rhai::INT` and all floats as `rhai::FLOAT`
+const RHAI_INT_TYPES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+const RHAI_FLOAT_TYPES: [&str; 2] = ["f32", "f64"];
+const RHAI_STRING_TYPES: [&str; 3] = ["String", "std::string::String", "alloc::string::String"];
+
+/// Keywords and reserved symbols of rhai
+const RHAI_RESERVED: &[&str] = &[
+    "Fn",
+    "_",
+    "as",
+    "async",
+    "await",
+    "break",
+    "call",
+    "case",
+    "catch",
+    "const",
+    "continue",
+    "curry",
+    "debug",
+    "default",
+    "do",
+    "else",
+    "eval",
+    "exit",
+    "export",
+    "false",
+    "fn",
+    "for",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "in",
+    "is",
+    "is_def_fn",
+    "is_def_var",
+    "is_shared",
+    "let",
+    "loop",
+    "match",
+    "module",
+    "new",
+    "nil",
+    "null",
+    "package",
+    "print",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "shared",
+    "spawn",
+    "static",
+    "super",
+    "switch",
+    "sync",
+    "this",
+    "thread",
+    "throw",
+    "true",
+    "try",
+    "type_of",
+    "until",
+    "use",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Suffixes rhai keywords with an underscore, so that they can be called from scripts
+fn rhai_ident(ident: &str) -> String {
+    match RHAI_RESERVED.contains(&ident) {
+        true => format!("{ident}_"),
+        false => ident.to_owned(),
+    }
+}
+
+/// The type used in place of the given rust type in functions registered with rhai,
+/// None if values of the type cannot be exchanged with rhai by the generated bindings.
+///
+/// Mutable references are not supported since rhai passes every argument but the first by value.
+fn rhai_type(ty: &str) -> Option<String> {
+    let ty = ty.trim();
+    if ty.starts_with("&mut ") {
+        return None;
+    }
+    if let Some(inner) = ty.strip_prefix('&') {
+        return match inner.trim() {
+            "str" => Some("rhai::ImmutableString".to_owned()),
+            inner => rhai_type(inner),
+        };
+    }
+
+    Some(match ty {
+        _ if RHAI_INT_TYPES.contains(&ty) => "rhai::INT".to_owned(),
+        _ if RHAI_FLOAT_TYPES.contains(&ty) => "rhai::FLOAT".to_owned(),
+        _ if RHAI_STRING_TYPES.contains(&ty) => "rhai::ImmutableString".to_owned(),
+        "bool" | "char" | "()" => ty.to_owned(),
+        // generics, tuples, slices, pointers etc.
+        _ if ty.contains(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':')) => return None,
+        _ => ty.to_owned(),
+    })
+}
+
+/// Converts a variable of the [`rhai_type`] of `ty` to `ty`
+fn from_rhai(ty: &str, ident: &str) -> String {
+    let ty = ty.trim();
+    if let Some(inner) = ty.strip_prefix('&') {
+        return match inner.trim() {
+            "str" => format!("{ident}.as_str()"),
+            inner => format!("&{}", from_rhai(inner, ident)),
+        };
+    }
+
+    if RHAI_INT_TYPES.contains(&ty) || RHAI_FLOAT_TYPES.contains(&ty) {
+        format!("({ident} as {ty})")
+    } else if RHAI_STRING_TYPES.contains(&ty) {
+        format!("{ident}.to_string()")
+    } else {
+        ident.to_owned()
+    }
+}
+
+/// Converts an expression of type `ty` to the [`rhai_type`] of `ty`
+fn to_rhai(ty: &str, expr: &str) -> String {
+    let ty = ty.trim();
+    if let Some(inner) = ty.strip_prefix('&') {
+        return match inner.trim() {
+            "str" => format!("rhai::ImmutableString::from({expr})"),
+            inner => to_rhai(inner, &format!("({expr}).clone()")),
+        };
+    }
+
+    if RHAI_INT_TYPES.contains(&ty) {
+        format!("({expr}) as rhai::INT")
+    } else if RHAI_FLOAT_TYPES.contains(&ty) {
+        format!("({expr}) as rhai::FLOAT")
+    } else if RHAI_STRING_TYPES.contains(&ty) {
+        format!("rhai::ImmutableString::from({expr})")
+    } else {
+        expr.to_owned()
+    }
 }
 
 pub fn extend_context_with_args(template_args: Option<&str>, context: &mut tera::Context) {
@@ -337,3 +576,38 @@ fn expect_arg<'a>(args: &'a HashMap<String, Value>, key: &str) -> tera::Result<&
     args.get(key)
         .ok_or_else(|| tera::Error::msg(format!("Missing argument {}", key)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rhai_types() {
+        assert_eq!(rhai_type("u32").unwrap(), "rhai::INT");
+        assert_eq!(rhai_type("&f32").unwrap(), "rhai::FLOAT");
+        assert_eq!(rhai_type("&str").unwrap(), "rhai::ImmutableString");
+        assert_eq!(rhai_type("&bevy::math::Vec3").unwrap(), "bevy::math::Vec3");
+        assert_eq!(rhai_type("&mut bevy::math::Vec3"), None);
+        assert_eq!(rhai_type("std::option::Option<u32>"), None);
+        assert_eq!(rhai_type("[f32; 3]"), None);
+    }
+
+    #[test]
+    fn rhai_reserved_idents() {
+        assert_eq!(rhai_ident("new"), "new_");
+        assert_eq!(rhai_ident("length"), "length");
+    }
+
+    #[test]
+    fn rhai_conversions() {
+        assert_eq!(from_rhai("u32", "a"), "(a as u32)");
+        assert_eq!(from_rhai("&str", "a"), "a.as_str()");
+        assert_eq!(from_rhai("&bevy::math::Vec3", "a"), "&a");
+        assert_eq!(to_rhai("u32", "f()"), "(f()) as rhai::INT");
+        assert_eq!(to_rhai("&bevy::math::Vec3", "f()"), "(f()).clone()");
+        assert_eq!(
+            to_rhai("std::string::String", "f()"),
+            "rhai::ImmutableString::from(f())"
+        );
+    }
+}
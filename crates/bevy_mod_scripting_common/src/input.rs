@@ -12,9 +12,9 @@ use syn::{
     spanned::Spanned,
     token::{And, Gt, Lt, Mut, PathSep},
     visit_mut::VisitMut,
-    AngleBracketedGenericArguments, Attribute, Error, Field, Fields, GenericArgument, PatType,
-    Path, PathArguments, PathSegment, Receiver, TraitItemFn, Type, TypePath, TypeReference,
-    TypeTuple, Variant,
+    AngleBracketedGenericArguments, Attribute, Error, Field, Fields, FieldsNamed, FieldsUnnamed,
+    GenericArgument, PatType, Path, PathArguments, PathSegment, Receiver, TraitItemFn, Type,
+    TypePath, TypeReference, TypeTuple, Variant,
 };
 
 use crate::utils::ident_to_type_path;
@@ -47,6 +47,30 @@ pub enum StdTypeIdent {
 #[derive(Debug)]
 pub enum ProxyData {
     Struct { fields: Fields },
+    Enum { variants: Vec<Variant> },
+}
+
+impl From<darling::ast::Data<Variant, Field>> for ProxyData {
+    fn from(data: darling::ast::Data<Variant, Field>) -> Self {
+        match data {
+            darling::ast::Data::Enum(variants) => ProxyData::Enum { variants },
+            darling::ast::Data::Struct(fields) => {
+                let (style, fields) = fields.split();
+                let fields = match style {
+                    darling::ast::Style::Struct => Fields::Named(FieldsNamed {
+                        brace_token: Default::default(),
+                        named: Punctuated::from_iter(fields),
+                    }),
+                    darling::ast::Style::Tuple => Fields::Unnamed(FieldsUnnamed {
+                        paren_token: Default::default(),
+                        unnamed: Punctuated::from_iter(fields),
+                    }),
+                    darling::ast::Style::Unit => Fields::Unit,
+                };
+                ProxyData::Struct { fields }
+            }
+        }
+    }
 }
 
 /// For types of the form `Option<T>` i.e. an outer identifier with a nested type inside angle brackets.
@@ -613,6 +637,8 @@ impl VisitMut for IdentifierRenamingVisitor<'_> {
 
 #[cfg(test)]
 mod test {
+    use darling::FromDeriveInput;
+
     use super::VisitSimpleType;
 
     struct TestVisitor;
@@ -672,4 +698,35 @@ mod test {
             }))
         );
     }
+
+    #[test]
+    pub fn test_proxy_data_from_input() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            #[proxy(remote = "bevy::input::gamepad::GamepadAxisType")]
+            enum GamepadAxisType {
+                LeftStickX,
+                Other(u8),
+            }
+        };
+        let meta = super::ProxyInput::from_derive_input(&input).unwrap();
+        match super::ProxyData::from(meta.data) {
+            super::ProxyData::Enum { variants } => {
+                assert_eq!(variants.len(), 2);
+                assert!(variants[0].fields.is_empty());
+                assert_eq!(variants[1].fields.len(), 1);
+            }
+            data => panic!("Expected enum data, got: {data:?}"),
+        }
+
+        let input: syn::DeriveInput = syn::parse_quote! {
+            struct Transform(f32, f32);
+        };
+        let meta = super::ProxyInput::from_derive_input(&input).unwrap();
+        assert!(matches!(
+            super::ProxyData::from(meta.data),
+            super::ProxyData::Struct {
+                fields: syn::Fields::Unnamed(fields)
+            } if fields.unnamed.len() == 2
+        ));
+    }
 }
@@ -1,4 +1,10 @@
-use bevy::asset::Asset;
+use std::{collections::HashMap, sync::Arc};
+
+use bevy::{
+    asset::{Asset, AssetId, AssetPath, AssetServer, Handle},
+    tasks::block_on,
+};
+use parking_lot::RwLock;
 
 /// All code assets share this common interface.
 /// When adding a new code asset don't forget to implement asset loading
@@ -6,3 +12,174 @@ use bevy::asset::Asset;
 pub trait CodeAsset: Asset {
     fn bytes(&self) -> &[u8];
 }
+
+/// A code asset some script imported as a module
+struct ModuleEntry<A: Asset, S, C> {
+    /// keeps the module asset loaded
+    handle: Handle<A>,
+    /// None until the asset is loaded
+    source: Option<S>,
+    /// the compiled module, cleared whenever any module changes
+    compiled: Option<C>,
+}
+
+/// The code assets imported as modules by the scripts of a host, shared by all of them.
+///
+/// Language crates resolve the module names used by scripts to asset paths and retrieve the modules from here,
+/// keeping their source of type `S` in sync with the assets by calling [`AssetModules::update`] whenever a code asset is added or modified.
+/// Modules are requested from the asset server the first time they are imported.
+///
+/// Hosts can also store a compiled form of each module of type `C`, since modules import each other
+/// every compiled module is discarded whenever any of the modules changes.
+pub struct AssetModules<A: Asset, S, C = ()> {
+    asset_server: AssetServer,
+    modules: Arc<RwLock<HashMap<AssetPath<'static>, ModuleEntry<A, S, C>>>>,
+}
+
+impl<A: Asset, S, C> Clone for AssetModules<A, S, C> {
+    fn clone(&self) -> Self {
+        Self {
+            asset_server: self.asset_server.clone(),
+            modules: self.modules.clone(),
+        }
+    }
+}
+
+impl<A: Asset, S: Clone, C: Clone> AssetModules<A, S, C> {
+    pub fn new(asset_server: AssetServer) -> Self {
+        Self {
+            asset_server,
+            modules: Default::default(),
+        }
+    }
+
+    pub fn asset_server(&self) -> &AssetServer {
+        &self.asset_server
+    }
+
+    /// Retrieves the handle of the module at the given path, requesting it from the asset server if necessary.
+    ///
+    /// Returns `None` if there is no file at the given path, so that looking up candidate paths which
+    /// do not exist does not produce asset loading errors.
+    pub fn load(&self, path: &AssetPath<'static>) -> Option<Handle<A>> {
+        if let Some(entry) = self.modules.read().get(path) {
+            return Some(entry.handle.clone());
+        }
+
+        if !self.exists(path) {
+            return None;
+        }
+
+        let mut modules = self.modules.write();
+        let entry = modules
+            .entry(path.clone())
+            .or_insert_with(|| ModuleEntry {
+                handle: self.asset_server.load(path.clone()),
+                source: None,
+                compiled: None,
+            });
+        Some(entry.handle.clone())
+    }
+
+    /// The source of the module at the given path, `None` if it was never loaded or is not loaded yet
+    pub fn source(&self, path: &AssetPath<'static>) -> Option<S> {
+        let modules = self.modules.read();
+        modules.get(path).and_then(|m| m.source.clone())
+    }
+
+    /// The compiled module at the given path, if one was stored since the modules last changed
+    pub fn compiled(&self, path: &AssetPath<'static>) -> Option<C> {
+        let modules = self.modules.read();
+        modules.get(path).and_then(|m| m.compiled.clone())
+    }
+
+    /// Stores the compiled form of the module at the given path, does nothing if the module was never loaded
+    pub fn set_compiled(&self, path: &AssetPath<'static>, compiled: C) {
+        if let Some(entry) = self.modules.write().get_mut(path) {
+            entry.compiled = Some(compiled);
+        }
+    }
+
+    /// Updates the source of the module stored in the given asset and discards all compiled modules,
+    /// does nothing if no script imports the asset
+    pub fn update(&self, id: AssetId<A>, source: S) {
+        let mut modules = self.modules.write();
+        let Some(entry) = modules.values_mut().find(|m| m.handle.id() == id) else {
+            return;
+        };
+        entry.source = Some(source);
+
+        // modules importing the changed one hold onto its old version
+        for entry in modules.values_mut() {
+            entry.compiled = None;
+        }
+    }
+
+    /// Checks a file exists at the given path using the reader of its asset source
+    fn exists(&self, path: &AssetPath<'static>) -> bool {
+        let Ok(source) = self.asset_server.get_source(path.source()) else {
+            return false;
+        };
+        block_on(source.reader().read(path.path())).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        asset::{AssetApp, AssetPlugin},
+        core::TaskPoolPlugin,
+        prelude::*,
+    };
+
+    use super::*;
+
+    #[derive(Asset, TypePath)]
+    struct Code;
+
+    fn modules(dir: &str) -> AssetModules<Code, Arc<str>, u32> {
+        let dir = std::env::temp_dir().join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.code"), "a").unwrap();
+        std::fs::write(dir.join("b.code"), "b").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin {
+                file_path: dir.to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+        ))
+        .init_asset::<Code>();
+        AssetModules::new(app.world.resource::<AssetServer>().clone())
+    }
+
+    #[test]
+    fn missing_files_are_not_loaded() {
+        let modules = modules("bevy_mod_scripting_core_missing_modules");
+        assert!(modules.load(&"a.code".into()).is_some());
+        assert!(modules.load(&"missing.code".into()).is_none());
+    }
+
+    #[test]
+    fn updates_discard_every_compiled_module() {
+        let modules = modules("bevy_mod_scripting_core_compiled_modules");
+        let (a, b) = (AssetPath::from("a.code"), AssetPath::from("b.code"));
+        let a_handle = modules.load(&a).unwrap();
+        modules.load(&b).unwrap();
+
+        modules.update(a_handle.id(), "a".into());
+        assert_eq!(modules.source(&a).as_deref(), Some("a"));
+        assert_eq!(modules.source(&b), None);
+
+        modules.set_compiled(&a, 1);
+        modules.set_compiled(&b, 2);
+        assert_eq!(modules.compiled(&b), Some(2));
+
+        modules.update(a_handle.id(), "a2".into());
+        assert_eq!(modules.source(&a).as_deref(), Some("a2"));
+        assert_eq!(modules.compiled(&a), None);
+        assert_eq!(modules.compiled(&b), None);
+    }
+}
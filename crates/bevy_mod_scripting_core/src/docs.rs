@@ -1,10 +1,151 @@
-use crate::error::ScriptError;
+use std::{fmt::Write as _, fs::File, io::Write, path::Path};
+
+use crate::{error::ScriptError, schema::HookSchema};
 
 /// A documentation piece exported by an `APIProvider`
 pub trait DocFragment: 'static {
     fn merge(self, o: Self) -> Self;
     fn gen_docs(self) -> Result<(), ScriptError>;
 
+    /// Adds the hooks declared for the host to the documentation, see [`ScriptHooks`](crate::hooks::ScriptHooks).
+    /// By default hooks are not documented.
+    fn with_hooks(self, _hooks: &[HookSchema]) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
     /// Retrieves the name of the documentation fragment, most likely the name of your game!
     fn name(&self) -> &'static str;
 }
+
+static HTML_STYLE: &str = "body{display:flex;margin:0;font-family:sans-serif}\
+nav{min-width:14em;padding:1em;background:#f4f4f4;height:100vh;position:sticky;top:0;overflow:auto}\
+nav ul{list-style:none;padding:0}main{padding:1em 2em;max-width:60em}\
+pre{background:#f4f4f4;padding:.5em}section{border-bottom:1px solid #ddd}";
+
+/// A section of an [`HtmlPage`], linked to from the navigation bar
+pub struct HtmlSection {
+    id: String,
+    heading: String,
+    body: String,
+}
+
+impl HtmlSection {
+    /// Creates an empty section, the id is used as its anchor
+    pub fn new(id: &str, heading: &str) -> Self {
+        Self {
+            id: escape_html(id),
+            heading: escape_html(heading),
+            body: String::new(),
+        }
+    }
+
+    pub fn subheading(&mut self, heading: &str) -> &mut Self {
+        let _ = writeln!(self.body, "<h3>{}</h3>", escape_html(heading));
+        self
+    }
+
+    /// Adds a code block, i.e. a function signature
+    pub fn code(&mut self, code: &str) -> &mut Self {
+        let _ = writeln!(self.body, "<pre><code>{}</code></pre>", escape_html(code));
+        self
+    }
+
+    /// Adds each line of documentation as a paragraph
+    pub fn docs<S: AsRef<str>>(&mut self, lines: impl IntoIterator<Item = S>) -> &mut Self {
+        for line in lines {
+            let _ = writeln!(self.body, "<p>{}</p>", escape_html(line.as_ref()));
+        }
+        self
+    }
+}
+
+/// A single self-contained HTML documentation page, shared by the documentation generators of every language
+pub struct HtmlPage {
+    title: String,
+    sections: Vec<HtmlSection>,
+}
+
+impl HtmlPage {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: escape_html(title),
+            sections: Vec::default(),
+        }
+    }
+
+    pub fn section(&mut self, section: HtmlSection) -> &mut Self {
+        self.sections.push(section);
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let title = &self.title;
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>"
+        );
+        let _ = writeln!(out, "<nav>\n<h2>{title}</h2>\n<ul>");
+        for section in &self.sections {
+            let _ = writeln!(
+                out,
+                "<li><a href=\"#{}\">{}</a></li>",
+                section.id, section.heading
+            );
+        }
+        let _ = writeln!(out, "</ul>\n</nav>\n<main>\n<h1>{title}</h1>");
+        for section in &self.sections {
+            let _ = write!(
+                out,
+                "<section id=\"{}\">\n<h2>{}</h2>\n{}</section>\n",
+                section.id, section.heading, section.body
+            );
+        }
+        let _ = writeln!(out, "</main>\n</body>\n</html>");
+        out
+    }
+}
+
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Writes a generated documentation file, reporting failures as [`ScriptError::DocGenError`]
+pub fn write_doc_file(path: &Path, content: &str) -> Result<(), ScriptError> {
+    File::create(path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.flush()
+        })
+        .map_err(|e| ScriptError::DocGenError(format!("Could not write `{}`: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sections_are_linked_and_escaped() {
+        let mut section = HtmlSection::new("type.Vec<T>", "Vec<T>");
+        section.code("len(self)").docs(["Returns the <length>"]);
+        let html = HtmlPage::new("Game").section(section).render();
+
+        assert!(html.contains("<li><a href=\"#type.Vec&lt;T&gt;\">Vec&lt;T&gt;</a></li>"));
+        assert!(html.contains("<section id=\"type.Vec&lt;T&gt;\">\n<h2>Vec&lt;T&gt;</h2>"));
+        assert!(html.contains("<p>Returns the &lt;length&gt;</p>"));
+    }
+}
@@ -0,0 +1,252 @@
+//! Declarations of the hooks scripts can define
+use std::{fmt, marker::PhantomData};
+
+use bevy::prelude::*;
+
+use crate::{hosts::ScriptHost, schema::HookSchema};
+
+/// A function defined by a script, as reported by [`ScriptHost::script_functions`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptFunction {
+    pub name: String,
+    /// The number of parameters of the function, `None` if the language does not expose it
+    pub arity: Option<usize>,
+}
+
+/// A problem found in a script by [`ScriptHooks::lint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookLint {
+    /// The script defines a function which looks like a hook, but no such hook is registered so it will never be called
+    Unregistered {
+        function: String,
+        /// The registered hook with the most similar name, if any is close enough to be a typo
+        suggestion: Option<String>,
+    },
+    /// The script defines a registered hook with a different number of parameters than the hook is called with
+    WrongArity {
+        hook: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for HookLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookLint::Unregistered {
+                function,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "function `{function}` looks like a hook but no such hook is registered"
+                )?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean `{suggestion}`?")?;
+                }
+                Ok(())
+            }
+            HookLint::WrongArity {
+                hook,
+                expected,
+                found,
+            } => write!(
+                f,
+                "hook `{hook}` is called with {expected} argument(s) but is defined with {found} parameter(s)"
+            ),
+        }
+    }
+}
+
+/// The hooks (i.e. `on_update`) the app fires for scripts of the host `H`, along with their arguments.
+///
+/// Registered hooks are included in the generated documentation and API schema,
+/// and if `lint` is set, every script is checked against them as it loads, see [`ScriptHooks::lint`].
+#[derive(Resource)]
+pub struct ScriptHooks<H: ScriptHost> {
+    pub hooks: Vec<HookSchema>,
+    /// If true, problems found by [`ScriptHooks::lint`] are logged as warnings whenever a script loads
+    pub lint: bool,
+    /// Functions starting with this prefix are considered hooks by the lint, `on_` by default
+    pub prefix: String,
+    _ph: PhantomData<fn() -> H>,
+}
+
+impl<H: ScriptHost> Default for ScriptHooks<H> {
+    fn default() -> Self {
+        Self {
+            hooks: Vec::default(),
+            lint: false,
+            prefix: "on_".to_owned(),
+            _ph: PhantomData,
+        }
+    }
+}
+
+impl<H: ScriptHost> ScriptHooks<H> {
+    /// Registers a hook, replacing any previously registered hook with the same name
+    pub fn register(&mut self, hook: HookSchema) {
+        self.hooks.retain(|h| h.name != hook.name);
+        self.hooks.push(hook);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&HookSchema> {
+        self.hooks.iter().find(|h| h.name == name)
+    }
+
+    /// Checks the functions defined by a script against the registered hooks, reporting:
+    /// - functions starting with the hook prefix which are not registered hooks
+    /// - registered hooks defined with a different number of parameters than declared, if the arity is known
+    pub fn lint(&self, functions: &[ScriptFunction]) -> Vec<HookLint> {
+        lint(&self.hooks, &self.prefix, functions)
+    }
+}
+
+fn lint(hooks: &[HookSchema], prefix: &str, functions: &[ScriptFunction]) -> Vec<HookLint> {
+    functions
+        .iter()
+        .filter_map(|function| match hooks.iter().find(|h| h.name == function.name) {
+            Some(hook) => match function.arity {
+                Some(found) if found != hook.args.len() => Some(HookLint::WrongArity {
+                    hook: hook.name.clone(),
+                    expected: hook.args.len(),
+                    found,
+                }),
+                _ => None,
+            },
+            None if function.name.starts_with(prefix) => Some(HookLint::Unregistered {
+                function: function.name.clone(),
+                suggestion: suggest(hooks, &function.name),
+            }),
+            None => None,
+        })
+        .collect()
+}
+
+/// The hook closest to the given name, if it is at most 2 edits away
+fn suggest(hooks: &[HookSchema], name: &str) -> Option<String> {
+    hooks
+        .iter()
+        .map(|h| (edit_distance(&h.name, name), h))
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, h)| h.name.clone())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+/// Trait for app builder notation
+pub trait AddScriptHook {
+    /// Declares a hook fired for scripts of the given host, see [`ScriptHooks`]
+    fn add_script_hook<T: ScriptHost>(&mut self, hook: HookSchema) -> &mut Self;
+
+    /// Enables checking scripts of the given host against its declared hooks as they load, see [`ScriptHooks::lint`]
+    fn lint_script_hooks<T: ScriptHost>(&mut self) -> &mut Self;
+}
+
+impl AddScriptHook for App {
+    fn add_script_hook<T: ScriptHost>(&mut self, hook: HookSchema) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ScriptHooks::<T>::default)
+            .register(hook);
+        self
+    }
+
+    fn lint_script_hooks<T: ScriptHost>(&mut self) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ScriptHooks::<T>::default)
+            .lint = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hooks() -> Vec<HookSchema> {
+        vec![
+            HookSchema::new("on_update"),
+            HookSchema::new("on_event").with_arg("name", "String"),
+        ]
+    }
+
+    fn function(name: &str, arity: Option<usize>) -> ScriptFunction {
+        ScriptFunction {
+            name: name.to_owned(),
+            arity,
+        }
+    }
+
+    #[test]
+    fn registered_hooks_and_other_functions_pass() {
+        let functions = [
+            function("on_update", Some(0)),
+            function("on_event", Some(1)),
+            function("helper", Some(3)),
+        ];
+        assert_eq!(lint(&hooks(), "on_", &functions), vec![]);
+    }
+
+    #[test]
+    fn unregistered_prefixed_functions_are_reported() {
+        let lints = lint(&hooks(), "on_", &[function("on_spawn", Some(0))]);
+        assert_eq!(
+            lints,
+            vec![HookLint::Unregistered {
+                function: "on_spawn".to_owned(),
+                suggestion: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn typos_suggest_the_closest_hook() {
+        let lints = lint(&hooks(), "on_", &[function("on_updat", Some(0))]);
+        assert_eq!(
+            lints,
+            vec![HookLint::Unregistered {
+                function: "on_updat".to_owned(),
+                suggestion: Some("on_update".to_owned()),
+            }]
+        );
+    }
+
+    #[test]
+    fn wrong_arity_is_reported() {
+        let lints = lint(&hooks(), "on_", &[function("on_event", Some(2))]);
+        assert_eq!(
+            lints,
+            vec![HookLint::WrongArity {
+                hook: "on_event".to_owned(),
+                expected: 1,
+                found: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn unknown_arity_is_not_checked() {
+        assert_eq!(lint(&hooks(), "on_", &[function("on_event", None)]), vec![]);
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("on_update", "on_update"), 0);
+        assert_eq!(edit_distance("on_update", "on_updat"), 1);
+        assert_eq!(edit_distance("on_update", "on_udpate"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
@@ -1,8 +1,13 @@
 //! All script host related stuff
-use bevy::{asset::Asset, ecs::schedule::ScheduleLabel, prelude::*};
+use bevy::{
+    asset::{Asset, UntypedAssetId},
+    ecs::{schedule::ScheduleLabel, system::SystemParam},
+    prelude::*,
+};
 use std::{
     collections::HashMap,
     iter::once,
+    marker::PhantomData,
     sync::atomic::{AtomicU32, Ordering},
 };
 
@@ -11,6 +16,8 @@ use crate::{
     docs::DocFragment,
     error::ScriptError,
     event::{ScriptEvent, ScriptLoaded},
+    hooks::{ScriptFunction, ScriptHooks},
+    schema::{ApiSchema, HookSchema},
     world::WorldPointer,
 };
 
@@ -54,6 +61,32 @@ impl Default for Recipients {
     }
 }
 
+/// Describes the kind of world access a script needs while handling events
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum ScriptAccess {
+    /// The script may read and write the world freely, it is always executed on its own
+    #[default]
+    Exclusive,
+    /// The script only reads the world, any changes it makes are recorded as deferred commands.
+    /// Such scripts may be executed in parallel with each other, see [`ScriptExecutionMode`](crate::systems::ScriptExecutionMode)
+    ReadOnly,
+    // Declaring disjoint sets of written components is not supported, since script hosts hand out access
+    // to the whole world. Scripts which write components in parallel should be `ReadOnly` and rely on deferred writes.
+}
+
+/// A marker type distinguishing script hosts of the same language.
+///
+/// Scripts, their collections and contexts are tagged with the label of the host which runs them,
+/// so that multiple hosts of the same language (say one for UI and one for gameplay scripts) can coexist,
+/// each only loading the scripts from its own `ScriptCollection<T, L>` components.
+/// Any `'static` type implementing [`TypePath`] can be used as a label, `()` is the default.
+///
+/// Events are not labelled: hosts of the same language which share their event type read the same event queue,
+/// so each event is handled by the scripts of all of them. Such hosts should use distinct event argument types.
+pub trait ScriptLabel: TypePath + Send + Sync + 'static {}
+
+impl<T: TypePath + Send + Sync + 'static> ScriptLabel for T {}
+
 /// A script host is the interface between your rust application
 /// and the scripts in some interpreted language.
 pub trait ScriptHost: Send + Sync + 'static + Default + Resource {
@@ -68,6 +101,8 @@ pub trait ScriptHost: Send + Sync + 'static + Default + Resource {
     type APITarget: Send + Sync + 'static;
     /// the type of each doc fragment
     type DocTarget: DocFragment;
+    /// the label distinguishing this host from other hosts of the same language, see [`ScriptLabel`]
+    type Label: ScriptLabel;
 
     /// Loads a script in byte array format, the script name can be used
     /// to send useful errors.
@@ -78,6 +113,20 @@ pub trait ScriptHost: Send + Sync + 'static + Default + Resource {
         providers: &mut APIProviders<Self>,
     ) -> Result<Self::ScriptContext, ScriptError>;
 
+    /// Loads a script instance stored in the given asset, this is what script instances attached to entities are loaded with.
+    ///
+    /// Many instances can share one asset, hosts can override this to compile each asset once
+    /// and cache the result until the asset changes. By default this simply calls `ScriptHost::load_script`.
+    fn load_script_asset(
+        &mut self,
+        _asset: AssetId<Self::ScriptAsset>,
+        script: &[u8],
+        script_data: &ScriptData,
+        providers: &mut APIProviders<Self>,
+    ) -> Result<Self::ScriptContext, ScriptError> {
+        self.load_script(script, script_data, providers)
+    }
+
     /// Perform one-off initialization of scripts (happens for every new or re-loaded script)
     fn setup_script(
         &mut self,
@@ -96,6 +145,49 @@ pub trait ScriptHost: Send + Sync + 'static + Default + Resource {
         providers: &mut APIProviders<Self>,
     );
 
+    /// Like `handle_events` but only ever called with scripts which declared [`ScriptAccess::ReadOnly`] access,
+    /// when the [`ScriptExecutionMode::Parallel`](crate::systems::ScriptExecutionMode::Parallel) mode is enabled.
+    ///
+    /// Hosts whose contexts can be driven from multiple threads should override this to run the scripts in parallel
+    /// behind [`WorldPointer::read_only`] pointers, applying the deferred commands once all scripts are done.
+    /// By default the scripts are executed sequentially via `handle_events`.
+    fn handle_events_parallel<'a>(
+        &mut self,
+        world: &mut World,
+        events: &[Self::ScriptEvent],
+        ctxs: Vec<(ScriptData<'a>, &'a mut Self::ScriptContext)>,
+        providers: &mut APIProviders<Self>,
+    ) {
+        self.handle_events(world, events, ctxs.into_iter(), providers)
+    }
+
+    /// Takes the code assets (i.e. imported modules) the given script depended on when it was last loaded with `ScriptHost::load_script`,
+    /// whether or not loading succeeded.
+    ///
+    /// Called once after each load of the script, the dependencies are tracked in [`ScriptContexts`] from then on,
+    /// so hosts should not hold on to them any longer.
+    /// The script is reloaded whenever one of these assets is added or modified, see [`script_hot_reload_handler`](crate::systems::script_hot_reload_handler).
+    /// By default scripts have no dependencies.
+    fn take_script_dependencies(
+        &mut self,
+        _script_data: &ScriptData,
+    ) -> Vec<AssetId<Self::ScriptAsset>> {
+        Vec::default()
+    }
+
+    /// The functions defined by a loaded script, used to check scripts against the hooks declared in [`ScriptHooks`].
+    ///
+    /// By default no functions are reported, so scripts are not checked.
+    fn script_functions(&self, _ctx: &Self::ScriptContext) -> Vec<ScriptFunction> {
+        Vec::default()
+    }
+
+    /// Called once a hot reloaded script has been loaded and set up (`ScriptHost::load_script`, `ScriptHost::setup_script`),
+    /// with the context of the outdated script and the newly loaded one.
+    ///
+    /// Hosts can use this to carry script state across reloads, by default the old context is simply dropped.
+    fn reload_context(&mut self, _old: Self::ScriptContext, _new: &mut Self::ScriptContext) {}
+
     /// Loads and runs script instantaneously without storing any script data into the world.
     /// The script id is set to `u32::MAX`.
     fn run_one_shot(
@@ -114,6 +206,8 @@ pub trait ScriptHost: Send + Sync + 'static + Default + Resource {
 
         let mut providers: APIProviders<Self> = world.remove_resource().unwrap();
         let mut ctx = self.load_script(script, &fd, &mut providers).unwrap();
+        // one shot scripts are never reloaded
+        self.take_script_dependencies(&fd);
         self.setup_script(&fd, &mut ctx, &mut providers)?;
         let events = [event; 1];
 
@@ -180,6 +274,12 @@ pub trait APIProvider: 'static + Send + Sync {
         None
     }
 
+    /// Describe the globals, functions and hooks provided by this API provider along with any types which are not reflected,
+    /// to be merged with the reflected types into a language-neutral schema of the API, see [`APIProviders::gen_schema`]
+    fn get_api_schema(&self) -> Option<ApiSchema> {
+        None
+    }
+
     /// Some providers might provide additional types which need to be registered
     /// with the reflection API to work.
     fn register_with_app(&self, _app: &mut App) {}
@@ -241,7 +341,8 @@ impl<T: ScriptHost> APIProviders<T> {
         Ok(())
     }
 
-    pub fn gen_all(&self) -> Result<(), ScriptError> {
+    /// Generates the documentation of all the providers merged into a single fragment, along with the given hooks
+    pub fn gen_all(&self, hooks: &[HookSchema]) -> Result<(), ScriptError> {
         let mut d: Option<T::DocTarget> = None;
         for p in self.providers.iter() {
             if let Some(f) = p.get_doc_fragment() {
@@ -252,7 +353,19 @@ impl<T: ScriptHost> APIProviders<T> {
                 }
             }
         }
-        d.map(|d| d.gen_docs()).unwrap_or_else(|| Ok(()))
+        d.map(|d| d.with_hooks(hooks).gen_docs())
+            .unwrap_or_else(|| Ok(()))
+    }
+
+    /// Builds the schema of every reflected type in the registry merged with the schemas of all the providers
+    pub fn gen_schema(&self, registry: &bevy::reflect::TypeRegistry) -> ApiSchema {
+        let mut schema = ApiSchema::from_type_registry(registry);
+        for p in self.providers.iter() {
+            if let Some(s) = p.get_api_schema() {
+                schema.merge(s);
+            }
+        }
+        schema
     }
 }
 
@@ -263,22 +376,32 @@ impl<T: ScriptHost> APIProviders<T> {
 ///
 /// We keep this public for now since there is no API for communicating with scripts
 /// outside of events. Later this might change.
+///
+/// Contexts are tagged with the label of the host owning them, see [`ScriptLabel`].
 #[derive(Resource)]
-pub struct ScriptContexts<C> {
+pub struct ScriptContexts<C, L: ScriptLabel = ()> {
     /// holds script contexts for all scripts given their instance ids.
     /// This also stores contexts which are not fully loaded hence the Option
     pub context_entities: HashMap<u32, (Entity, Option<C>, String)>,
+    /// the world access declared by each script, scripts missing from this map are exclusive
+    pub context_access: HashMap<u32, ScriptAccess>,
+    /// the assets each script depends on, see [`ScriptHost::take_script_dependencies`]
+    pub context_dependencies: HashMap<u32, Vec<UntypedAssetId>>,
+    _ph: PhantomData<L>,
 }
 
-impl<C> Default for ScriptContexts<C> {
+impl<C, L: ScriptLabel> Default for ScriptContexts<C, L> {
     fn default() -> Self {
         Self {
             context_entities: Default::default(),
+            context_access: Default::default(),
+            context_dependencies: Default::default(),
+            _ph: Default::default(),
         }
     }
 }
 
-impl<C> ScriptContexts<C> {
+impl<C, L: ScriptLabel> ScriptContexts<C, L> {
     pub fn script_owner(&self, script_id: u32) -> Option<Entity> {
         self.context_entities.get(&script_id).map(|(e, _c, _n)| *e)
     }
@@ -288,8 +411,20 @@ impl<C> ScriptContexts<C> {
             .insert(fd.sid, (fd.entity, ctx, fd.name.to_owned()));
     }
 
-    pub fn remove_context(&mut self, script_id: u32) {
-        self.context_entities.remove(&script_id);
+    /// removes the context of the given script, returning it if it was loaded
+    pub fn remove_context(&mut self, script_id: u32) -> Option<C> {
+        self.context_access.remove(&script_id);
+        self.context_dependencies.remove(&script_id);
+        self.context_entities
+            .remove(&script_id)
+            .and_then(|(_, c, _)| c)
+    }
+
+    /// returns true if the given script depended on the given asset when it was last loaded
+    pub fn depends_on(&self, script_id: u32, asset: UntypedAssetId) -> bool {
+        self.context_dependencies
+            .get(&script_id)
+            .is_some_and(|deps| deps.contains(&asset))
     }
 
     pub fn has_context(&self, script_id: u32) -> bool {
@@ -305,8 +440,10 @@ impl<C> ScriptContexts<C> {
 
 /// A struct defining an instance of a script asset.
 /// Multiple instances of the same script can exist on the same entity
+///
+/// The script will only be picked up by the host with the matching label, see [`ScriptLabel`].
 #[derive(Debug, Reflect)]
-pub struct Script<T: Asset> {
+pub struct Script<T: Asset, L: ScriptLabel = ()> {
     /// a strong handle to the script asset
     handle: Handle<T>,
 
@@ -315,11 +452,17 @@ pub struct Script<T: Asset> {
 
     /// uniquely identifies the script instance (scripts which use the same asset don't necessarily have the same ID)
     id: u32,
+
+    /// the kind of world access this script needs while handling events
+    access: ScriptAccess,
+
+    #[reflect(ignore)]
+    _ph: PhantomData<L>,
 }
 
 static COUNTER: AtomicU32 = AtomicU32::new(0);
 
-impl<T: Asset> Script<T> {
+impl<T: Asset, L: ScriptLabel> Script<T, L> {
     /// creates a new script instance with the given name and asset handle
     /// automatically gives this script instance a unique ID.
     /// No two scripts instances ever share the same ID
@@ -328,9 +471,17 @@ impl<T: Asset> Script<T> {
             handle,
             name,
             id: COUNTER.fetch_add(1, Ordering::Relaxed),
+            access: ScriptAccess::Exclusive,
+            _ph: PhantomData,
         }
     }
 
+    /// declares the kind of world access this script needs, see [`ScriptAccess`]
+    pub fn with_access(mut self, access: ScriptAccess) -> Self {
+        self.access = access;
+        self
+    }
+
     #[inline(always)]
     /// returns the name of the script
     pub fn name(&self) -> &str {
@@ -349,56 +500,69 @@ impl<T: Asset> Script<T> {
         self.id
     }
 
+    #[inline(always)]
+    /// returns the kind of world access this script needs
+    pub fn access(&self) -> ScriptAccess {
+        self.access
+    }
+
     /// reloads the script by deleting the old context and inserting a new one
     /// if the script context never existed, it will after this call.
     pub(crate) fn reload_script<H: ScriptHost>(
-        host: &mut H,
-        script: &Script<H::ScriptAsset>,
-        script_assets: &Assets<H::ScriptAsset>,
-        providers: &mut APIProviders<H>,
-        contexts: &mut ScriptContexts<H::ScriptContext>,
-        event_writer: &mut EventWriter<ScriptLoaded>,
+        script: &Script<H::ScriptAsset, H::Label>,
+        params: &mut ScriptLoadParams<H>,
     ) {
         debug!("reloading script {}", script.id);
 
         // retrieve owning entity
-        if let Some(entity) = contexts.script_owner(script.id()) {
+        if let Some(entity) = params.contexts.script_owner(script.id()) {
             // remove old context
-            contexts.remove_context(script.id());
+            let old_ctx = params.contexts.remove_context(script.id());
             // insert new re-loaded context
-            Self::insert_new_script_context::<H>(
-                host,
-                script,
-                entity,
-                script_assets,
-                providers,
-                contexts,
-                event_writer,
-            );
+            Self::insert_new_script_context::<H>(script, entity, params);
+
+            // let the host carry state over from the outdated context
+            let new_ctx = params
+                .contexts
+                .context_entities
+                .get_mut(&script.id())
+                .and_then(|(_, c, _)| c.as_mut());
+            if let (Some(old_ctx), Some(new_ctx)) = (old_ctx, new_ctx) {
+                params.host.reload_context(old_ctx, new_ctx);
+            }
         } else {
             // remove old context
-            contexts.remove_context(script.id());
+            params.contexts.remove_context(script.id());
         }
     }
 
     /// checks if a script has loaded, and if so loads (`ScriptHost::load_script`),
-    /// sets up (`ScriptHost::setup_script`) and inserts its new context into the contexts resource
+    /// sets up (`ScriptHost::setup_script`), lints it if enabled in [`ScriptHooks`] and inserts its new context into the contexts resource
     /// otherwise inserts None. Sends ScriptLoaded event if the script was loaded
     pub(crate) fn insert_new_script_context<H: ScriptHost>(
-        host: &mut H,
-        new_script: &Script<H::ScriptAsset>,
+        new_script: &Script<H::ScriptAsset, H::Label>,
         entity: Entity,
-        script_assets: &Assets<H::ScriptAsset>,
-        providers: &mut APIProviders<H>,
-        contexts: &mut ScriptContexts<H::ScriptContext>,
-        event_writer: &mut EventWriter<ScriptLoaded>,
+        params: &mut ScriptLoadParams<H>,
     ) {
+        let ScriptLoadParams {
+            host,
+            providers,
+            hooks,
+            script_assets,
+            contexts,
+            event_writer,
+        } = params;
+
         let fd = ScriptData {
             sid: new_script.id(),
             entity,
             name: new_script.name(),
         };
 
+        contexts
+            .context_access
+            .insert(new_script.id(), new_script.access());
+
         let script = match script_assets.get(&new_script.handle) {
             Some(s) => s,
             None => {
@@ -410,10 +574,27 @@ impl<T: Asset> Script<T> {
         };
         debug!("Inserted script {:?}", fd);
 
-        match host.load_script(script.bytes(), &fd, providers) {
+        let loaded = host.load_script_asset(new_script.handle.id(), script.bytes(), &fd, providers);
+
+        // track dependencies even if loading failed, the script might be waiting on one of them
+        let dependencies = host
+            .take_script_dependencies(&fd)
+            .into_iter()
+            .map(AssetId::untyped)
+            .collect();
+        contexts
+            .context_dependencies
+            .insert(new_script.id(), dependencies);
+
+        match loaded {
             Ok(mut ctx) => {
                 host.setup_script(&fd, &mut ctx, providers)
                     .expect("Failed to setup script");
+                if let Some(hooks) = hooks.as_deref().filter(|h| h.lint) {
+                    for lint in hooks.lint(&host.script_functions(&ctx)) {
+                        warn!("In script {}: {}", &new_script.name, lint);
+                    }
+                }
                 contexts.insert_context(fd, Some(ctx));
                 event_writer.send(ScriptLoaded {
                     sid: new_script.id(),
@@ -429,17 +610,30 @@ impl<T: Asset> Script<T> {
     }
 }
 
+/// The resources used to load the scripts of the host `H` into contexts
+#[derive(SystemParam)]
+pub struct ScriptLoadParams<'w, H: ScriptHost> {
+    pub host: ResMut<'w, H>,
+    pub providers: ResMut<'w, APIProviders<H>>,
+    pub hooks: Option<Res<'w, ScriptHooks<H>>>,
+    pub script_assets: Res<'w, Assets<<H as ScriptHost>::ScriptAsset>>,
+    pub contexts: ResMut<'w, ScriptContexts<<H as ScriptHost>::ScriptContext, <H as ScriptHost>::Label>>,
+    pub event_writer: EventWriter<'w, ScriptLoaded>,
+}
+
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component, Default)]
 /// The component storing many scripts.
 /// Scripts receive information about the entity they are attached to
 /// Scripts have unique identifiers and hence multiple copies of the same script
 /// can be attached to the same entity
-pub struct ScriptCollection<T: Asset> {
-    pub scripts: Vec<Script<T>>,
+///
+/// Each host only handles the collections tagged with its label, see [`ScriptLabel`].
+pub struct ScriptCollection<T: Asset, L: ScriptLabel = ()> {
+    pub scripts: Vec<Script<T, L>>,
 }
 
-impl<T: Asset> Default for ScriptCollection<T> {
+impl<T: Asset, L: ScriptLabel> Default for ScriptCollection<T, L> {
     fn default() -> Self {
         Self {
             scripts: Default::default(),
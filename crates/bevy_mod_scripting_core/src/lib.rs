@@ -1,5 +1,6 @@
 use crate::{
     event::ScriptErrorEvent,
+    hooks::ScriptHooks,
     hosts::{APIProvider, APIProviders, ScriptHost},
 };
 use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
@@ -10,21 +11,26 @@ pub mod asset;
 pub mod docs;
 pub mod error;
 pub mod event;
+pub mod hooks;
 pub mod hosts;
+pub mod schema;
 pub mod systems;
 pub mod world;
 pub mod prelude {
     // general
     pub use {
-        crate::asset::CodeAsset,
+        crate::asset::{AssetModules, CodeAsset},
         crate::docs::DocFragment,
         crate::error::ScriptError,
         crate::event::{ScriptErrorEvent, ScriptEvent},
+        crate::hooks::{AddScriptHook, HookLint, ScriptFunction, ScriptHooks},
         crate::hosts::{
-            APIProvider, APIProviders, Recipients, Script, ScriptCollection, ScriptContexts,
-            ScriptData, ScriptHost,
+            APIProvider, APIProviders, Recipients, Script, ScriptAccess, ScriptCollection,
+            ScriptContexts, ScriptData, ScriptHost, ScriptLabel,
         },
-        crate::systems::script_event_handler,
+        crate::schema::{ApiSchema, HookSchema},
+        crate::systems::{script_event_handler, ScriptExecutionMode},
+        crate::world::WorldMutationMode,
         crate::{
             AddScriptApiProvider, AddScriptHost, AddScriptHostHandler, GenDocumentation,
             ScriptingPlugin,
@@ -43,12 +49,16 @@ pub struct ScriptingPlugin;
 
 impl Plugin for ScriptingPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_event::<ScriptErrorEvent>();
+        app.add_event::<ScriptErrorEvent>()
+            .init_resource::<world::WorldMutationMode>()
+            .init_resource::<systems::ScriptExecutionMode>();
     }
 }
 
 pub trait GenDocumentation {
     fn update_documentation<T: ScriptHost>(&mut self) -> &mut Self;
+
+    fn export_api_schema<T: ScriptHost>(&mut self, path: impl AsRef<std::path::Path>) -> &mut Self;
 }
 
 impl GenDocumentation for App {
@@ -59,7 +69,11 @@ impl GenDocumentation for App {
             info!("Generating documentation");
             let w = &mut self.world;
             let providers: &APIProviders<T> = w.resource();
-            if let Err(e) = providers.gen_all() {
+            let hooks = w
+                .get_resource::<ScriptHooks<T>>()
+                .map(|h| h.hooks.as_slice())
+                .unwrap_or_default();
+            if let Err(e) = providers.gen_all(hooks) {
                 error!("{}", e);
             }
             info!("Documentation generated");
@@ -67,6 +81,39 @@ impl GenDocumentation for App {
 
         self
     }
+
+    /// Writes the language-neutral schema of the API of the given script host to a JSON file, made of every type in the `AppTypeRegistry`
+    /// and the metadata of each API provider. Disabled in optimized builds unless `doc_always` feature is enabled.
+    fn export_api_schema<T: ScriptHost>(
+        &mut self,
+        #[allow(unused_variables)] path: impl AsRef<std::path::Path>,
+    ) -> &mut Self {
+        #[cfg(any(debug_assertions, feature = "doc_always"))]
+        {
+            let path = path.as_ref();
+            info!("Exporting API schema to {}", path.display());
+            let registry = self.world.resource::<AppTypeRegistry>().read();
+            let providers: &APIProviders<T> = self.world.resource();
+            let mut schema = providers.gen_schema(&registry);
+            if let Some(hooks) = self.world.get_resource::<ScriptHooks<T>>() {
+                schema.hooks.extend(hooks.hooks.iter().cloned());
+            }
+            let result = schema
+                .to_json()
+                .map_err(|e| e.to_string())
+                .and_then(|json| {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                    }
+                    std::fs::write(path, json).map_err(|e| e.to_string())
+                });
+            if let Err(e) = result {
+                error!("Could not export API schema: {}", e);
+            }
+        }
+
+        self
+    }
 }
 
 /// Trait for app builder notation
@@ -97,6 +144,7 @@ impl AddScriptHost for App {
     {
         T::register_with_app_in_set(self, schedule, set);
         self.init_resource::<T>();
+        self.init_resource::<ScriptHooks<T>>();
         self.add_event::<ScriptLoaded>();
         self
     }
@@ -107,6 +155,7 @@ impl AddScriptHost for App {
     {
         T::register_with_app(self, schedule);
         self.init_resource::<T>();
+        self.init_resource::<ScriptHooks<T>>();
         self.add_event::<ScriptLoaded>();
         self
     }
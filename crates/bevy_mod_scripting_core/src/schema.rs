@@ -0,0 +1,308 @@
+//! A language-neutral description of the scripting API
+use bevy::reflect::{TypeInfo, TypeRegistry, VariantInfo};
+use serde::{Deserialize, Serialize};
+
+/// A machine-readable description of everything exposed to the scripts of a host,
+/// meant to drive editor plugins, documentation and binding validators regardless of the scripting language.
+///
+/// Built from the reflected types in the `AppTypeRegistry` merged with the schemas of each `APIProvider`,
+/// see [`APIProvider::get_api_schema`](crate::hosts::APIProvider::get_api_schema). Type names are the rust type paths of reflected types.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiSchema {
+    pub types: Vec<TypeSchema>,
+    pub functions: Vec<FunctionSchema>,
+    pub globals: Vec<GlobalSchema>,
+    pub hooks: Vec<HookSchema>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeKind {
+    Struct,
+    TupleStruct,
+    Tuple,
+    List,
+    Array,
+    Map,
+    Enum,
+    /// A type exposed without any reflected structure
+    #[default]
+    Opaque,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeSchema {
+    /// The full name of the type, i.e. `bevy_transform::components::transform::Transform`
+    pub name: String,
+    /// The short name of the type, i.e. `Transform`
+    pub short_name: String,
+    pub kind: TypeKind,
+    pub docs: Option<String>,
+    /// Named fields, or the fields of tuple structs and tuples named by their index
+    pub fields: Vec<FieldSchema>,
+    pub variants: Vec<VariantSchema>,
+    pub methods: Vec<FunctionSchema>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    pub name: String,
+    pub ty: String,
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantSchema {
+    pub name: String,
+    pub fields: Vec<FieldSchema>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArgSchema {
+    pub name: String,
+    pub ty: String,
+}
+
+/// A free function, or a method when part of a [`TypeSchema`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionSchema {
+    pub name: String,
+    /// true for methods called on an instance of the type
+    pub receiver: bool,
+    pub args: Vec<ArgSchema>,
+    pub returns: Option<String>,
+    pub docs: Option<String>,
+}
+
+/// A value available to every script, such as `world`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlobalSchema {
+    pub name: String,
+    pub ty: String,
+    pub docs: Option<String>,
+}
+
+/// A callback scripts can define which the host calls in response to events
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookSchema {
+    pub name: String,
+    pub args: Vec<ArgSchema>,
+    pub docs: Option<String>,
+}
+
+impl HookSchema {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Appends an argument the hook is called with
+    pub fn with_arg(mut self, name: impl Into<String>, ty: impl Into<String>) -> Self {
+        self.args.push(ArgSchema {
+            name: name.into(),
+            ty: ty.into(),
+        });
+        self
+    }
+
+    pub fn with_docs(mut self, docs: impl Into<String>) -> Self {
+        self.docs = Some(docs.into());
+        self
+    }
+
+    /// i.e. `on_update(dt: f32)`
+    pub fn signature(&self) -> String {
+        let args = self
+            .args
+            .iter()
+            .map(|a| format!("{}: {}", a.name, a.ty))
+            .collect::<Vec<_>>();
+        format!("{}({})", self.name, args.join(", "))
+    }
+}
+
+impl ApiSchema {
+    /// Describes every type in the registry which has type info, sorted by name
+    pub fn from_type_registry(registry: &TypeRegistry) -> Self {
+        let mut types = registry
+            .iter()
+            .map(|registration| TypeSchema::from_type_info(registration.type_info()))
+            .collect::<Vec<_>>();
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            types,
+            ..Default::default()
+        }
+    }
+
+    /// Merges another schema into this one.
+    ///
+    /// Types with the same name are combined, the kind and structure of reflected types take precedence
+    /// while the docs and methods of both are kept.
+    pub fn merge(&mut self, o: Self) {
+        for ty in o.types {
+            match self.types.iter_mut().find(|t| t.name == ty.name) {
+                Some(existing) => existing.merge(ty),
+                None => self.types.push(ty),
+            }
+        }
+        self.functions.extend(o.functions);
+        self.globals.extend(o.globals);
+        self.hooks.extend(o.hooks);
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl TypeSchema {
+    /// Describes the structure of a reflected type
+    pub fn from_type_info(info: &TypeInfo) -> Self {
+        let mut schema = Self {
+            name: info.type_path().to_owned(),
+            short_name: info.type_path_table().short_path().to_owned(),
+            ..Default::default()
+        };
+
+        match info {
+            TypeInfo::Struct(info) => {
+                schema.kind = TypeKind::Struct;
+                schema.fields = info
+                    .iter()
+                    .map(|f| field(f.name().to_owned(), f.type_path()))
+                    .collect();
+            }
+            TypeInfo::TupleStruct(info) => {
+                schema.kind = TypeKind::TupleStruct;
+                schema.fields = info
+                    .iter()
+                    .map(|f| field(f.index().to_string(), f.type_path()))
+                    .collect();
+            }
+            TypeInfo::Tuple(info) => {
+                schema.kind = TypeKind::Tuple;
+                schema.fields = info
+                    .iter()
+                    .map(|f| field(f.index().to_string(), f.type_path()))
+                    .collect();
+            }
+            TypeInfo::List(_) => schema.kind = TypeKind::List,
+            TypeInfo::Array(_) => schema.kind = TypeKind::Array,
+            TypeInfo::Map(_) => schema.kind = TypeKind::Map,
+            TypeInfo::Enum(info) => {
+                schema.kind = TypeKind::Enum;
+                schema.variants = info
+                    .iter()
+                    .map(|variant| VariantSchema {
+                        name: variant.name().to_owned(),
+                        fields: match variant {
+                            VariantInfo::Struct(v) => v
+                                .iter()
+                                .map(|f| field(f.name().to_owned(), f.type_path()))
+                                .collect(),
+                            VariantInfo::Tuple(v) => v
+                                .iter()
+                                .map(|f| field(f.index().to_string(), f.type_path()))
+                                .collect(),
+                            VariantInfo::Unit(_) => Vec::default(),
+                        },
+                    })
+                    .collect();
+            }
+            TypeInfo::Value(_) => schema.kind = TypeKind::Opaque,
+        }
+
+        schema
+    }
+
+    fn merge(&mut self, o: Self) {
+        if self.kind == TypeKind::Opaque {
+            self.kind = o.kind;
+            self.fields = o.fields;
+            self.variants = o.variants;
+        }
+        self.docs = match (self.docs.take(), o.docs) {
+            (Some(a), Some(b)) => Some(format!("{a}\n{b}")),
+            (a, b) => a.or(b),
+        };
+        self.methods.extend(o.methods);
+    }
+}
+
+fn field(name: String, ty: &str) -> FieldSchema {
+    FieldSchema {
+        name,
+        ty: ty.to_owned(),
+        docs: None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::reflect::{Reflect, TypePath};
+
+    use super::*;
+
+    #[derive(Reflect)]
+    struct Player {
+        health: f32,
+    }
+
+    #[derive(Reflect)]
+    enum Shape {
+        Circle(f32),
+        Empty,
+    }
+
+    #[test]
+    fn reflected_types_are_merged_with_provider_schemas() {
+        let mut registry = TypeRegistry::empty();
+        registry.register::<Player>();
+        registry.register::<Shape>();
+
+        let mut schema = ApiSchema::from_type_registry(&registry);
+        schema.merge(ApiSchema {
+            types: vec![TypeSchema {
+                name: Player::type_path().to_owned(),
+                methods: vec![FunctionSchema {
+                    name: "heal".to_owned(),
+                    receiver: true,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            globals: vec![GlobalSchema {
+                name: "world".to_owned(),
+                ty: "World".to_owned(),
+                docs: None,
+            }],
+            ..Default::default()
+        });
+
+        let player = schema
+            .types
+            .iter()
+            .find(|t| t.short_name == "Player")
+            .unwrap();
+        assert_eq!(player.kind, TypeKind::Struct);
+        assert_eq!(player.fields[0].name, "health");
+        assert_eq!(player.fields[0].ty, "f32");
+        assert_eq!(player.methods[0].name, "heal");
+
+        let shape = schema
+            .types
+            .iter()
+            .find(|t| t.short_name == "Shape")
+            .unwrap();
+        assert_eq!(shape.variants.len(), 2);
+        assert_eq!(shape.variants[0].fields[0].name, "0");
+
+        let json = schema.to_json().unwrap();
+        let roundtrip: ApiSchema = serde_json::from_str(&json).unwrap();
+        assert_eq!(roundtrip, schema);
+    }
+}
@@ -5,7 +5,11 @@ use bevy_event_priority::PriorityEventReader;
 
 use crate::{
     event::ScriptLoaded,
-    prelude::{APIProviders, Script, ScriptCollection, ScriptContexts, ScriptData, ScriptHost},
+    hosts::ScriptLoadParams,
+    prelude::{
+        APIProviders, Script, ScriptAccess, ScriptCollection, ScriptContexts, ScriptData,
+        ScriptHost,
+    },
     ScriptErrorEvent,
 };
 
@@ -16,6 +20,18 @@ pub enum ScriptSystemSet {
     EventHandling,
 }
 
+/// Determines how script event handlers schedule the scripts they run
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScriptExecutionMode {
+    /// Every script is executed one after the other
+    #[default]
+    Sequential,
+    /// Scripts which declared [`ScriptAccess::ReadOnly`] access are executed first and in parallel
+    /// (if the host supports it, see [`ScriptHost::handle_events_parallel`]),
+    /// followed by the remaining scripts executed sequentially
+    Parallel,
+}
+
 /// Handles creating contexts for new/modified scripts
 /// Scripts are likely not loaded instantly at this point, so most of the time
 /// this system simply inserts an empty context
@@ -23,30 +39,22 @@ pub fn script_add_synchronizer<H: ScriptHost + 'static>(
     query: Query<
         (
             Entity,
-            &ScriptCollection<H::ScriptAsset>,
-            Ref<ScriptCollection<H::ScriptAsset>>,
+            &ScriptCollection<H::ScriptAsset, H::Label>,
+            Ref<ScriptCollection<H::ScriptAsset, H::Label>>,
         ),
-        Changed<ScriptCollection<H::ScriptAsset>>,
+        Changed<ScriptCollection<H::ScriptAsset, H::Label>>,
     >,
-    mut host: ResMut<H>,
-    mut providers: ResMut<APIProviders<H>>,
-    script_assets: Res<Assets<H::ScriptAsset>>,
-    mut contexts: ResMut<ScriptContexts<H::ScriptContext>>,
-    mut event_writer: EventWriter<ScriptLoaded>,
+    mut params: ScriptLoadParams<H>,
 ) {
     debug!("Handling addition/modification of scripts");
 
     query.iter().for_each(|(entity, new_scripts, tracker)| {
         if tracker.is_added() {
             new_scripts.scripts.iter().for_each(|new_script| {
-                Script::<H::ScriptAsset>::insert_new_script_context::<H>(
-                    &mut host,
+                Script::<H::ScriptAsset, H::Label>::insert_new_script_context::<H>(
                     new_script,
                     entity,
-                    &script_assets,
-                    &mut providers,
-                    &mut contexts,
-                    &mut event_writer,
+                    &mut params,
                 )
             })
         } else {
@@ -55,7 +63,8 @@ pub fn script_add_synchronizer<H: ScriptHost + 'static>(
             // we only care about added or removed scripts here
             // if the script asset gets changed we deal with that elsewhere
 
-            let context_ids = contexts
+            let context_ids = params
+                .contexts
                 .context_entities
                 .iter()
                 .filter_map(|(sid, (e, _, _))| if *e == entity { Some(sid) } else { None })
@@ -71,19 +80,15 @@ pub fn script_add_synchronizer<H: ScriptHost + 'static>(
             let added_scripts = script_ids.difference(&context_ids);
 
             for r in removed_scripts {
-                contexts.remove_context(*r);
+                params.contexts.remove_context(*r);
             }
 
             for a in added_scripts {
                 let script = new_scripts.scripts.iter().find(|e| &e.id() == a).unwrap();
-                Script::<H::ScriptAsset>::insert_new_script_context::<H>(
-                    &mut host,
+                Script::<H::ScriptAsset, H::Label>::insert_new_script_context::<H>(
                     script,
                     entity,
-                    &script_assets,
-                    &mut providers,
-                    &mut contexts,
-                    &mut event_writer,
+                    &mut params,
                 )
             }
         }
@@ -92,8 +97,8 @@ pub fn script_add_synchronizer<H: ScriptHost + 'static>(
 
 /// Handles the removal of script components and their contexts
 pub fn script_remove_synchronizer<H: ScriptHost>(
-    mut query: RemovedComponents<ScriptCollection<H::ScriptAsset>>,
-    mut contexts: ResMut<ScriptContexts<H::ScriptContext>>,
+    mut query: RemovedComponents<ScriptCollection<H::ScriptAsset, H::Label>>,
+    mut contexts: ResMut<ScriptContexts<H::ScriptContext, H::Label>>,
 ) {
     for v in query.read() {
         // we know that this entity used to have a script component
@@ -114,12 +119,8 @@ pub fn script_remove_synchronizer<H: ScriptHost>(
 /// Reloads hot-reloaded scripts, or loads missing contexts for scripts which were added but not loaded
 pub fn script_hot_reload_handler<H: ScriptHost>(
     mut events: EventReader<AssetEvent<H::ScriptAsset>>,
-    mut host: ResMut<H>,
-    scripts: Query<&ScriptCollection<H::ScriptAsset>>,
-    script_assets: Res<Assets<H::ScriptAsset>>,
-    mut providers: ResMut<APIProviders<H>>,
-    mut contexts: ResMut<ScriptContexts<H::ScriptContext>>,
-    mut event_writer: EventWriter<ScriptLoaded>,
+    scripts: Query<&ScriptCollection<H::ScriptAsset, H::Label>>,
+    mut params: ScriptLoadParams<H>,
 ) {
     for e in events.read() {
         let (handle, created) = match e {
@@ -137,17 +138,13 @@ pub fn script_hot_reload_handler<H: ScriptHost>(
             for script in &scripts.scripts {
                 // the script could have well loaded in the same frame that it was added
                 // in that case it will have a context attached and we do not want to reload it
-                if script.handle().id() == *handle
-                    && !(contexts.has_context(script.id()) && created)
-                {
-                    Script::<H::ScriptAsset>::reload_script::<H>(
-                        &mut host,
-                        script,
-                        &script_assets,
-                        &mut providers,
-                        &mut contexts,
-                        &mut event_writer,
-                    );
+                let is_script = script.handle().id() == *handle
+                    && !(params.contexts.has_context(script.id()) && created);
+                // scripts are also reloaded whenever a module they depend on changes or becomes available
+                let is_dependent = params.contexts.depends_on(script.id(), handle.untyped());
+
+                if is_script || is_dependent {
+                    Script::<H::ScriptAsset, H::Label>::reload_script::<H>(script, &mut params);
                 }
             }
         }
@@ -173,16 +170,25 @@ pub fn script_event_handler<H: ScriptHost, const MAX: u32, const MIN: u32>(world
         return;
     }
 
-    let mut ctxts: ScriptContexts<H::ScriptContext> = world.remove_resource().unwrap();
+    let mut ctxts: ScriptContexts<H::ScriptContext, H::Label> = world.remove_resource().unwrap();
 
     let mut host: H = world.remove_resource().unwrap();
     let mut providers: APIProviders<H> = world.remove_resource().unwrap();
 
+    let parallel = world
+        .get_resource::<ScriptExecutionMode>()
+        .is_some_and(|mode| *mode == ScriptExecutionMode::Parallel);
+
     // we need a resource scope to be able to simultaneously access the contexts as well
     // as provide world access to scripts
     // afaik there is not really a better way to do this in bevy just now
-    let ctx_iter = ctxts
-        .context_entities
+    let ScriptContexts {
+        context_entities,
+        context_access,
+        ..
+    } = &mut ctxts;
+
+    let (read_only, exclusive): (Vec<_>, Vec<_>) = context_entities
         .iter_mut()
         .filter_map(|(sid, (entity, o, name))| {
             let ctx = match o {
@@ -198,11 +204,22 @@ pub fn script_event_handler<H: ScriptHost, const MAX: u32, const MIN: u32>(world
                 },
                 ctx,
             ))
+        })
+        .partition(|(script_data, _)| {
+            parallel
+                && context_access
+                    .get(&script_data.sid)
+                    .copied()
+                    .unwrap_or_default()
+                    == ScriptAccess::ReadOnly
         });
 
     // safety: we have unique access to world, future accesses are protected
     // by the lock in the pointer
-    host.handle_events(world, &events, ctx_iter, &mut providers);
+    if !read_only.is_empty() {
+        host.handle_events_parallel(world, &events, read_only, &mut providers);
+    }
+    host.handle_events(world, &events, exclusive.into_iter(), &mut providers);
 
     world.insert_resource(ctxts);
     world.insert_resource(host);
@@ -1,18 +1,72 @@
+use std::cell::RefCell;
 use std::ops::Deref;
 use std::sync::Arc;
 
-use bevy::prelude::World;
+use bevy::ecs::system::{Command, CommandQueue};
+use bevy::prelude::{Resource, World};
 use parking_lot::{
-    MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    MappedRwLockReadGuard, MappedRwLockWriteGuard, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
 };
 
+/// Determines when structural changes to the world requested by scripts
+/// (spawning and despawning entities, removing components, changing the hierarchy etc.) are applied.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WorldMutationMode {
+    /// Changes are applied as soon as the script requests them.
+    #[default]
+    Immediate,
+    /// Changes are recorded into a per-script [`ScriptCommandQueue`] and applied after the script
+    /// finishes handling its events. Entities spawned by scripts are reserved and their IDs returned immediately.
+    Deferred,
+}
+
+/// A queue of world commands recorded by a single script while it handles events.
+///
+/// Cloning the queue yields another handle to the same underlying [`CommandQueue`].
+#[derive(Clone, Default)]
+pub struct ScriptCommandQueue(Arc<Mutex<CommandQueue>>);
+
+impl std::fmt::Debug for ScriptCommandQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ScriptCommandQueue").finish_non_exhaustive()
+    }
+}
+
+impl ScriptCommandQueue {
+    /// Records a command to be applied later
+    pub fn push<C: Command>(&self, command: C) {
+        self.0.lock().push(command);
+    }
+
+    /// Applies all the recorded commands to the world, leaving the queue empty.
+    ///
+    /// Entities reserved while the commands were recorded are spawned before any command runs.
+    pub fn apply(&self, world: &mut World) {
+        self.0.lock().apply(world);
+    }
+}
+
 /// Pointer to a bevy world, safely allows multiple access via RwLock
 ///
 /// If the original [WorldPointerGuard] that created this pointer is dropped,
 /// the `read` and `write` methods will panic, and the "try" variants will
 /// return `None`.
+///
+/// A pointer can optionally carry a [`ScriptCommandQueue`], in which case script API's
+/// should record structural changes into it instead of applying them immediately, see [`WorldPointer::deferred`].
+///
+/// Read-only pointers (see [`WorldPointer::read_only`]) never hand out write guards, which allows
+/// multiple scripts holding them to run in parallel.
 #[derive(Debug, Clone)]
-pub struct WorldPointer(Arc<RwLock<Option<*mut World>>>);
+pub struct WorldPointer {
+    world: Arc<RwLock<Option<*mut World>>>,
+    commands: Option<ScriptCommandQueue>,
+    read_only: bool,
+}
+
+thread_local! {
+    static CURRENT_WORLD: RefCell<Option<WorldPointer>> = const { RefCell::new(None) };
+}
 
 /// Guarded pointer to a bevy world, can be used to `clone` additional
 /// [WorldPointer]s for safe access.
@@ -44,7 +98,11 @@ impl WorldPointerGuard {
     /// [WorldPointer]s.
     #[allow(clippy::arc_with_non_send_sync)]
     pub unsafe fn new(world: &mut World) -> Self {
-        WorldPointerGuard(WorldPointer(Arc::new(RwLock::new(Some(world)))))
+        WorldPointerGuard(WorldPointer {
+            world: Arc::new(RwLock::new(Some(world))),
+            commands: None,
+            read_only: false,
+        })
     }
 }
 
@@ -53,11 +111,91 @@ impl Drop for WorldPointerGuard {
         // Being explicit about the types here to make sure we're getting things
         // correct.
         let world_ptr: &WorldPointer = &self.0;
-        let _: Option<*mut World> = RwLock::write(&world_ptr.0).take();
+        let _: Option<*mut World> = RwLock::write(&world_ptr.world).take();
     }
 }
 
 impl WorldPointer {
+    /// Returns a pointer to the same world which records structural changes into a new, empty [`ScriptCommandQueue`].
+    ///
+    /// The recorded commands are applied with [`WorldPointer::apply_deferred`].
+    pub fn deferred(&self) -> Self {
+        Self {
+            world: self.world.clone(),
+            commands: Some(Default::default()),
+            read_only: self.read_only,
+        }
+    }
+
+    /// Returns a deferred pointer to the same world which cannot be used to obtain write guards,
+    /// the `write` methods will panic and the "try" variants will return `None`.
+    ///
+    /// All changes made through this pointer must be recorded into its [`ScriptCommandQueue`].
+    pub fn read_only(&self) -> Self {
+        Self {
+            read_only: true,
+            ..self.deferred()
+        }
+    }
+
+    /// Returns true if this pointer cannot be used to obtain write guards, see [`WorldPointer::read_only`]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Returns a pointer to the same world which is deferred only if the given mode is [`WorldMutationMode::Deferred`]
+    pub fn with_mode(&self, mode: WorldMutationMode) -> Self {
+        match mode {
+            WorldMutationMode::Immediate => Self {
+                world: self.world.clone(),
+                commands: None,
+                read_only: self.read_only,
+            },
+            WorldMutationMode::Deferred => self.deferred(),
+        }
+    }
+
+    /// Returns the command queue structural changes should be recorded into, if this pointer is deferred.
+    pub fn commands(&self) -> Option<&ScriptCommandQueue> {
+        self.commands.as_ref()
+    }
+
+    /// Applies all the commands recorded through this pointer so far, does nothing if the pointer is not deferred.
+    ///
+    /// Works for read-only pointers too, blocks until write access to the world is available,
+    /// panics if the pointer has gone out of scope.
+    pub fn apply_deferred(&self) {
+        if let Some(commands) = &self.commands {
+            let mut world = self
+                .try_write_inner(true)
+                .expect("the world pointer is out of scope");
+            commands.apply(&mut world);
+        }
+    }
+
+    /// Runs the given closure with this pointer set as the world of the script executing on the current thread,
+    /// see [`WorldPointer::current`]. The previously set world is restored afterwards.
+    ///
+    /// Script hosts wrap their calls into scripts with this, so that conversions with no access to the script context
+    /// (i.e. converting event arguments) can still reach the world.
+    pub fn enter<O>(&self, f: impl FnOnce() -> O) -> O {
+        struct Restore(Option<WorldPointer>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT_WORLD.with(|current| *current.borrow_mut() = self.0.take());
+            }
+        }
+
+        let _restore = Restore(CURRENT_WORLD.with(|current| current.replace(Some(self.clone()))));
+        f()
+    }
+
+    /// Returns the world of the script executing on the current thread, if any, see [`WorldPointer::enter`]
+    pub fn current() -> Option<WorldPointer> {
+        CURRENT_WORLD.with(|current| current.borrow().clone())
+    }
+
     /// Returns a read guard which can be used for immutable world access.
     ///
     /// Panics if the pointer is already locked or has gone out of scope.
@@ -67,8 +205,12 @@ impl WorldPointer {
 
     /// Returns a write guard which can be used for mutable world access.
     ///
-    /// Panics if the pointer is already locked or has gone out of scope.
+    /// Panics if the pointer is read-only, already locked or has gone out of scope.
     pub fn write(&self) -> MappedRwLockWriteGuard<World> {
+        assert!(
+            !self.read_only,
+            "write access through a read-only world pointer"
+        );
         self.try_write()
             .expect("concurrent read/write world access")
     }
@@ -83,9 +225,12 @@ impl WorldPointer {
 
     /// Returns a write guard which can be used for mutable world access.
     ///
-    /// Returns `None` if the pointer is already locked or has gone out of
+    /// Returns `None` if the pointer is read-only, already locked or has gone out of
     /// scope.
     pub fn try_write(&self) -> Option<MappedRwLockWriteGuard<World>> {
+        if self.read_only {
+            return None;
+        }
         self.try_write_inner(false)
     }
 
@@ -100,9 +245,13 @@ impl WorldPointer {
 
     /// Returns a write guard which can be used for mutable world access.
     ///
-    /// Panics if the pointer has gone out of scope. May block if another thread
+    /// Panics if the pointer is read-only or has gone out of scope. May block if another thread
     /// holds the lock.
     pub fn write_blocking(&self) -> MappedRwLockWriteGuard<World> {
+        assert!(
+            !self.read_only,
+            "write access through a read-only world pointer"
+        );
         self.try_write_blocking()
             .expect("the world pointer is out of scope")
     }
@@ -117,17 +266,20 @@ impl WorldPointer {
 
     /// Returns a write guard which can be used for mutable world access.
     ///
-    /// Returns `None` if has gone out of scope. May block if another thread
+    /// Returns `None` if the pointer is read-only or has gone out of scope. May block if another thread
     /// holds the lock.
     pub fn try_write_blocking(&self) -> Option<MappedRwLockWriteGuard<World>> {
+        if self.read_only {
+            return None;
+        }
         self.try_write_inner(true)
     }
 
     fn try_read_inner(&self, blocking: bool) -> Option<MappedRwLockReadGuard<World>> {
         let guard = if blocking {
-            self.0.read()
+            self.world.read()
         } else {
-            self.0.try_read()?
+            self.world.try_read()?
         };
         // Check if the inner pointer is there so we can invert the `Option`.
         if guard.is_none() {
@@ -142,9 +294,9 @@ impl WorldPointer {
 
     fn try_write_inner(&self, blocking: bool) -> Option<MappedRwLockWriteGuard<World>> {
         let guard = if blocking {
-            self.0.write()
+            self.world.write()
         } else {
-            self.0.try_write()?
+            self.world.try_write()?
         };
         // Check if the inner pointer is there so we can invert the `Option`.
         if guard.is_none() {
@@ -157,3 +309,85 @@ impl WorldPointer {
         ))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Entity;
+
+    use super::*;
+
+    #[test]
+    fn deferred_pointer_applies_commands_on_request() {
+        let mut world = World::new();
+        let despawned = world.spawn(()).id();
+
+        let guard = unsafe { WorldPointerGuard::new(&mut world) };
+        let ptr = guard.with_mode(WorldMutationMode::Deferred);
+
+        let reserved = ptr.read().entities().reserve_entity();
+        ptr.commands().unwrap().push(move |w: &mut World| {
+            w.despawn(despawned);
+        });
+
+        assert!(ptr.read().get_entity(despawned).is_some());
+        assert!(ptr.read().get_entity(reserved).is_none());
+
+        ptr.apply_deferred();
+
+        assert!(ptr.read().get_entity(despawned).is_none());
+        assert!(ptr.read().get_entity(reserved).is_some());
+        drop(guard);
+
+        assert_eq!(
+            world
+                .iter_entities()
+                .map(|e| e.id())
+                .collect::<Vec<Entity>>(),
+            vec![reserved]
+        );
+    }
+
+    #[test]
+    fn immediate_pointer_has_no_queue() {
+        let mut world = World::new();
+        let guard = unsafe { WorldPointerGuard::new(&mut world) };
+
+        assert!(guard.commands().is_none());
+        assert!(guard
+            .with_mode(WorldMutationMode::Immediate)
+            .commands()
+            .is_none());
+        assert!(guard.deferred().commands().is_some());
+    }
+
+    #[test]
+    fn entered_pointer_is_current_until_closure_returns() {
+        let mut world = World::new();
+        let guard = unsafe { WorldPointerGuard::new(&mut world) };
+
+        assert!(WorldPointer::current().is_none());
+        guard.read_only().enter(|| {
+            assert!(WorldPointer::current().unwrap().is_read_only());
+            guard.enter(|| assert!(!WorldPointer::current().unwrap().is_read_only()));
+            assert!(WorldPointer::current().unwrap().is_read_only());
+        });
+        assert!(WorldPointer::current().is_none());
+    }
+
+    #[test]
+    fn read_only_pointer_denies_writes_but_applies_commands() {
+        let mut world = World::new();
+        let guard = unsafe { WorldPointerGuard::new(&mut world) };
+        let ptr = guard.read_only();
+
+        assert!(ptr.is_read_only());
+        assert!(ptr.try_write().is_none());
+        assert!(ptr.try_write_blocking().is_none());
+
+        let reserved = ptr.read().entities().reserve_entity();
+        ptr.apply_deferred();
+
+        assert!(guard.read().get_entity(reserved).is_some());
+        assert!(guard.try_write().is_some());
+    }
+}
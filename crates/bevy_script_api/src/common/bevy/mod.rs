@@ -1,9 +1,8 @@
-use crate::ReflectReference;
+use crate::{script_ref::ReflectedValue, ReflectReference};
 /// Common functionality for all script hosts
 use bevy::{
     ecs::{
         component::ComponentId,
-        query::QueryBuilder,
         system::Command,
         world::{EntityRef, World},
     },
@@ -11,15 +10,20 @@ use bevy::{
         AppTypeRegistry, BuildWorldChildren, Children, DespawnChildrenRecursive, DespawnRecursive,
         Entity, Parent, ReflectComponent, ReflectDefault, ReflectResource,
     },
+    log::warn,
     reflect::{
         DynamicArray, DynamicEnum, DynamicList, DynamicMap, DynamicStruct, DynamicTuple,
-        DynamicTupleStruct, TypeRegistration,
+        DynamicTupleStruct, Reflect, TypeInfo, TypeRegistration,
     },
 };
-use bevy_mod_scripting_core::{prelude::ScriptError, world::WorldPointer};
-use parking_lot::MappedRwLockWriteGuard;
+use bevy_mod_scripting_core::{
+    prelude::ScriptError,
+    schema::{ApiSchema, ArgSchema, FunctionSchema, GlobalSchema, TypeKind, TypeSchema},
+    world::WorldPointer,
+};
+use parking_lot::MappedRwLockReadGuard;
 use std::{
-    any::Any,
+    any::{type_name, Any},
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -31,6 +35,11 @@ pub trait GetWorld {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "rune", derive(bevy_mod_scripting_rune::prelude::rune::Any))]
+#[cfg_attr(
+    feature = "rune",
+    rune(module = bevy_mod_scripting_rune::prelude::rune, name = TypeRegistration)
+)]
 pub struct ScriptTypeRegistration(pub(crate) Arc<TypeRegistration>);
 
 impl ScriptTypeRegistration {
@@ -72,6 +81,11 @@ impl Deref for ScriptTypeRegistration {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "rune", derive(bevy_mod_scripting_rune::prelude::rune::Any))]
+#[cfg_attr(
+    feature = "rune",
+    rune(module = bevy_mod_scripting_rune::prelude::rune, name = QueryBuilder)
+)]
 pub struct ScriptQueryBuilder {
     world: ScriptWorld,
     components: Vec<ScriptTypeRegistration>,
@@ -117,6 +131,11 @@ impl ScriptQueryBuilder {
 pub struct ScriptQueryResult(pub Entity, pub Vec<ReflectReference>);
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "rune", derive(bevy_mod_scripting_rune::prelude::rune::Any))]
+#[cfg_attr(
+    feature = "rune",
+    rune(module = bevy_mod_scripting_rune::prelude::rune, name = World)
+)]
 pub struct ScriptWorld(WorldPointer);
 
 impl std::fmt::Display for ScriptWorld {
@@ -156,6 +175,150 @@ impl ScriptWorld {
         Self(ptr)
     }
 
+    /// Describes the methods of the world available in every scripting language, for use in an `ApiSchema`.
+    /// Types are referred to by their full rust type paths
+    pub fn api_schema() -> TypeSchema {
+        let entity = type_name::<Entity>();
+        let registration = type_name::<ScriptTypeRegistration>();
+        let value = type_name::<ReflectedValue>();
+        let query = type_name::<ScriptQueryBuilder>();
+
+        let methods: Vec<(&str, Vec<(&str, String)>, Option<String>, &str)> = vec![
+            (
+                "get_type_by_name",
+                vec![("type_name", "String".to_owned())],
+                Some(format!("Option<{registration}>")),
+                "Retrieves type information given either a short (`MyType`) or fully qualified rust type name (`MyModule::MyType`).",
+            ),
+            (
+                "add_default_component",
+                vec![("entity", entity.to_owned()), ("type", registration.to_owned())],
+                Some(value.to_owned()),
+                "Adds the default value of the given component type to the entity and returns a reference to it.",
+            ),
+            (
+                "get_component",
+                vec![("entity", entity.to_owned()), ("type", registration.to_owned())],
+                Some(format!("Option<{value}>")),
+                "Retrieves a reference to the component of the given type on the entity, if it has one.",
+            ),
+            (
+                "has_component",
+                vec![("entity", entity.to_owned()), ("type", registration.to_owned())],
+                Some("bool".to_owned()),
+                "Returns true if the entity has a component of the given type.",
+            ),
+            (
+                "remove_component",
+                vec![("entity", entity.to_owned()), ("type", registration.to_owned())],
+                None,
+                "Removes the component of the given type from the entity.",
+            ),
+            (
+                "get_resource",
+                vec![("type", registration.to_owned())],
+                Some(format!("Option<{value}>")),
+                "Retrieves a reference to the resource of the given type, if it exists.",
+            ),
+            (
+                "has_resource",
+                vec![("type", registration.to_owned())],
+                Some("bool".to_owned()),
+                "Returns true if a resource of the given type exists.",
+            ),
+            (
+                "remove_resource",
+                vec![("type", registration.to_owned())],
+                None,
+                "Removes the resource of the given type.",
+            ),
+            (
+                "query",
+                vec![("types", format!("Vec<{registration}>"))],
+                Some(query.to_owned()),
+                "Creates a query over the entities with all of the given components.",
+            ),
+            (
+                "get_children",
+                vec![("parent", entity.to_owned())],
+                Some(format!("Vec<{entity}>")),
+                "Returns the children of the entity.",
+            ),
+            (
+                "get_parent",
+                vec![("entity", entity.to_owned())],
+                Some(format!("Option<{entity}>")),
+                "Returns the parent of the entity, if it has one.",
+            ),
+            (
+                "push_children",
+                vec![("parent", entity.to_owned()), ("children", format!("Vec<{entity}>"))],
+                None,
+                "Attaches the children to the parent, after its existing children.",
+            ),
+            (
+                "remove_children",
+                vec![("parent", entity.to_owned()), ("children", format!("Vec<{entity}>"))],
+                None,
+                "Detaches the children from the parent.",
+            ),
+            (
+                "insert_children",
+                vec![("parent", entity.to_owned()), ("index", "usize".to_owned()), ("children", format!("Vec<{entity}>"))],
+                None,
+                "Inserts the children into the children of the parent at the given index.",
+            ),
+            (
+                "despawn_children_recursive",
+                vec![("entity", entity.to_owned())],
+                None,
+                "Despawns the children of the entity and all of their descendants.",
+            ),
+            (
+                "despawn_recursive",
+                vec![("entity", entity.to_owned())],
+                None,
+                "Despawns the entity and all of its descendants.",
+            ),
+            (
+                "spawn",
+                vec![],
+                Some(entity.to_owned()),
+                "Spawns a new entity and returns it.",
+            ),
+            (
+                "despawn",
+                vec![("entity", entity.to_owned())],
+                Some("bool".to_owned()),
+                "Despawns the given entity if it exists, returns true if deletion was successfull.",
+            ),
+        ];
+
+        TypeSchema {
+            name: type_name::<Self>().to_owned(),
+            short_name: "World".to_owned(),
+            kind: TypeKind::Opaque,
+            docs: Some("The world the script lives in.".to_owned()),
+            methods: methods
+                .into_iter()
+                .map(|(name, args, returns, docs)| FunctionSchema {
+                    name: name.to_owned(),
+                    receiver: true,
+                    args: args
+                        .into_iter()
+                        .map(|(name, ty)| ArgSchema {
+                            name: name.to_owned(),
+                            ty,
+                        })
+                        .collect(),
+                    returns,
+                    docs: Some(docs.to_owned()),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
     pub fn get_children(&self, parent: Entity) -> Vec<Entity> {
         let w = self.read();
         w.get::<Children>(parent)
@@ -169,36 +332,83 @@ impl ScriptWorld {
     }
 
     pub fn push_child(&self, parent: Entity, child: Entity) {
-        let mut w = self.write();
-        if let Some(mut entity) = w.get_entity_mut(parent) {
-            entity.push_children(&[child]);
-        }
+        self.push_children(parent, &[child]);
     }
 
-    pub fn remove_children(&self, parent: Entity, children: &[Entity]) {
-        let mut w = self.write();
+    pub fn push_children(&self, parent: Entity, children: &[Entity]) {
+        let children = children.to_vec();
+        self.apply_or_defer(move |w| {
+            if let Some(mut entity) = w.get_entity_mut(parent) {
+                entity.push_children(&children);
+            }
+        });
+    }
 
-        if let Some(mut entity) = w.get_entity_mut(parent) {
-            entity.remove_children(children);
-        }
+    pub fn remove_children(&self, parent: Entity, children: &[Entity]) {
+        let children = children.to_vec();
+        self.apply_or_defer(move |w| {
+            if let Some(mut entity) = w.get_entity_mut(parent) {
+                entity.remove_children(&children);
+            }
+        });
     }
 
     pub fn insert_children(&self, parent: Entity, index: usize, children: &[Entity]) {
-        let mut w = self.write();
-
-        if let Some(mut entity) = w.get_entity_mut(parent) {
-            entity.insert_children(index, children);
-        }
+        let children = children.to_vec();
+        self.apply_or_defer(move |w| {
+            if let Some(mut entity) = w.get_entity_mut(parent) {
+                entity.insert_children(index, &children);
+            }
+        });
     }
 
     pub fn despawn_children_recursive(&self, entity: Entity) {
-        let mut w = self.write();
-        DespawnChildrenRecursive { entity }.apply(&mut w);
+        self.apply_or_defer(move |w| DespawnChildrenRecursive { entity }.apply(w));
     }
 
     pub fn despawn_recursive(&self, entity: Entity) {
-        let mut w = self.write();
-        DespawnRecursive { entity }.apply(&mut w);
+        self.apply_or_defer(move |w| DespawnRecursive { entity }.apply(w));
+    }
+
+    /// Spawns a new empty entity and returns its ID.
+    ///
+    /// If this world is deferred the entity is only reserved, and will be spawned once the deferred commands are applied.
+    pub fn spawn(&self) -> Entity {
+        if self.is_deferred() {
+            self.read().entities().reserve_entity()
+        } else {
+            self.write().spawn(()).id()
+        }
+    }
+
+    /// Despawns the given entity, returns true if the entity existed.
+    ///
+    /// If this world is deferred the entity is despawned once the deferred commands are applied.
+    pub fn despawn(&self, entity: Entity) -> bool {
+        if let Some(commands) = self.commands() {
+            let exists = self.read().get_entity(entity).is_some();
+            commands.push(move |w: &mut World| {
+                w.despawn(entity);
+            });
+            exists
+        } else {
+            self.write().despawn(entity)
+        }
+    }
+
+    /// Returns true if structural changes made via this world are deferred
+    /// until the script finishes handling its events, see [`WorldMutationMode`](bevy_mod_scripting_core::world::WorldMutationMode)
+    pub fn is_deferred(&self) -> bool {
+        self.commands().is_some()
+    }
+
+    /// Applies the given structural change immediately or records it in the command queue if this world is deferred.
+    fn apply_or_defer<F: FnOnce(&mut World) + Send + 'static>(&self, f: F) {
+        if let Some(commands) = self.commands() {
+            commands.push(f);
+        } else {
+            f(&mut self.write());
+        }
     }
 
     pub fn get_type_by_name(&self, type_name: &str) -> Option<ScriptTypeRegistration> {
@@ -214,49 +424,39 @@ impl ScriptWorld {
             .map(|registration| ScriptTypeRegistration::new(Arc::new(registration.clone())))
     }
 
+    /// Adds the default value of the given component type to the entity and returns a reference to it.
+    ///
+    /// If this world is deferred (which read-only worlds always are) the component is inserted once the
+    /// deferred commands are applied, until then the returned reference cannot be used.
     pub fn add_default_component(
         &self,
         entity: Entity,
         comp_type: ScriptTypeRegistration,
     ) -> Result<ReflectReference, ScriptError> {
-        let mut w = self.write();
-
-        // Remove: AppTypeRegistry
-        let registry: AppTypeRegistry = w.remove_resource().unwrap();
-
-        let mut entity_ref = w
-            .get_entity_mut(entity)
-            .ok_or_else(|| ScriptError::Other(format!("Entity is not valid {:#?}", entity)))?;
-
         let component_data = comp_type.data::<ReflectComponent>().ok_or_else(|| {
             ScriptError::Other(format!("Not a component {}", comp_type.short_name()))
         })?;
 
-        let registry_lock = registry.read();
-
-        // this is just a formality
-        // TODO: maybe get an add_default impl added to ReflectComponent
-        // this means that we don't require ReflectDefault for adding components!
-        match comp_type.0.type_info(){
-            bevy::reflect::TypeInfo::Struct(_) => component_data.insert(&mut entity_ref, &DynamicStruct::default(), &registry_lock),
-            bevy::reflect::TypeInfo::TupleStruct(_) => component_data.insert(&mut entity_ref, &DynamicTupleStruct::default(), &registry_lock),
-            bevy::reflect::TypeInfo::Tuple(_) => component_data.insert(&mut entity_ref, &DynamicTuple::default(), &registry_lock),
-            bevy::reflect::TypeInfo::List(_) => component_data.insert(&mut entity_ref, &DynamicList::default(), &registry_lock),
-            bevy::reflect::TypeInfo::Array(_) => component_data.insert(&mut entity_ref, &DynamicArray::new(Box::new([])), &registry_lock),
-            bevy::reflect::TypeInfo::Map(_) => component_data.insert(&mut entity_ref, &DynamicMap::default(), &registry_lock),
-            bevy::reflect::TypeInfo::Value(_) => component_data.insert(&mut entity_ref,
-                comp_type.data::<ReflectDefault>().ok_or_else(||
-                    ScriptError::Other(format!("Component {} is a value or dynamic type with no `ReflectDefault` type_data, cannot instantiate sensible value",comp_type.short_name())))?
-                    .default()
-                    .as_ref(),
-                    &registry_lock),
-            bevy::reflect::TypeInfo::Enum(_) => component_data.insert(&mut entity_ref, &DynamicEnum::default(), &registry_lock)
-        };
-        // if we do not drop the lock here, line below will complain registry is still borrowed at drop
-        drop(registry_lock);
+        if !self.read().entities().contains(entity) {
+            return Err(ScriptError::Other(format!(
+                "Entity is not valid {:#?}",
+                entity
+            )));
+        }
 
-        // Insert: AppTypeRegistry
-        w.insert_resource(registry);
+        if let Some(commands) = self.commands() {
+            // fail early for components which cannot be instantiated
+            default_component_value(&comp_type)?;
+
+            let comp_type = comp_type.clone();
+            commands.push(move |w: &mut World| {
+                if let Err(e) = insert_default_component(w, entity, &comp_type) {
+                    warn!("{e}");
+                }
+            });
+        } else {
+            insert_default_component(&mut self.write(), entity, &comp_type)?;
+        }
 
         Ok(ReflectReference::new_component_ref(
             component_data.clone(),
@@ -307,15 +507,33 @@ impl ScriptWorld {
         entity: Entity,
         comp_type: ScriptTypeRegistration,
     ) -> Result<(), ScriptError> {
+        let component_data = comp_type.data::<ReflectComponent>().ok_or_else(|| {
+            ScriptError::Other(format!("Not a component {}", comp_type.short_name()))
+        })?;
+
+        if let Some(commands) = self.commands() {
+            if !self.read().entities().contains(entity) {
+                return Err(ScriptError::Other(format!(
+                    "Entity is not valid {:#?}",
+                    entity
+                )));
+            }
+
+            let component_data = component_data.clone();
+            commands.push(move |w: &mut World| {
+                if let Some(mut entity_ref) = w.get_entity_mut(entity) {
+                    component_data.remove(&mut entity_ref);
+                }
+            });
+            return Ok(());
+        }
+
         let mut w = self.write();
 
         let mut entity_ref = w
             .get_entity_mut(entity)
             .ok_or_else(|| ScriptError::Other(format!("Entity is not valid {:#?}", entity)))?;
 
-        let component_data = comp_type.data::<ReflectComponent>().ok_or_else(|| {
-            ScriptError::Other(format!("Not a component {}", comp_type.short_name()))
-        })?;
         component_data.remove(&mut entity_ref);
         Ok(())
     }
@@ -346,12 +564,12 @@ impl ScriptWorld {
     }
 
     pub fn remove_resource(&mut self, res_type: ScriptTypeRegistration) -> Result<(), ScriptError> {
-        let mut w = self.write();
+        let resource_data = res_type
+            .data::<ReflectResource>()
+            .ok_or_else(|| ScriptError::Other(format!("Not a resource {}", res_type.short_name())))?
+            .clone();
 
-        let resource_data = res_type.data::<ReflectResource>().ok_or_else(|| {
-            ScriptError::Other(format!("Not a resource {}", res_type.short_name()))
-        })?;
-        resource_data.remove(&mut w);
+        self.apply_or_defer(move |w| resource_data.remove(w));
         Ok(())
     }
 
@@ -361,10 +579,11 @@ impl ScriptWorld {
         with: Vec<ScriptTypeRegistration>,
         without: Vec<ScriptTypeRegistration>,
     ) -> Result<Vec<ScriptQueryResult>, ScriptError> {
-        let mut w = self.write();
+        // only read access is needed, so read-only scripts can query the world as well
+        let w = self.read();
 
         let get_id = |component: &ScriptTypeRegistration,
-                      w: &MappedRwLockWriteGuard<World>|
+                      w: &MappedRwLockReadGuard<World>|
          -> Result<ComponentId, ScriptError> {
             w.components()
                 .get_id(component.type_info().type_id())
@@ -395,21 +614,21 @@ impl ScriptWorld {
             .map(|component| get_id(component, &w))
             .collect::<Result<Vec<_>, ScriptError>>()?;
 
-        let mut q = QueryBuilder::<EntityRef>::new(&mut w);
-
-        for (_, id) in &components {
-            q.ref_id(*id);
-        }
-
-        for with_id in with_ids {
-            q.with_id(with_id);
-        }
-
-        for without_id in without_ids {
-            q.without_id(without_id);
-        }
-
-        let query_result: Vec<EntityRef<'_>> = q.build().iter_mut(&mut w).collect();
+        // an archetype matches if it has every fetched and `with` component and none of the `without` ones
+        let query_result: Vec<EntityRef<'_>> = w
+            .archetypes()
+            .iter()
+            .filter(|archetype| {
+                components
+                    .iter()
+                    .map(|(_, id)| id)
+                    .chain(&with_ids)
+                    .all(|id| archetype.contains(*id))
+                    && !without_ids.iter().any(|id| archetype.contains(*id))
+            })
+            .flat_map(|archetype| archetype.entities())
+            .map(|archetype_entity| w.entity(archetype_entity.id()))
+            .collect();
 
         query_result
             .into_iter()
@@ -441,3 +660,77 @@ impl ScriptWorld {
             .collect::<Result<Vec<_>, ScriptError>>()
     }
 }
+
+/// The part of the bevy API shared by every scripting language: the world type and the `world` and `entity` globals.
+/// Languages without global variables expose the globals as functions of the same name instead.
+pub fn bevy_api_schema() -> ApiSchema {
+    ApiSchema {
+        types: vec![ScriptWorld::api_schema()],
+        globals: vec![
+            GlobalSchema {
+                name: "world".to_owned(),
+                ty: type_name::<ScriptWorld>().to_owned(),
+                docs: Some("The world the script lives in.".to_owned()),
+            },
+            GlobalSchema {
+                name: "entity".to_owned(),
+                ty: type_name::<Entity>().to_owned(),
+                docs: Some("The entity the script is attached to.".to_owned()),
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+/// Creates a default value of the given component type, if the type can be instantiated
+fn default_component_value(comp_type: &ScriptTypeRegistration) -> Result<Box<dyn Reflect>, ScriptError> {
+    // this is just a formality
+    // TODO: maybe get an add_default impl added to ReflectComponent
+    // this means that we don't require ReflectDefault for adding components!
+    Ok(match comp_type.0.type_info() {
+        TypeInfo::Struct(_) => Box::<DynamicStruct>::default(),
+        TypeInfo::TupleStruct(_) => Box::<DynamicTupleStruct>::default(),
+        TypeInfo::Tuple(_) => Box::<DynamicTuple>::default(),
+        TypeInfo::List(_) => Box::<DynamicList>::default(),
+        TypeInfo::Array(_) => Box::new(DynamicArray::new(Box::new([]))),
+        TypeInfo::Map(_) => Box::<DynamicMap>::default(),
+        TypeInfo::Value(_) => comp_type
+            .data::<ReflectDefault>()
+            .ok_or_else(|| {
+                ScriptError::Other(format!("Component {} is a value or dynamic type with no `ReflectDefault` type_data, cannot instantiate sensible value", comp_type.short_name()))
+            })?
+            .default(),
+        TypeInfo::Enum(_) => Box::<DynamicEnum>::default(),
+    })
+}
+
+/// Inserts the default value of the given component type into the entity
+fn insert_default_component(
+    w: &mut World,
+    entity: Entity,
+    comp_type: &ScriptTypeRegistration,
+) -> Result<(), ScriptError> {
+    let component_data = comp_type.data::<ReflectComponent>().ok_or_else(|| {
+        ScriptError::Other(format!("Not a component {}", comp_type.short_name()))
+    })?;
+    let value = default_component_value(comp_type)?;
+
+    // Remove: AppTypeRegistry
+    let registry: AppTypeRegistry = w.remove_resource().unwrap();
+
+    // entities reserved by deferred spawns only exist once flushed, which `get_or_spawn` does for us
+    let result = match w.get_or_spawn(entity) {
+        Some(mut entity_ref) => {
+            component_data.insert(&mut entity_ref, value.as_ref(), &registry.read());
+            Ok(())
+        }
+        None => Err(ScriptError::Other(format!(
+            "Entity is not valid {:#?}",
+            entity
+        ))),
+    };
+
+    // Insert: AppTypeRegistry
+    w.insert_resource(registry);
+    result
+}
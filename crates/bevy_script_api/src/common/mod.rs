@@ -1,2 +1,3 @@
 pub mod bevy;
 pub mod std;
+pub mod value;
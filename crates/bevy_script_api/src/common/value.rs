@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use bevy::{
+    prelude::{Component, Entity, FromWorld, ReflectComponent, ReflectResource, Resource},
+    reflect::{FromReflect, FromType, Reflect},
+};
+use bevy_mod_scripting_core::world::WorldPointer;
+use parking_lot::RwLock;
+
+use crate::{
+    sub_reflect::{ReflectBase, ReflectionPath},
+    ReflectReference,
+};
+
+/// A dynamically typed value which can be handed to scripts of any language, most notably as an event argument.
+///
+/// World backed values (components and resources) only describe where the value lives,
+/// they are turned into [`ReflectReference`]s once converted into a script value using the world of the receiving script.
+/// Reflected values without a world counterpart are shared between all the scripts receiving them,
+/// and like other script owned values are only valid while the event is being handled.
+#[derive(Clone, Default)]
+pub enum ScriptValue {
+    #[default]
+    Nil,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Entity(Entity),
+    /// A component on the given entity
+    Component {
+        entity: Entity,
+        component: ReflectComponent,
+    },
+    /// A resource in the world
+    Resource(ReflectResource),
+    /// A reflected value owned by the event, converted using the script proxy type data registered for its type
+    Reflect(Arc<RwLock<dyn Reflect>>),
+    List(Vec<ScriptValue>),
+}
+
+impl std::fmt::Debug for ScriptValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nil => f.write_str("Nil"),
+            Self::Bool(v) => f.debug_tuple("Bool").field(v).finish(),
+            Self::Integer(v) => f.debug_tuple("Integer").field(v).finish(),
+            Self::Float(v) => f.debug_tuple("Float").field(v).finish(),
+            Self::String(v) => f.debug_tuple("String").field(v).finish(),
+            Self::Entity(v) => f.debug_tuple("Entity").field(v).finish(),
+            Self::Component { entity, .. } => f
+                .debug_struct("Component")
+                .field("entity", entity)
+                .finish_non_exhaustive(),
+            Self::Resource(_) => f.debug_tuple("Resource").finish_non_exhaustive(),
+            Self::Reflect(v) => f.debug_tuple("Reflect").field(&v.read()).finish(),
+            Self::List(v) => f.debug_tuple("List").field(v).finish(),
+        }
+    }
+}
+
+impl ScriptValue {
+    /// A reference to the component of type `C` on the given entity
+    pub fn component<C: Component + Reflect + FromReflect>(entity: Entity) -> Self {
+        Self::Component {
+            entity,
+            component: <ReflectComponent as FromType<C>>::from_type(),
+        }
+    }
+
+    /// A reference to the resource of type `R`
+    pub fn resource<R: Resource + Reflect + FromWorld>() -> Self {
+        Self::Resource(<ReflectResource as FromType<R>>::from_type())
+    }
+
+    /// Wraps an arbitrary reflected value
+    pub fn reflect<T: Reflect>(value: T) -> Self {
+        Self::Reflect(Arc::new(RwLock::new(value)))
+    }
+
+    /// Converts world backed and reflected values to a [`ReflectReference`] into the given world,
+    /// returns `None` for values which have a direct script representation.
+    pub fn reflect_ref(&self, world_ptr: WorldPointer) -> Option<ReflectReference> {
+        match self {
+            Self::Component { entity, component } => Some(ReflectReference::new_component_ref(
+                component.clone(),
+                *entity,
+                world_ptr,
+            )),
+            Self::Resource(resource) => Some(ReflectReference::new_resource_ref(
+                resource.clone(),
+                world_ptr,
+            )),
+            Self::Reflect(value) => Some(ReflectReference {
+                path: ReflectionPath::new(ReflectBase::ScriptOwned {
+                    val: Arc::downgrade(value),
+                }),
+                world_ptr,
+            }),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_from_scalar {
+    ($variant:ident($target:ty): $($source:ty),*) => {
+        $(
+            impl From<$source> for ScriptValue {
+                fn from(value: $source) -> Self {
+                    Self::$variant(value as $target)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_scalar!(Integer(i64): i8, i16, i32, i64, isize, u8, u16, u32);
+impl_from_scalar!(Float(f64): f32, f64);
+
+impl From<bool> for ScriptValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<String> for ScriptValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for ScriptValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<Entity> for ScriptValue {
+    fn from(value: Entity) -> Self {
+        Self::Entity(value)
+    }
+}
+
+impl<T: Into<ScriptValue>> From<Option<T>> for ScriptValue {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or_default()
+    }
+}
+
+impl<T: Into<ScriptValue>> From<Vec<T>> for ScriptValue {
+    fn from(value: Vec<T>) -> Self {
+        Self::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+/// A list of dynamically typed arguments, usable as the argument type of script events in any language.
+///
+/// Each value is passed to the script hook as a separate argument, so the same event type
+/// can carry `on_damage(entity, amount)` as well as `on_chat(text)`.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptArgs(pub Vec<ScriptValue>);
+
+impl ScriptArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an argument
+    pub fn with_arg(mut self, value: impl Into<ScriptValue>) -> Self {
+        self.0.push(value.into());
+        self
+    }
+}
+
+impl From<Vec<ScriptValue>> for ScriptArgs {
+    fn from(value: Vec<ScriptValue>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Into<ScriptValue>> FromIterator<T> for ScriptArgs {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().map(Into::into).collect())
+    }
+}
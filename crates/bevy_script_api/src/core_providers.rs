@@ -61,6 +61,18 @@ impl bevy_mod_scripting_core::hosts::APIProvider for LuaCoreBevyAPIProvider {
         ))
     }
 
+    fn get_api_schema(&self) -> Option<bevy_mod_scripting_core::schema::ApiSchema> {
+        let mut schema = crate::common::bevy::bevy_api_schema();
+        schema
+            .globals
+            .push(bevy_mod_scripting_core::schema::GlobalSchema {
+                name: "script".to_owned(),
+                ty: std::any::type_name::<crate::lua::bevy::LuaScriptData>().to_owned(),
+                docs: Some("The data of the script, i.e. its name and id.".to_owned()),
+            });
+        Some(schema)
+    }
+
     fn setup_script(
         &mut self,
         script_data: &bevy_mod_scripting_core::hosts::ScriptData,
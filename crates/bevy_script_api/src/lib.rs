@@ -5,12 +5,14 @@ pub mod error;
 pub mod lua;
 #[cfg(feature = "rhai")]
 pub mod rhai;
+#[cfg(feature = "rune")]
+pub mod rune;
 
 pub mod common;
 
 #[cfg(feature = "lua")]
 pub mod core_providers;
-// for now providers do not support any other lang so just remove this whole module if they are not needed
+// the generated lua providers, the generated rhai providers live in `rhai::providers`
 #[cfg(feature = "lua")]
 pub mod providers;
 
@@ -36,7 +38,19 @@ pub mod prelude {
         FromRhaiProxy, ReflectRhaiProxyable, RhaiProxyable, ToRhaiProxy,
     };
 
-    pub use crate::{common::bevy::GetWorld, ValueIndex};
+    #[cfg(feature = "rune")]
+    pub use crate::rune::{
+        bevy::RuneBevyAPIProvider, FromRuneProxy, ReflectRuneProxyable, RegisterForeignRuneType,
+        RuneEntity, RuneProxyable,
+    };
+
+    pub use crate::{
+        common::{
+            bevy::GetWorld,
+            value::{ScriptArgs, ScriptValue},
+        },
+        ValueIndex,
+    };
 }
 
 #[cfg(feature = "lua")]
@@ -9,7 +9,6 @@ use crate::lua::{
 };
 use crate::providers::bevy_ecs::LuaEntity;
 use crate::{impl_from_lua_with_clone, impl_tealr_type};
-use bevy::hierarchy::BuildWorldChildren;
 use bevy::prelude::{AppTypeRegistry, ReflectResource};
 use bevy_mod_scripting_core::prelude::*;
 use bevy_mod_scripting_lua::{prelude::IntoLua, tealr};
@@ -240,16 +239,12 @@ impl TealData for LuaWorld {
         methods.document(
             "Removes the given resource from the world, if one doesn't exist it does nothing.",
         );
-        methods.add_method(
+        methods.add_method_mut(
             "remove_resource",
             |_, world, res_type: LuaTypeRegistration| {
-                let mut w = world.write();
-
-                let resource_data = res_type.data::<ReflectResource>().ok_or_else(|| {
-                    mlua::Error::RuntimeError(format!("Not a resource {}", res_type.short_name()))
-                })?;
-                resource_data.remove(&mut w);
-                Ok(())
+                world
+                    .remove_resource(res_type)
+                    .map_err(|e| mlua::Error::RuntimeError(e.to_string()))
             },
         );
 
@@ -282,16 +277,12 @@ impl TealData for LuaWorld {
         methods.add_method(
             "push_children",
             |_, world, (parent, children): (LuaEntity, Vec<LuaEntity>)| {
-                let mut w = world.write();
                 let children = children
                     .iter()
                     .map(|e| e.inner())
                     .collect::<Result<Vec<_>, _>>()?;
 
-                if let Some(mut entity) = w.get_entity_mut(parent.inner()?) {
-                    entity.push_children(&children);
-                }
-
+                world.push_children(parent.inner()?, &children);
                 Ok(())
             },
         );
@@ -368,19 +359,14 @@ impl TealData for LuaWorld {
         });
 
         methods.document("Spawns a new entity and returns its Entity ID");
-        methods.add_method("spawn", |_, world, ()| {
-            let mut w = world.write();
-
-            Ok(LuaEntity::new(w.spawn(()).id()))
-        });
+        methods.document("If structural changes are deferred, the entity is reserved and spawned once the script finishes handling its events.");
+        methods.add_method("spawn", |_, world, ()| Ok(LuaEntity::new(world.spawn())));
 
         methods.document(
             "Despawns the given entity if it exists, returns true if deletion was successfull",
         );
         methods.add_method("despawn", |_, world, entity: LuaEntity| {
-            let mut w = world.write();
-
-            Ok(w.despawn(entity.inner()?))
+            Ok(world.despawn(entity.inner()?))
         });
     }
 }
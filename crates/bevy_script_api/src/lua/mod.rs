@@ -24,6 +24,7 @@ use self::util::to_host_idx;
 pub mod bevy;
 pub mod std;
 pub mod util;
+pub mod value;
 
 /// A trait allowing to register the [`LuaProxyable`] trait with the type registry for foreign types
 ///
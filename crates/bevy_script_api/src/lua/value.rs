@@ -0,0 +1,129 @@
+use bevy_mod_scripting_lua::tealr::mlu::mlua::{
+    self, IntoLua, IntoLuaMulti, Lua, MultiValue, Value,
+};
+
+use crate::{
+    common::{
+        bevy::GetWorld,
+        value::{ScriptArgs, ScriptValue},
+    },
+    providers::bevy_ecs::LuaEntity,
+};
+
+impl<'lua> IntoLua<'lua> for ScriptValue {
+    /// Converts scalars and entities directly, world backed and reflected values are converted
+    /// via [`ReflectReference`](crate::ReflectReference) using the `world` of the receiving script,
+    /// which means they are subject to the same [`ReflectLuaProxyable`](super::ReflectLuaProxyable) conversions as any other reference.
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<Value<'lua>> {
+        match self {
+            ScriptValue::Nil => Ok(Value::Nil),
+            ScriptValue::Bool(v) => Ok(Value::Boolean(v)),
+            ScriptValue::Integer(v) => Ok(Value::Integer(v as mlua::Integer)),
+            ScriptValue::Float(v) => Ok(Value::Number(v as mlua::Number)),
+            ScriptValue::String(v) => v.into_lua(lua),
+            ScriptValue::Entity(v) => LuaEntity::new(v).into_lua(lua),
+            ScriptValue::List(v) => lua
+                .create_sequence_from(
+                    v.into_iter()
+                        .map(|v| v.into_lua(lua))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+                .map(Value::Table),
+            v => v
+                .reflect_ref(lua.get_world()?)
+                .expect("Not a reflected value")
+                .into_lua(lua),
+        }
+    }
+}
+
+impl<'lua> IntoLuaMulti<'lua> for ScriptArgs {
+    fn into_lua_multi(self, lua: &'lua Lua) -> mlua::Result<MultiValue<'lua>> {
+        self.0
+            .into_iter()
+            .map(|v| v.into_lua(lua))
+            .collect::<mlua::Result<MultiValue>>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::*;
+    use bevy_mod_scripting_core::world::WorldPointerGuard;
+    use bevy_mod_scripting_lua::tealr::mlu::mlua::Function;
+
+    use super::*;
+    use crate::lua::{bevy::LuaWorld, RegisterForeignLuaType};
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Health {
+        value: f32,
+    }
+
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct Score {
+        value: f32,
+    }
+
+    #[test]
+    fn script_args_are_passed_as_separate_arguments() {
+        let lua = Lua::new();
+        let f: Function = lua
+            .load(
+                "return function(entity, amount, text, list, nothing)
+                    return tostring(entity ~= nil) .. amount .. text .. #list .. tostring(nothing)
+                end",
+            )
+            .eval()
+            .unwrap();
+
+        let args = ScriptArgs::new()
+            .with_arg(Entity::from_raw(42))
+            .with_arg(7)
+            .with_arg("hit")
+            .with_arg(vec![1.0, 2.0])
+            .with_arg(ScriptValue::Nil);
+
+        assert_eq!(f.call::<_, String>(args).unwrap(), "true7hit2nil");
+    }
+
+    #[test]
+    fn world_backed_and_reflected_args_are_converted_to_references() {
+        let mut app = App::new();
+        app.register_type::<Health>()
+            .register_type::<Score>()
+            .register_foreign_lua_type::<f32>()
+            .insert_resource(Score { value: 1.0 });
+        let entity = app.world.spawn(Health { value: 10.0 }).id();
+
+        let lua = Lua::new();
+        let f: Function = lua
+            .load(
+                "return function(health, score, amount)
+                    health.value = health.value - amount
+                    score.value = score.value + amount
+                    return amount
+                end",
+            )
+            .eval()
+            .unwrap();
+
+        let args = ScriptArgs::new()
+            .with_arg(ScriptValue::component::<Health>(entity))
+            .with_arg(ScriptValue::resource::<Score>())
+            .with_arg(ScriptValue::reflect(2.5f32));
+
+        {
+            let world = unsafe { WorldPointerGuard::new(&mut app.world) };
+            lua.globals()
+                .set("world", LuaWorld::new(world.clone()))
+                .unwrap();
+            assert_eq!(f.call::<_, f32>(args).unwrap(), 2.5);
+        }
+
+        assert_eq!(app.world.get::<Health>(entity).unwrap().value, 7.5);
+        assert_eq!(app.world.resource::<Score>().value, 3.5);
+    }
+}
@@ -3,6 +3,7 @@
 #![allow(unused, deprecated, dead_code)]
 #![cfg_attr(rustfmt, rustfmt_skip)]
 use super::bevy_ecs::*;
+#[cfg(feature = "api_math")]
 use super::bevy_reflect::*;
 extern crate self as bevy_script_api;
 use bevy_script_api::{
@@ -2,6 +2,7 @@
 #![allow(clippy::all)]
 #![allow(unused, deprecated, dead_code)]
 #![cfg_attr(rustfmt, rustfmt_skip)]
+#[cfg(feature = "api_math")]
 use super::bevy_reflect::*;
 extern crate self as bevy_script_api;
 use bevy_script_api::{
@@ -3,8 +3,11 @@
 #![allow(unused, deprecated, dead_code)]
 #![cfg_attr(rustfmt, rustfmt_skip)]
 use super::bevy_ecs::*;
+#[cfg(feature = "api_math")]
 use super::bevy_reflect::*;
+#[cfg(feature = "api_core")]
 use super::bevy_core::*;
+#[cfg(feature = "api_hierarchy")]
 use super::bevy_hierarchy::*;
 extern crate self as bevy_script_api;
 use bevy_script_api::{
@@ -3,7 +3,9 @@
 #![allow(unused, deprecated, dead_code)]
 #![cfg_attr(rustfmt, rustfmt_skip)]
 use super::bevy_ecs::*;
+#[cfg(feature = "api_math")]
 use super::bevy_reflect::*;
+#[cfg(feature = "api_input")]
 use super::bevy_input::*;
 extern crate self as bevy_script_api;
 use bevy_script_api::{
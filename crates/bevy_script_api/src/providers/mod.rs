@@ -3,16 +3,75 @@
 #![allow(unused, deprecated, dead_code)]
 #![cfg_attr(rustfmt, rustfmt_skip)]
 pub mod bevy_ecs;
+#[cfg(feature = "api_transform")]
 pub mod bevy_transform;
+#[cfg(feature = "api_input")]
 pub mod bevy_input;
+#[cfg(feature = "api_core")]
 pub mod bevy_core;
+#[cfg(feature = "api_time")]
 pub mod bevy_time;
+#[cfg(feature = "api_hierarchy")]
 pub mod bevy_hierarchy;
+#[cfg(feature = "api_window")]
 pub mod bevy_window;
+#[cfg(feature = "api_math")]
 pub mod bevy_reflect;
 extern crate self as bevy_script_api;
 use bevy_mod_scripting_core::docs::DocFragment;
-pub struct LuaBevyAPIProvider;
+/// Attaches the generated bindings of every crate compiled in, use [`LuaBevyAPIProvider::empty`] and [`LuaBevyAPIProvider::with_crate`]
+/// or [`LuaBevyAPIProvider::without_crate`] to attach a subset of them to each script context.
+#[derive(Debug, Clone)]
+pub struct LuaBevyAPIProvider {
+    crates: Vec<String>,
+}
+impl LuaBevyAPIProvider {
+    /// The names of the crates whose bindings are compiled in
+    pub fn compiled_crates() -> Vec<&'static str> {
+        let mut crates = Vec::default();
+        crates.push("bevy_ecs");
+        #[cfg(feature = "api_transform")] crates.push("bevy_transform");
+        #[cfg(feature = "api_input")] crates.push("bevy_input");
+        #[cfg(feature = "api_core")] crates.push("bevy_core");
+        #[cfg(feature = "api_time")] crates.push("bevy_time");
+        #[cfg(feature = "api_hierarchy")] crates.push("bevy_hierarchy");
+        #[cfg(feature = "api_window")] crates.push("bevy_window");
+        #[cfg(feature = "api_math")] crates.push("bevy_reflect");
+        crates
+    }
+    /// A provider which attaches no bindings
+    pub fn empty() -> Self {
+        Self { crates: Vec::default() }
+    }
+    /// Attaches the bindings of the given crate, crates which are not compiled in are ignored with a warning
+    pub fn with_crate(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !Self::compiled_crates().contains(&name.as_str()) {
+            bevy::log::warn!(
+                "The bindings of `{name}` are not compiled in, enable the feature of `bevy_script_api` which includes them"
+            );
+        } else if !self.crates.contains(&name) {
+            self.crates.push(name);
+        }
+        self
+    }
+    /// Stops the bindings of the given crate from being attached
+    pub fn without_crate(mut self, name: &str) -> Self {
+        self.crates.retain(|c| c != name);
+        self
+    }
+    /// Returns true if the bindings of the given crate are attached
+    pub fn includes(&self, name: &str) -> bool {
+        self.crates.iter().any(|c| c == name)
+    }
+}
+impl Default for LuaBevyAPIProvider {
+    fn default() -> Self {
+        Self {
+            crates: Self::compiled_crates().into_iter().map(ToOwned::to_owned).collect(),
+        }
+    }
+}
 impl bevy_mod_scripting_core::hosts::APIProvider for LuaBevyAPIProvider {
     type APITarget = std::sync::Mutex<bevy_mod_scripting_lua::tealr::mlu::mlua::Lua>;
     type ScriptContext = std::sync::Mutex<bevy_mod_scripting_lua::tealr::mlu::mlua::Lua>;
@@ -21,29 +80,75 @@ impl bevy_mod_scripting_core::hosts::APIProvider for LuaBevyAPIProvider {
         &mut self,
         ctx: &mut Self::APITarget,
     ) -> Result<(), bevy_mod_scripting_core::error::ScriptError> {
-        bevy_ecs::BevyEcsAPIProvider.attach_api(ctx)?;
-        bevy_transform::BevyTransformAPIProvider.attach_api(ctx)?;
-        bevy_input::BevyInputAPIProvider.attach_api(ctx)?;
-        bevy_core::BevyCoreAPIProvider.attach_api(ctx)?;
-        bevy_time::BevyTimeAPIProvider.attach_api(ctx)?;
-        bevy_hierarchy::BevyHierarchyAPIProvider.attach_api(ctx)?;
-        bevy_window::BevyWindowAPIProvider.attach_api(ctx)?;
-        bevy_reflect::BevyReflectAPIProvider.attach_api(ctx)?;
+        if self.includes("bevy_ecs") {
+            bevy_ecs::BevyEcsAPIProvider.attach_api(ctx)?;
+        }
+        #[cfg(feature = "api_transform")]
+        if self.includes("bevy_transform") {
+            bevy_transform::BevyTransformAPIProvider.attach_api(ctx)?;
+        }
+        #[cfg(feature = "api_input")]
+        if self.includes("bevy_input") {
+            bevy_input::BevyInputAPIProvider.attach_api(ctx)?;
+        }
+        #[cfg(feature = "api_core")]
+        if self.includes("bevy_core") {
+            bevy_core::BevyCoreAPIProvider.attach_api(ctx)?;
+        }
+        #[cfg(feature = "api_time")]
+        if self.includes("bevy_time") {
+            bevy_time::BevyTimeAPIProvider.attach_api(ctx)?;
+        }
+        #[cfg(feature = "api_hierarchy")]
+        if self.includes("bevy_hierarchy") {
+            bevy_hierarchy::BevyHierarchyAPIProvider.attach_api(ctx)?;
+        }
+        #[cfg(feature = "api_window")]
+        if self.includes("bevy_window") {
+            bevy_window::BevyWindowAPIProvider.attach_api(ctx)?;
+        }
+        #[cfg(feature = "api_math")]
+        if self.includes("bevy_reflect") {
+            bevy_reflect::BevyReflectAPIProvider.attach_api(ctx)?;
+        }
         Ok(())
     }
     fn get_doc_fragment(&self) -> Option<Self::DocTarget> {
-        [
-            bevy_ecs::BevyEcsAPIProvider.get_doc_fragment(),
-            bevy_transform::BevyTransformAPIProvider.get_doc_fragment(),
-            bevy_input::BevyInputAPIProvider.get_doc_fragment(),
-            bevy_core::BevyCoreAPIProvider.get_doc_fragment(),
-            bevy_time::BevyTimeAPIProvider.get_doc_fragment(),
-            bevy_hierarchy::BevyHierarchyAPIProvider.get_doc_fragment(),
-            bevy_window::BevyWindowAPIProvider.get_doc_fragment(),
-            bevy_reflect::BevyReflectAPIProvider.get_doc_fragment(),
-        ]
+        let mut fragments: Vec<Option<Self::DocTarget>> = Vec::default();
+        if self.includes("bevy_ecs") {
+            fragments.push(bevy_ecs::BevyEcsAPIProvider.get_doc_fragment());
+        }
+        #[cfg(feature = "api_transform")]
+        if self.includes("bevy_transform") {
+            fragments.push(bevy_transform::BevyTransformAPIProvider.get_doc_fragment());
+        }
+        #[cfg(feature = "api_input")]
+        if self.includes("bevy_input") {
+            fragments.push(bevy_input::BevyInputAPIProvider.get_doc_fragment());
+        }
+        #[cfg(feature = "api_core")]
+        if self.includes("bevy_core") {
+            fragments.push(bevy_core::BevyCoreAPIProvider.get_doc_fragment());
+        }
+        #[cfg(feature = "api_time")]
+        if self.includes("bevy_time") {
+            fragments.push(bevy_time::BevyTimeAPIProvider.get_doc_fragment());
+        }
+        #[cfg(feature = "api_hierarchy")]
+        if self.includes("bevy_hierarchy") {
+            fragments.push(bevy_hierarchy::BevyHierarchyAPIProvider.get_doc_fragment());
+        }
+        #[cfg(feature = "api_window")]
+        if self.includes("bevy_window") {
+            fragments.push(bevy_window::BevyWindowAPIProvider.get_doc_fragment());
+        }
+        #[cfg(feature = "api_math")]
+        if self.includes("bevy_reflect") {
+            fragments.push(bevy_reflect::BevyReflectAPIProvider.get_doc_fragment());
+        }
+        fragments
             .into_iter()
-            .filter_map(|a: Option<_>| a)
+            .flatten()
             .fold(
                 None,
                 |a, b| match a {
@@ -52,6 +157,57 @@ impl bevy_mod_scripting_core::hosts::APIProvider for LuaBevyAPIProvider {
                 },
             )
     }
+    fn get_api_schema(&self) -> Option<bevy_mod_scripting_core::schema::ApiSchema> {
+        let mut schema = bevy_mod_scripting_core::schema::ApiSchema::default();
+        if self.includes("bevy_ecs") {
+            if let Some(crate_schema) = bevy_ecs::BevyEcsAPIProvider.get_api_schema() {
+                schema.merge(crate_schema);
+            }
+        }
+        #[cfg(feature = "api_transform")]
+        if self.includes("bevy_transform") {
+            if let Some(crate_schema) = bevy_transform::BevyTransformAPIProvider.get_api_schema() {
+                schema.merge(crate_schema);
+            }
+        }
+        #[cfg(feature = "api_input")]
+        if self.includes("bevy_input") {
+            if let Some(crate_schema) = bevy_input::BevyInputAPIProvider.get_api_schema() {
+                schema.merge(crate_schema);
+            }
+        }
+        #[cfg(feature = "api_core")]
+        if self.includes("bevy_core") {
+            if let Some(crate_schema) = bevy_core::BevyCoreAPIProvider.get_api_schema() {
+                schema.merge(crate_schema);
+            }
+        }
+        #[cfg(feature = "api_time")]
+        if self.includes("bevy_time") {
+            if let Some(crate_schema) = bevy_time::BevyTimeAPIProvider.get_api_schema() {
+                schema.merge(crate_schema);
+            }
+        }
+        #[cfg(feature = "api_hierarchy")]
+        if self.includes("bevy_hierarchy") {
+            if let Some(crate_schema) = bevy_hierarchy::BevyHierarchyAPIProvider.get_api_schema() {
+                schema.merge(crate_schema);
+            }
+        }
+        #[cfg(feature = "api_window")]
+        if self.includes("bevy_window") {
+            if let Some(crate_schema) = bevy_window::BevyWindowAPIProvider.get_api_schema() {
+                schema.merge(crate_schema);
+            }
+        }
+        #[cfg(feature = "api_math")]
+        if self.includes("bevy_reflect") {
+            if let Some(crate_schema) = bevy_reflect::BevyReflectAPIProvider.get_api_schema() {
+                schema.merge(crate_schema);
+            }
+        }
+        Some(schema)
+    }
     fn setup_script(
         &mut self,
         script_data: &bevy_mod_scripting_core::hosts::ScriptData,
@@ -69,12 +225,19 @@ impl bevy_mod_scripting_core::hosts::APIProvider for LuaBevyAPIProvider {
     }
     fn register_with_app(&self, app: &mut bevy::app::App) {
         bevy_ecs::BevyEcsAPIProvider.register_with_app(app);
+        #[cfg(feature = "api_transform")]
         bevy_transform::BevyTransformAPIProvider.register_with_app(app);
+        #[cfg(feature = "api_input")]
         bevy_input::BevyInputAPIProvider.register_with_app(app);
+        #[cfg(feature = "api_core")]
         bevy_core::BevyCoreAPIProvider.register_with_app(app);
+        #[cfg(feature = "api_time")]
         bevy_time::BevyTimeAPIProvider.register_with_app(app);
+        #[cfg(feature = "api_hierarchy")]
         bevy_hierarchy::BevyHierarchyAPIProvider.register_with_app(app);
+        #[cfg(feature = "api_window")]
         bevy_window::BevyWindowAPIProvider.register_with_app(app);
+        #[cfg(feature = "api_math")]
         bevy_reflect::BevyReflectAPIProvider.register_with_app(app);
     }
 }
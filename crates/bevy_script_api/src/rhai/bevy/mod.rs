@@ -9,11 +9,11 @@ use bevy_mod_scripting_rhai::{
 use rhai::plugin::*;
 
 use crate::{
-    common::bevy::{ScriptQueryBuilder, ScriptTypeRegistration, ScriptWorld},
+    common::bevy::{bevy_api_schema, ScriptQueryBuilder, ScriptTypeRegistration, ScriptWorld},
     ReflectedValue,
 };
 
-use super::{RegisterForeignRhaiType, ToDynamic};
+use super::{providers::RhaiGeneratedBevyAPIProvider, RegisterForeignRhaiType, ToDynamic};
 
 #[allow(deprecated)]
 impl CustomType for ScriptTypeRegistration {
@@ -263,14 +263,9 @@ impl CustomType for ScriptWorld {
                 "despawn_recursive",
                 |self_: &mut ScriptWorld, entity: Entity| self_.despawn_recursive(entity),
             )
-            .with_fn("spawn", |self_: &mut ScriptWorld| {
-                let mut w = self_.write();
-                w.spawn(()).id()
-            })
+            .with_fn("spawn", |self_: &mut ScriptWorld| self_.spawn())
             .with_fn("despawn", |self_: &mut ScriptWorld, entity: Entity| {
-                let mut w = self_.write();
-
-                w.despawn(entity)
+                self_.despawn(entity)
             })
             .with_fn("to_string", |self_: &mut ScriptWorld| self_.to_string())
             .with_fn("to_debug", |self_: &mut ScriptWorld| format!("{:?}", self_))
@@ -298,6 +293,8 @@ impl CustomType for ScriptWorld {
     }
 }
 
+/// Provides the world and entity of each script alongside the generated bindings for bevy types,
+/// see [`RhaiGeneratedBevyAPIProvider`]
 pub struct RhaiBevyAPIProvider;
 
 impl APIProvider for RhaiBevyAPIProvider {
@@ -312,7 +309,7 @@ impl APIProvider for RhaiBevyAPIProvider {
         engine.build_type::<ScriptQueryBuilder>();
         engine.register_iterator::<Vec<rhai::Map>>();
         engine.register_iterator::<ScriptQueryBuilder>();
-        Ok(())
+        RhaiGeneratedBevyAPIProvider.attach_api(engine)
     }
 
     fn setup_script_runtime(
@@ -335,7 +332,13 @@ impl APIProvider for RhaiBevyAPIProvider {
     }
 
     fn get_doc_fragment(&self) -> Option<Self::DocTarget> {
-        None
+        Some(RhaiDocFragment::new("BevyAPI", |engine| {
+            RhaiBevyAPIProvider.attach_api(engine)
+        }))
+    }
+
+    fn get_api_schema(&self) -> Option<ApiSchema> {
+        Some(bevy_api_schema())
     }
 
     fn register_with_app(&self, app: &mut bevy::prelude::App) {
@@ -355,5 +358,6 @@ impl APIProvider for RhaiBevyAPIProvider {
         app.register_foreign_rhai_type::<u128>();
         app.register_foreign_rhai_type::<usize>();
         app.register_foreign_rhai_type::<String>();
+        RhaiGeneratedBevyAPIProvider.register_with_app(app);
     }
 }
@@ -10,7 +10,9 @@ use bevy_mod_scripting_rhai::rhai::{CustomType, Dynamic, EvalAltResult, INT};
 use crate::{ReflectReference, ReflectedValue, ValueIndex};
 
 pub mod bevy;
+pub mod providers;
 pub mod std;
+pub mod value;
 
 /// A trait allowing the registration of the [`RhaiProxyable`] trait with the type registry for foreign types
 ///
@@ -0,0 +1,47 @@
+// @generated by cargo bevy-api-gen collect, modify the templates not this file
+#![allow(clippy::all)]
+#![allow(unused, deprecated, dead_code)]
+#![cfg_attr(rustfmt, rustfmt_skip)]
+extern crate self as bevy_script_api;
+use bevy_mod_scripting_core::{docs::DocFragment, hosts::APIProvider};
+pub struct RhaiGeneratedBevyAPIProvider;
+impl bevy_mod_scripting_core::hosts::APIProvider for RhaiGeneratedBevyAPIProvider {
+    type APITarget = bevy_mod_scripting_rhai::rhai::Engine;
+    type ScriptContext = bevy_mod_scripting_rhai::RhaiContext;
+    type DocTarget = bevy_mod_scripting_rhai::docs::RhaiDocFragment;
+    fn attach_api(
+        &mut self,
+        engine: &mut Self::APITarget,
+    ) -> Result<(), bevy_mod_scripting_core::error::ScriptError> {
+        Ok(())
+    }
+    fn get_doc_fragment(&self) -> Option<Self::DocTarget> {
+        let mut fragments: Vec<Option<Self::DocTarget>> = Vec::default();
+        fragments
+            .into_iter()
+            .flatten()
+            .fold(
+                None,
+                |a, b| match a {
+                    Some(a) => Some(a.merge(b)),
+                    None => Some(b),
+                },
+            )
+    }
+    fn setup_script(
+        &mut self,
+        script_data: &bevy_mod_scripting_core::hosts::ScriptData,
+        ctx: &mut Self::ScriptContext,
+    ) -> Result<(), bevy_mod_scripting_core::error::ScriptError> {
+        Ok(())
+    }
+    fn setup_script_runtime(
+        &mut self,
+        world_ptr: bevy_mod_scripting_core::world::WorldPointer,
+        _script_data: &bevy_mod_scripting_core::hosts::ScriptData,
+        ctx: &mut Self::ScriptContext,
+    ) -> Result<(), bevy_mod_scripting_core::error::ScriptError> {
+        Ok(())
+    }
+    fn register_with_app(&self, app: &mut bevy::app::App) {}
+}
@@ -0,0 +1,89 @@
+use bevy_mod_scripting_core::world::WorldPointer;
+use bevy_mod_scripting_rhai::{
+    rhai::{Array, Dynamic, EvalAltResult, INT},
+    RhaiArgs,
+};
+
+use crate::common::value::{ScriptArgs, ScriptValue};
+
+use super::ToDynamic;
+
+impl ToDynamic for ScriptValue {
+    /// Converts scalars and entities directly, world backed and reflected values are converted
+    /// via [`ReflectReference`](crate::ReflectReference) using the world of the script currently being executed,
+    /// which means they are subject to the same [`ReflectRhaiProxyable`](super::ReflectRhaiProxyable) conversions as any other reference.
+    fn to_dynamic(self) -> Result<Dynamic, Box<EvalAltResult>> {
+        match self {
+            ScriptValue::Nil => Ok(Dynamic::UNIT),
+            ScriptValue::Bool(v) => Ok(Dynamic::from_bool(v)),
+            ScriptValue::Integer(v) => Ok(Dynamic::from_int(v as INT)),
+            ScriptValue::Float(v) => Ok(Dynamic::from_float(v as _)),
+            ScriptValue::String(v) => Ok(v.into()),
+            ScriptValue::Entity(v) => Ok(Dynamic::from(v)),
+            ScriptValue::List(v) => v
+                .into_iter()
+                .map(ToDynamic::to_dynamic)
+                .collect::<Result<Array, _>>()
+                .map(Dynamic::from_array),
+            v => {
+                let world = WorldPointer::current().ok_or_else(|| {
+                    Box::<EvalAltResult>::from("Reflected script values require a script world")
+                })?;
+                v.reflect_ref(world)
+                    .expect("Not a reflected value")
+                    .to_dynamic()
+            }
+        }
+    }
+}
+
+impl RhaiArgs for ScriptArgs {
+    fn into_args(self) -> Result<Vec<Dynamic>, Box<EvalAltResult>> {
+        self.0.into_iter().map(ToDynamic::to_dynamic).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use bevy::prelude::Entity;
+    use bevy_mod_scripting_rhai::rhai::{Engine, Scope};
+    use parking_lot::RwLock;
+
+    use super::*;
+
+    #[test]
+    fn script_args_are_passed_as_separate_arguments() {
+        let mut engine = Engine::new();
+        engine.register_type::<Entity>();
+        let ast = engine
+            .compile(
+                r#"fn hook(entity, amount, text, list, nothing) {
+                    `${type_of(entity) != "()"}${amount}${text}${list.len()}${nothing}`
+                }"#,
+            )
+            .unwrap();
+
+        let args = ScriptArgs::new()
+            .with_arg(Entity::from_raw(42))
+            .with_arg(7)
+            .with_arg("hit")
+            .with_arg(vec![1.0, 2.0])
+            .with_arg(ScriptValue::Nil);
+
+        let out: String = engine
+            .call_fn(&mut Scope::new(), &ast, "hook", args.into_args().unwrap())
+            .unwrap();
+        assert_eq!(out, "true7hit2");
+    }
+
+    #[test]
+    fn reflected_args_fail_to_convert_without_a_world() {
+        let args = ScriptArgs::new()
+            .with_arg(1)
+            .with_arg(ScriptValue::Reflect(Arc::new(RwLock::new(1.0f32))));
+
+        assert!(args.into_args().is_err());
+    }
+}
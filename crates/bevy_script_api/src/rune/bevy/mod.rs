@@ -0,0 +1,544 @@
+use bevy::prelude::{App, Entity};
+use bevy_mod_scripting_core::{prelude::*, schema::FunctionSchema, world::WorldPointer};
+use bevy_mod_scripting_rune::prelude::{
+    rune::{
+        self,
+        alloc::fmt::TryWrite,
+        runtime::{Formatter, Protocol, Ref, ToValue, Value, VmResult},
+        ContextError, FromValue, Module,
+    },
+    current_entity, Context, RuneDocFragment, RuneDocs, RuneScriptContext,
+};
+
+use crate::{
+    common::bevy::{bevy_api_schema, ScriptQueryBuilder, ScriptTypeRegistration, ScriptWorld},
+    ReflectedValue,
+};
+
+use super::{vm_result, RegisterForeignRuneType, RuneEntity};
+
+/// The world and entity of the script currently being executed, Rune has no notion of globals,
+/// so these are exposed to scripts via the `world()` and `entity()` functions instead.
+fn script_globals() -> VmResult<(ScriptWorld, Entity)> {
+    match (WorldPointer::current(), current_entity()) {
+        (Some(world), Some(entity)) => VmResult::Ok((ScriptWorld::new(world), entity)),
+        _ => VmResult::panic("`world()` and `entity()` are only available inside script hooks"),
+    }
+}
+
+/// Clones a list of script values holding Rune `Any` types without taking them away from the script
+fn from_values<T: rune::Any + Clone>(values: Vec<Value>) -> VmResult<Vec<T>> {
+    let mut out = Vec::with_capacity(values.len());
+    for value in values {
+        out.push(rune::vm_try!(Ref::<T>::from_value(value)).clone());
+    }
+    VmResult::Ok(out)
+}
+
+fn entities(values: Vec<Value>) -> VmResult<Vec<Entity>> {
+    VmResult::Ok(
+        rune::vm_try!(from_values::<RuneEntity>(values))
+            .into_iter()
+            .map(|e| e.inner())
+            .collect(),
+    )
+}
+
+/// Functions associated with [`ScriptTypeRegistration`], kept apart since they share names with its methods
+mod type_registration {
+    use super::*;
+
+    /// The short name of the type, i.e. `Transform`.
+    #[rune::function(instance)]
+    pub(super) fn short_name(registration: &ScriptTypeRegistration) -> String {
+        registration.short_name().to_owned()
+    }
+
+    /// The fully qualified name of the type, i.e. `bevy_transform::components::transform::Transform`.
+    #[rune::function(instance)]
+    pub(super) fn type_name(registration: &ScriptTypeRegistration) -> &'static str {
+        registration.type_name()
+    }
+}
+
+impl ScriptTypeRegistration {
+    fn install(module: &mut Module, docs: &mut RuneDocs) -> Result<(), ContextError> {
+        docs.ty::<Self>(module, &[
+            "Type information of a rust type registered with the type registry.",
+            "Used to retrieve, insert and query components and resources of the given type.",
+        ])?;
+        docs.associated_function::<Self>(module, type_registration::short_name)?;
+        docs.associated_function::<Self>(module, type_registration::type_name)?;
+        module.associated_function(Protocol::STRING_DISPLAY, |s: &Self, f: &mut Formatter| {
+            rune::vm_write!(f, "{s}");
+            VmResult::Ok(())
+        })?;
+        module.associated_function(Protocol::STRING_DEBUG, |s: &Self, f: &mut Formatter| {
+            rune::vm_write!(f, "{s:?}");
+            VmResult::Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// Functions associated with [`ScriptQueryBuilder`], kept apart since they share names with its methods
+mod query_builder {
+    use super::*;
+
+    /// Only matches entities which also have components of the given types.
+    #[rune::function(instance)]
+    pub(super) fn with(
+        query: &ScriptQueryBuilder,
+        types: Vec<Value>,
+    ) -> VmResult<ScriptQueryBuilder> {
+        let types = rune::vm_try!(from_values(types));
+        VmResult::Ok(query.clone().with(types).clone())
+    }
+
+    /// Only matches entities which have none of the components of the given types.
+    #[rune::function(instance)]
+    pub(super) fn without(
+        query: &ScriptQueryBuilder,
+        types: Vec<Value>,
+    ) -> VmResult<ScriptQueryBuilder> {
+        let types = rune::vm_try!(from_values(types));
+        VmResult::Ok(query.clone().without(types).clone())
+    }
+
+    /// Runs the query, returning a tuple of each matching entity followed by its queried components.
+    #[rune::function(instance)]
+    pub(super) fn iter(query: &ScriptQueryBuilder) -> VmResult<Vec<Value>> {
+        let results = rune::vm_try!(vm_result(query.clone().build()));
+        let mut out = Vec::with_capacity(results.len());
+        for result in results {
+            let mut tuple = rune::vm_try!(rune::alloc::Vec::try_with_capacity(result.1.len() + 1));
+            rune::vm_try!(tuple.try_push(rune::vm_try!(RuneEntity(result.0).to_value())));
+            for component in result.1 {
+                rune::vm_try!(tuple.try_push(rune::vm_try!(component.to_value())));
+            }
+            out.push(rune::vm_try!(Value::tuple(tuple)));
+        }
+        VmResult::Ok(out)
+    }
+}
+
+impl ScriptQueryBuilder {
+    fn install(module: &mut Module, docs: &mut RuneDocs) -> Result<(), ContextError> {
+        docs.ty::<Self>(
+            module,
+            &["A query over entities with the given components, created with `World::query`."],
+        )?;
+        docs.associated_function::<Self>(module, query_builder::with)?;
+        docs.associated_function::<Self>(module, query_builder::without)?;
+        docs.associated_function::<Self>(module, query_builder::iter)?;
+        Ok(())
+    }
+}
+
+/// Functions associated with [`ScriptWorld`], kept apart since they share names with its methods
+mod world {
+    use super::*;
+
+    /// Retrieves type information given either a short (`MyType`) or fully qualified rust type name (`MyModule::MyType`).
+    /// Returns `None` if no such type exists or if one wasn't registered on the rust side.
+    #[rune::function(instance)]
+    pub(super) fn get_type_by_name(
+        world: &ScriptWorld,
+        type_name: &str,
+    ) -> Option<ScriptTypeRegistration> {
+        world.get_type_by_name(type_name)
+    }
+
+    /// Inserts a component of the given type to the given entity by instantiating a default version of it.
+    /// The component can then be modified using field access.
+    #[rune::function(instance)]
+    pub(super) fn add_default_component(
+        world: &ScriptWorld,
+        entity: &RuneEntity,
+        comp_type: &ScriptTypeRegistration,
+    ) -> VmResult<Value> {
+        let component = rune::vm_try!(vm_result(
+            world.add_default_component(entity.inner(), comp_type.clone())
+        ));
+        component.to_value()
+    }
+
+    /// Retrieves a component of the given type from the given entity.
+    /// If such a component does not exist returns `None`.
+    #[rune::function(instance)]
+    pub(super) fn get_component(
+        world: &ScriptWorld,
+        entity: &RuneEntity,
+        comp_type: &ScriptTypeRegistration,
+    ) -> VmResult<Value> {
+        rune::vm_try!(vm_result(
+            world.get_component(entity.inner(), comp_type.clone())
+        ))
+        .to_value()
+    }
+
+    /// Returns `true` if the given entity contains a component of the given type.
+    #[rune::function(instance)]
+    pub(super) fn has_component(
+        world: &ScriptWorld,
+        entity: &RuneEntity,
+        comp_type: &ScriptTypeRegistration,
+    ) -> VmResult<bool> {
+        vm_result(world.has_component(entity.inner(), comp_type.clone()))
+    }
+
+    /// Removes the given component from the given entity, does nothing if it doesn't exist on the entity.
+    #[rune::function(instance)]
+    pub(super) fn remove_component(
+        world: &ScriptWorld,
+        entity: &RuneEntity,
+        comp_type: &ScriptTypeRegistration,
+    ) -> VmResult<()> {
+        vm_result(world.clone().remove_component(entity.inner(), comp_type.clone()))
+    }
+
+    /// Creates a QueryBuilder, querying for the passed components types.
+    /// Can be iterated over using `QueryBuilder::iter`
+    #[rune::function(instance)]
+    pub(super) fn query(world: &ScriptWorld, types: Vec<Value>) -> VmResult<ScriptQueryBuilder> {
+        let types = rune::vm_try!(from_values(types));
+        VmResult::Ok(
+            ScriptQueryBuilder::new(world.clone())
+                .components(types)
+                .clone(),
+        )
+    }
+
+    /// Retrieves a resource of the given type from the world.
+    /// If such a resource does not exist returns `None`.
+    #[rune::function(instance)]
+    pub(super) fn get_resource(
+        world: &ScriptWorld,
+        res_type: &ScriptTypeRegistration,
+    ) -> VmResult<Value> {
+        rune::vm_try!(vm_result(world.get_resource(res_type.clone()))).to_value()
+    }
+
+    /// Returns `true` if the world contains a resource of the given type.
+    #[rune::function(instance)]
+    pub(super) fn has_resource(
+        world: &ScriptWorld,
+        res_type: &ScriptTypeRegistration,
+    ) -> VmResult<bool> {
+        vm_result(world.has_resource(res_type.clone()))
+    }
+
+    /// Removes the given resource from the world, if one doesn't exist it does nothing.
+    #[rune::function(instance)]
+    pub(super) fn remove_resource(
+        world: &ScriptWorld,
+        res_type: &ScriptTypeRegistration,
+    ) -> VmResult<()> {
+        vm_result(world.clone().remove_resource(res_type.clone()))
+    }
+
+    /// Retrieves children entities of the parent entity if it has any.
+    #[rune::function(instance)]
+    pub(super) fn get_children(world: &ScriptWorld, parent: &RuneEntity) -> Vec<RuneEntity> {
+        world
+            .get_children(parent.inner())
+            .into_iter()
+            .map(RuneEntity::new)
+            .collect()
+    }
+
+    /// Retrieves the parent entity of the given entity if it has any.
+    #[rune::function(instance)]
+    pub(super) fn get_parent(world: &ScriptWorld, entity: &RuneEntity) -> Option<RuneEntity> {
+        world.get_parent(entity.inner()).map(RuneEntity::new)
+    }
+
+    /// Attaches children entities to the given parent entity.
+    #[rune::function(instance)]
+    pub(super) fn push_children(
+        world: &ScriptWorld,
+        parent: &RuneEntity,
+        children: Vec<Value>,
+    ) -> VmResult<()> {
+        let children = rune::vm_try!(entities(children));
+        world.push_children(parent.inner(), &children);
+        VmResult::Ok(())
+    }
+
+    /// Attaches child entity to the given parent entity.
+    #[rune::function(instance)]
+    pub(super) fn push_child(world: &ScriptWorld, parent: &RuneEntity, child: &RuneEntity) {
+        world.push_child(parent.inner(), child.inner())
+    }
+
+    /// Removes children entities from the given parent entity.
+    #[rune::function(instance)]
+    pub(super) fn remove_children(
+        world: &ScriptWorld,
+        parent: &RuneEntity,
+        children: Vec<Value>,
+    ) -> VmResult<()> {
+        let children = rune::vm_try!(entities(children));
+        world.remove_children(parent.inner(), &children);
+        VmResult::Ok(())
+    }
+
+    /// Removes child entity from the given parent entity.
+    #[rune::function(instance)]
+    pub(super) fn remove_child(world: &ScriptWorld, parent: &RuneEntity, child: &RuneEntity) {
+        world.remove_children(parent.inner(), &[child.inner()])
+    }
+
+    /// Inserts children entities to the given parent entity at the given index.
+    #[rune::function(instance)]
+    pub(super) fn insert_children(
+        world: &ScriptWorld,
+        parent: &RuneEntity,
+        index: usize,
+        children: Vec<Value>,
+    ) -> VmResult<()> {
+        let children = rune::vm_try!(entities(children));
+        world.insert_children(parent.inner(), index, &children);
+        VmResult::Ok(())
+    }
+
+    /// Inserts child entity to the given parent entity at the given index.
+    #[rune::function(instance)]
+    pub(super) fn insert_child(
+        world: &ScriptWorld,
+        parent: &RuneEntity,
+        index: usize,
+        child: &RuneEntity,
+    ) {
+        world.insert_children(parent.inner(), index, &[child.inner()])
+    }
+
+    /// Despawns the given entity's children recursively
+    #[rune::function(instance)]
+    pub(super) fn despawn_children_recursive(world: &ScriptWorld, entity: &RuneEntity) {
+        world.despawn_children_recursive(entity.inner())
+    }
+
+    /// Despawns the given entity and the entity's children recursively
+    #[rune::function(instance)]
+    pub(super) fn despawn_recursive(world: &ScriptWorld, entity: &RuneEntity) {
+        world.despawn_recursive(entity.inner())
+    }
+
+    /// Spawns a new entity and returns its Entity ID
+    /// If structural changes are deferred, the entity is reserved and spawned once the script finishes handling its events.
+    #[rune::function(instance)]
+    pub(super) fn spawn(world: &ScriptWorld) -> RuneEntity {
+        RuneEntity::new(world.spawn())
+    }
+
+    /// Despawns the given entity if it exists, returns true if deletion was successfull
+    #[rune::function(instance)]
+    pub(super) fn despawn(world: &ScriptWorld, entity: &RuneEntity) -> bool {
+        world.despawn(entity.inner())
+    }
+}
+
+/// The free functions giving scripts access to their world and entity
+mod globals {
+    use super::*;
+
+    /// Returns the world the current script lives in.
+    #[rune::function]
+    pub(super) fn world() -> VmResult<ScriptWorld> {
+        VmResult::Ok(rune::vm_try!(script_globals()).0)
+    }
+
+    /// Returns the entity the current script is attached to.
+    #[rune::function]
+    pub(super) fn entity() -> VmResult<RuneEntity> {
+        VmResult::Ok(RuneEntity::new(rune::vm_try!(script_globals()).1))
+    }
+}
+
+impl ScriptWorld {
+    fn install(module: &mut Module, docs: &mut RuneDocs) -> Result<(), ContextError> {
+        docs.ty::<Self>(
+            module,
+            &["The world the current script lives in, retrieved with `world()`."],
+        )?;
+
+        module.associated_function(Protocol::STRING_DISPLAY, |s: &Self, f: &mut Formatter| {
+            rune::vm_write!(f, "{s}");
+            VmResult::Ok(())
+        })?;
+        module.associated_function(Protocol::STRING_DEBUG, |s: &Self, f: &mut Formatter| {
+            rune::vm_write!(f, "{s:?}");
+            VmResult::Ok(())
+        })?;
+
+        for function in [
+            world::get_type_by_name,
+            world::add_default_component,
+            world::get_component,
+            world::has_component,
+            world::remove_component,
+            world::query,
+            world::get_resource,
+            world::has_resource,
+            world::remove_resource,
+            world::get_children,
+            world::get_parent,
+            world::push_children,
+            world::push_child,
+            world::remove_children,
+            world::remove_child,
+            world::insert_children,
+            world::insert_child,
+            world::despawn_children_recursive,
+            world::despawn_recursive,
+            world::spawn,
+            world::despawn,
+        ] {
+            docs.associated_function::<Self>(module, function)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Provides the Bevy world API to Rune scripts.
+///
+/// Scripts can access the world and the entity they are attached to through the `world()` and `entity()` functions.
+pub struct RuneBevyAPIProvider;
+
+impl RuneBevyAPIProvider {
+    /// The Rune module containing the Bevy API, documentation of everything installed is recorded in `docs`
+    pub fn module(docs: &mut RuneDocs) -> Result<Module, ContextError> {
+        let mut module = Module::new();
+
+        ReflectedValue::install(&mut module, docs)?;
+        RuneEntity::install(&mut module, docs)?;
+        ScriptTypeRegistration::install(&mut module, docs)?;
+        ScriptQueryBuilder::install(&mut module, docs)?;
+        ScriptWorld::install(&mut module, docs)?;
+        docs.function(&mut module, globals::world)?;
+        docs.function(&mut module, globals::entity)?;
+
+        Ok(module)
+    }
+}
+
+impl APIProvider for RuneBevyAPIProvider {
+    type APITarget = Context;
+    type ScriptContext = RuneScriptContext;
+    type DocTarget = RuneDocFragment;
+
+    fn attach_api(&mut self, ctx: &mut Self::APITarget) -> Result<(), ScriptError> {
+        let module = Self::module(&mut RuneDocs::new()).map_err(ScriptError::new_other)?;
+        ctx.install(module).map_err(ScriptError::new_other)
+    }
+
+    fn get_doc_fragment(&self) -> Option<Self::DocTarget> {
+        let mut docs = RuneDocs::new();
+        Self::module(&mut docs).expect("Something went wrong documenting the Bevy API");
+        Some(RuneDocFragment::new("BevyAPI", docs))
+    }
+
+    fn get_api_schema(&self) -> Option<ApiSchema> {
+        // rune has no global variables, the globals are functions of the same name
+        let mut schema = bevy_api_schema();
+        schema.functions = std::mem::take(&mut schema.globals)
+            .into_iter()
+            .map(|global| FunctionSchema {
+                name: global.name,
+                returns: Some(global.ty),
+                docs: global.docs,
+                ..Default::default()
+            })
+            .collect();
+        Some(schema)
+    }
+
+    fn register_with_app(&self, app: &mut App) {
+        app.register_foreign_rune_type::<bool>();
+        app.register_foreign_rune_type::<f32>();
+        app.register_foreign_rune_type::<f64>();
+        app.register_foreign_rune_type::<i8>();
+        app.register_foreign_rune_type::<i16>();
+        app.register_foreign_rune_type::<i32>();
+        app.register_foreign_rune_type::<i64>();
+        app.register_foreign_rune_type::<i128>();
+        app.register_foreign_rune_type::<isize>();
+        app.register_foreign_rune_type::<u8>();
+        app.register_foreign_rune_type::<u16>();
+        app.register_foreign_rune_type::<u32>();
+        app.register_foreign_rune_type::<u64>();
+        app.register_foreign_rune_type::<u128>();
+        app.register_foreign_rune_type::<usize>();
+        app.register_foreign_rune_type::<String>();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use bevy::prelude::{Transform, Vec3};
+    use bevy_mod_scripting_core::world::WorldPointerGuard;
+    use bevy_mod_scripting_rune::prelude::{
+        rune::{Source, Sources, Vm},
+        with_current_entity,
+    };
+
+    use super::*;
+
+    #[test]
+    fn scripts_can_modify_components_through_the_world() {
+        let mut app = App::new();
+        app.register_type::<Transform>();
+        RuneBevyAPIProvider.register_with_app(&mut app);
+
+        let entity = app.world.spawn(Transform::default()).id();
+
+        let mut context = Context::with_default_modules().unwrap();
+        RuneBevyAPIProvider.attach_api(&mut context).unwrap();
+
+        let mut sources = Sources::new();
+        sources
+            .insert(
+                Source::new(
+                    "test",
+                    r#"pub fn hook() {
+                        let world = world();
+                        let transform = world.get_type_by_name("Transform").unwrap();
+                        let component = world.get_component(entity(), transform).unwrap();
+                        component["translation"]["x"] = 2.0;
+                        for (e, t) in world.query([transform]).iter() {
+                            t["scale"]["y"] = t["translation"]["x"] * 2.0;
+                        }
+                    }"#,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        let unit = rune::prepare(&mut sources)
+            .with_context(&context)
+            .build()
+            .unwrap();
+
+        let mut vm = Vm::new(Arc::new(context.runtime().unwrap()), Arc::new(unit));
+
+        {
+            let world = unsafe { WorldPointerGuard::new(&mut app.world) };
+            world.enter(|| {
+                with_current_entity(entity, || {
+                    vm.execute(["hook"], ())
+                        .unwrap()
+                        .complete()
+                        .into_result()
+                        .unwrap();
+                })
+            });
+        }
+
+        let transform = app.world.get::<Transform>(entity).unwrap();
+        assert_eq!(transform.translation.x, 2.0);
+        assert_eq!(transform.scale, Vec3::new(1.0, 4.0, 1.0));
+    }
+}
@@ -0,0 +1,271 @@
+use ::std::{any::TypeId, borrow::Cow, fmt::Display};
+
+use ::bevy::{
+    prelude::{App, AppTypeRegistry, Entity},
+    reflect::{FromType, GetTypeRegistration, Reflect},
+};
+use bevy_mod_scripting_rune::prelude::{
+    rune::{
+        self,
+        alloc::fmt::TryWrite,
+        runtime::{Formatter, Protocol, ToValue, Value, VmResult},
+        Any, ContextError, Module,
+    },
+    RuneDocs,
+};
+
+use crate::{ReflectReference, ReflectedValue, ValueIndex};
+
+pub mod bevy;
+pub mod std;
+pub mod value;
+
+/// A trait allowing the registration of the [`RuneProxyable`] trait with the type registry for foreign types
+///
+/// If you have access to the type you should prefer to use `#[reflect(RuneProxyable)]` instead.
+/// This is exactly equivalent.
+pub trait RegisterForeignRuneType {
+    fn register_foreign_rune_type<T: RuneProxyable + Reflect + GetTypeRegistration>(
+        &mut self,
+    ) -> &mut Self;
+}
+
+impl RegisterForeignRuneType for App {
+    fn register_foreign_rune_type<T: RuneProxyable + Reflect + GetTypeRegistration>(
+        &mut self,
+    ) -> &mut Self {
+        {
+            let registry = self.world.resource_mut::<AppTypeRegistry>();
+            let mut registry = registry.write();
+
+            let rune_data = <ReflectRuneProxyable as FromType<T>>::from_type();
+
+            if let Some(registration) = registry.get_mut(TypeId::of::<T>()) {
+                registration.insert(rune_data)
+            } else {
+                let mut registration = T::get_type_registration();
+                registration.insert(rune_data);
+                registry.add_registration(registration);
+            }
+        }
+
+        self
+    }
+}
+
+pub trait RuneProxyable {
+    fn ref_to_rune(self_: ReflectReference) -> VmResult<Value>;
+    fn apply_rune(self_: &mut ReflectReference, new_val: Value) -> VmResult<()>;
+}
+
+pub trait FromRuneProxy: Sized {
+    fn from_rune_proxy(self_: Value) -> VmResult<Self>;
+}
+
+#[derive(Clone)]
+pub struct ReflectRuneProxyable {
+    ref_to_rune: fn(ref_: ReflectReference) -> VmResult<Value>,
+    apply_rune: fn(ref_: &mut ReflectReference, new_val: Value) -> VmResult<()>,
+}
+
+impl ReflectRuneProxyable {
+    pub fn ref_to_rune(&self, ref_: ReflectReference) -> VmResult<Value> {
+        (self.ref_to_rune)(ref_)
+    }
+
+    pub fn apply_rune(&self, ref_: &mut ReflectReference, new_val: Value) -> VmResult<()> {
+        (self.apply_rune)(ref_, new_val)
+    }
+}
+
+impl<T: RuneProxyable + Reflect> FromType<T> for ReflectRuneProxyable {
+    fn from_type() -> Self {
+        Self {
+            ref_to_rune: T::ref_to_rune,
+            apply_rune: T::apply_rune,
+        }
+    }
+}
+
+/// Converts errors raised on the rust side into Rune panics, which abort the script and get reported by the host
+pub(crate) fn vm_result<T, E: Display>(result: Result<T, E>) -> VmResult<T> {
+    match result {
+        Ok(v) => VmResult::Ok(v),
+        Err(e) => VmResult::panic(e.to_string()),
+    }
+}
+
+impl ToValue for ReflectReference {
+    fn to_value(self) -> VmResult<Value> {
+        let proxyable = {
+            let world = self.world_ptr.read();
+            let type_registry = world.resource::<AppTypeRegistry>().read();
+            let type_id = rune::vm_try!(vm_result(self.get(|s| s.type_id())));
+            type_registry
+                .get_type_data::<ReflectRuneProxyable>(type_id)
+                .cloned()
+        };
+
+        match proxyable {
+            Some(v) => v.ref_to_rune(self),
+            None => ReflectedValue { ref_: self }.to_value(),
+        }
+    }
+}
+
+pub trait ApplyRune {
+    fn apply_rune(&mut self, value: Value) -> VmResult<()>;
+}
+
+impl ApplyRune for ReflectReference {
+    fn apply_rune(&mut self, value: Value) -> VmResult<()> {
+        // release the world before applying, the proxy will need to lock it again
+        let proxyable = {
+            let world = self.world_ptr.read();
+            let type_registry = world.resource::<AppTypeRegistry>().read();
+            let type_id = rune::vm_try!(vm_result(self.get(|s| s.type_id())));
+            type_registry
+                .get_type_data::<ReflectRuneProxyable>(type_id)
+                .cloned()
+        };
+
+        if let Some(proxyable) = proxyable {
+            return proxyable.apply_rune(self, value);
+        }
+
+        if let Ok(other) = rune::from_value::<ReflectedValue>(value.clone()) {
+            return vm_result(self.apply(&other.into()));
+        }
+
+        VmResult::panic(format!(
+            "Attempted to assign `{}` = {value:?}. Did you forget to call `app.register_foreign_rune_type::<{}>`?",
+            self.path,
+            rune::vm_try!(vm_result(self.get(|s| s
+                .get_represented_type_info()
+                .map(|i| i.type_path())
+                .unwrap_or("unknown"))))
+        ))
+    }
+}
+
+impl ValueIndex<Value> for ReflectReference {
+    type Output = VmResult<Self>;
+
+    fn index(&self, index: Value) -> Self::Output {
+        match index {
+            Value::Integer(i) => match usize::try_from(i) {
+                Ok(i) => VmResult::Ok(self.index(i)),
+                Err(_) => VmResult::panic(format!("Expected a non-negative index, got {i}")),
+            },
+            Value::String(s) => {
+                let s = rune::vm_try!(s.borrow_ref());
+                VmResult::Ok(self.index(Cow::Owned(s.to_string())))
+            }
+            v => VmResult::panic(format!("Expected an integer or string index, got {v:?}")),
+        }
+    }
+}
+
+impl ReflectedValue {
+    fn install(module: &mut Module, docs: &mut RuneDocs) -> Result<(), ContextError> {
+        docs.ty::<Self>(module, &[
+            "A reference to a value living in the world, such as a component, resource or one of their fields.",
+            "Fields and elements can be read and assigned via indexing, i.e. `transform[\"translation\"][\"x\"] = 2.0`.",
+        ])?;
+        module.associated_function(Protocol::INDEX_GET, |s: &Self, index: Value| {
+            rune::vm_try!(s.ref_.index(index)).to_value()
+        })?;
+        module.associated_function(
+            Protocol::INDEX_SET,
+            |s: &Self, index: Value, value: Value| {
+                rune::vm_try!(s.ref_.index(index)).apply_rune(value)
+            },
+        )?;
+        module.associated_function(Protocol::STRING_DISPLAY, |s: &Self, f: &mut Formatter| {
+            let repr = rune::vm_try!(vm_result(s.ref_.get(|s| format!("{:?}", &s))));
+            rune::vm_write!(f, "{repr}");
+            VmResult::Ok(())
+        })?;
+        module.associated_function(Protocol::STRING_DEBUG, |s: &Self, f: &mut Formatter| {
+            rune::vm_write!(f, "{s:?}");
+            VmResult::Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// A Rune representation of an [`Entity`], Rune can only hold types which implement its own `Any` trait
+#[derive(Any, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[rune(name = Entity)]
+pub struct RuneEntity(pub Entity);
+
+impl RuneEntity {
+    pub fn new(entity: Entity) -> Self {
+        Self(entity)
+    }
+
+    pub fn inner(&self) -> Entity {
+        self.0
+    }
+
+    /// Creates an entity with the given index and a generation of 1.
+    #[rune::function(keep, path = Self::from_raw)]
+    fn from_raw(index: u32) -> Self {
+        Self(Entity::from_raw(index))
+    }
+
+    /// Reconstructs an entity previously converted with `to_bits`.
+    #[rune::function(keep, path = Self::from_bits)]
+    fn from_bits(bits: u64) -> Self {
+        Self(Entity::from_bits(bits))
+    }
+
+    /// The index of the entity, may be reused once the entity is despawned.
+    #[rune::function(keep)]
+    fn index(&self) -> u32 {
+        self.0.index()
+    }
+
+    /// The generation of the entity, incremented each time its index is reused.
+    #[rune::function(keep)]
+    fn generation(&self) -> u32 {
+        self.0.generation()
+    }
+
+    /// Converts the entity to a single integer uniquely identifying it.
+    // taking `self` by value would move the entity out of the script
+    #[allow(clippy::wrong_self_convention)]
+    #[rune::function(keep)]
+    fn to_bits(&self) -> u64 {
+        self.0.to_bits()
+    }
+
+    fn install(module: &mut Module, docs: &mut RuneDocs) -> Result<(), ContextError> {
+        docs.ty::<Self>(
+            module,
+            &["An identifier of an entity in the world, can be compared for equality."],
+        )?;
+        docs.associated_function::<Self>(module, Self::from_raw__meta)?;
+        docs.associated_function::<Self>(module, Self::from_bits__meta)?;
+        docs.associated_function::<Self>(module, Self::index__meta)?;
+        docs.associated_function::<Self>(module, Self::generation__meta)?;
+        docs.associated_function::<Self>(module, Self::to_bits__meta)?;
+        module.associated_function(Protocol::PARTIAL_EQ, |s: &Self, o: &Self| s == o)?;
+        module.associated_function(Protocol::EQ, |s: &Self, o: &Self| s == o)?;
+        module.associated_function(Protocol::STRING_DEBUG, |s: &Self, f: &mut Formatter| {
+            rune::vm_write!(f, "{:?}", s.0);
+            VmResult::Ok(())
+        })?;
+        module.associated_function(Protocol::STRING_DISPLAY, |s: &Self, f: &mut Formatter| {
+            rune::vm_write!(f, "{}v{}", s.0.index(), s.0.generation());
+            VmResult::Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+impl From<Entity> for RuneEntity {
+    fn from(value: Entity) -> Self {
+        Self(value)
+    }
+}
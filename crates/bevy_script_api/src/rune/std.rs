@@ -0,0 +1,146 @@
+use bevy_mod_scripting_rune::prelude::rune::{
+    self,
+    runtime::{ToValue, Value, VmResult},
+};
+
+use crate::ReflectReference;
+
+use super::{vm_result, FromRuneProxy, RuneProxyable};
+
+/// Implements RuneProxyable for a primitive type by converting it to and from one of Rune's own primitives
+macro_rules! impl_rune_proxy {
+    // i.e. impl_rune_proxy!(f32 as f64: Float, Integer)
+    ($type:ty as $proxy_type:ty: $($variant:ident),+) => {
+        impl RuneProxyable for $type {
+            fn ref_to_rune(self_: ReflectReference) -> VmResult<Value> {
+                rune::vm_try!(vm_result(self_.get_typed(|s: &$type| *s as $proxy_type))).to_value()
+            }
+
+            fn apply_rune(self_: &mut ReflectReference, new_val: Value) -> VmResult<()> {
+                let new_val = rune::vm_try!(Self::from_rune_proxy(new_val));
+                vm_result(self_.set_val(new_val))
+            }
+        }
+
+        impl FromRuneProxy for $type {
+            fn from_rune_proxy(self_: Value) -> VmResult<Self> {
+                match self_ {
+                    $(Value::$variant(v) => VmResult::Ok(v as $type),)+
+                    v => VmResult::panic(format!(
+                        "Expected {}, got {v:?}",
+                        stringify!($type)
+                    )),
+                }
+            }
+        }
+    };
+}
+
+/// Implements RuneProxyable for integer types by converting them to and from Rune's `i64` integers,
+/// values which do not fit into the target type raise an error instead of being truncated
+macro_rules! impl_rune_integer_proxy {
+    ($($type:ty),+) => {
+        $(
+            // the conversions are infallible for some of the types
+            #[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)]
+            impl RuneProxyable for $type {
+                fn ref_to_rune(self_: ReflectReference) -> VmResult<Value> {
+                    let v = rune::vm_try!(vm_result(self_.get_typed(|s: &$type| *s)));
+                    match i64::try_from(v) {
+                        Ok(v) => v.to_value(),
+                        Err(_) => VmResult::panic(format!("{v} does not fit into a Rune integer")),
+                    }
+                }
+
+                fn apply_rune(self_: &mut ReflectReference, new_val: Value) -> VmResult<()> {
+                    let new_val = rune::vm_try!(Self::from_rune_proxy(new_val));
+                    vm_result(self_.set_val(new_val))
+                }
+            }
+
+            #[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)]
+            impl FromRuneProxy for $type {
+                fn from_rune_proxy(self_: Value) -> VmResult<Self> {
+                    match self_ {
+                        Value::Integer(v) => match <$type>::try_from(v) {
+                            Ok(v) => VmResult::Ok(v),
+                            Err(_) => VmResult::panic(format!(
+                                "{v} does not fit into {}",
+                                stringify!($type)
+                            )),
+                        },
+                        v => VmResult::panic(format!(
+                            "Expected {}, got {v:?}",
+                            stringify!($type)
+                        )),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_rune_integer_proxy!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_rune_proxy!(f32 as f64: Float, Integer);
+impl_rune_proxy!(f64 as f64: Float, Integer);
+
+impl RuneProxyable for bool {
+    fn ref_to_rune(self_: ReflectReference) -> VmResult<Value> {
+        rune::vm_try!(vm_result(self_.get_typed(|s: &bool| *s))).to_value()
+    }
+
+    fn apply_rune(self_: &mut ReflectReference, new_val: Value) -> VmResult<()> {
+        let new_val = rune::vm_try!(Self::from_rune_proxy(new_val));
+        vm_result(self_.set_val(new_val))
+    }
+}
+
+impl FromRuneProxy for bool {
+    fn from_rune_proxy(self_: Value) -> VmResult<Self> {
+        self_.as_bool()
+    }
+}
+
+impl RuneProxyable for String {
+    fn ref_to_rune(self_: ReflectReference) -> VmResult<Value> {
+        rune::vm_try!(vm_result(self_.get_typed(|s: &String| s.clone()))).to_value()
+    }
+
+    fn apply_rune(self_: &mut ReflectReference, new_val: Value) -> VmResult<()> {
+        let new_val = rune::vm_try!(Self::from_rune_proxy(new_val));
+        vm_result(self_.set_val(new_val))
+    }
+}
+
+impl FromRuneProxy for String {
+    fn from_rune_proxy(self_: Value) -> VmResult<Self> {
+        match self_ {
+            Value::String(s) => VmResult::Ok(rune::vm_try!(s.borrow_ref()).to_string()),
+            v => VmResult::panic(format!("Expected String, got {v:?}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use bevy::prelude::World;
+    use bevy_mod_scripting_core::world::WorldPointerGuard;
+    use parking_lot::RwLock;
+
+    use super::*;
+
+    #[test]
+    fn integers_out_of_range_are_not_truncated() {
+        assert_eq!(u32::from_rune_proxy(Value::Integer(7)).into_result().unwrap(), 7);
+        assert!(u8::from_rune_proxy(Value::Integer(300)).into_result().is_err());
+        assert!(u64::from_rune_proxy(Value::Integer(-1)).into_result().is_err());
+
+        let mut world = World::new();
+        let world = unsafe { WorldPointerGuard::new(&mut world) };
+        let value = Arc::new(RwLock::new(u64::MAX));
+        let reference = ReflectReference::new_script_ref(Arc::downgrade(&value), world.clone());
+        assert!(u64::ref_to_rune(reference).into_result().is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use bevy_mod_scripting_core::world::WorldPointer;
+use bevy_mod_scripting_rune::prelude::{
+    rune::{
+        self, alloc,
+        runtime::{Stack, ToValue, Value, VmResult},
+    },
+    Args,
+};
+
+use crate::common::value::{ScriptArgs, ScriptValue};
+
+use super::RuneEntity;
+
+impl ToValue for ScriptValue {
+    /// Converts scalars and entities directly, world backed and reflected values are converted
+    /// via [`ReflectReference`](crate::ReflectReference) using the world of the script currently being executed,
+    /// which means they are subject to the same [`ReflectRuneProxyable`](super::ReflectRuneProxyable) conversions as any other reference.
+    fn to_value(self) -> VmResult<Value> {
+        match self {
+            ScriptValue::Nil => ().to_value(),
+            ScriptValue::Bool(v) => v.to_value(),
+            ScriptValue::Integer(v) => v.to_value(),
+            ScriptValue::Float(v) => v.to_value(),
+            ScriptValue::String(v) => v.to_value(),
+            ScriptValue::Entity(v) => RuneEntity(v).to_value(),
+            ScriptValue::List(v) => v.to_value(),
+            v => match WorldPointer::current() {
+                Some(world) => v
+                    .reflect_ref(world)
+                    .expect("Not a reflected value")
+                    .to_value(),
+                None => VmResult::panic("Reflected script values require a script world"),
+            },
+        }
+    }
+}
+
+impl Args for ScriptArgs {
+    fn into_stack(self, stack: &mut Stack) -> VmResult<()> {
+        for value in self.0 {
+            rune::vm_try!(stack.push(rune::vm_try!(value.to_value())));
+        }
+        VmResult::Ok(())
+    }
+
+    fn try_into_vec(self) -> VmResult<alloc::Vec<Value>> {
+        let mut vec = rune::vm_try!(alloc::Vec::try_with_capacity(self.0.len()));
+        for value in self.0 {
+            rune::vm_try!(vec.try_push(rune::vm_try!(value.to_value())));
+        }
+        VmResult::Ok(vec)
+    }
+
+    fn count(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use bevy::prelude::Entity;
+    use bevy_mod_scripting_rune::prelude::rune::{Context, Source, Sources, Vm};
+
+    use super::*;
+
+    #[test]
+    fn script_args_are_passed_as_separate_arguments() {
+        let context = Context::with_default_modules().unwrap();
+
+        let mut sources = Sources::new();
+        sources
+            .insert(
+                Source::new(
+                    "test",
+                    r#"pub fn hook(entity, amount, text, list, nothing) {
+                        `${amount}${text}${list.len()}${nothing is Tuple}`
+                    }"#,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        let unit = rune::prepare(&mut sources)
+            .with_context(&context)
+            .build()
+            .unwrap();
+        let mut vm = Vm::new(Arc::new(context.runtime().unwrap()), Arc::new(unit));
+
+        let args = ScriptArgs::new()
+            .with_arg(Entity::from_raw(42))
+            .with_arg(7)
+            .with_arg("hit")
+            .with_arg(vec![1.0, 2.0])
+            .with_arg(ScriptValue::Nil);
+
+        let out = vm
+            .execute(["hook"], args)
+            .unwrap()
+            .complete()
+            .into_result()
+            .unwrap();
+        assert_eq!(rune::from_value::<String>(out).unwrap(), "7hit2true");
+    }
+}
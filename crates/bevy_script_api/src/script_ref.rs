@@ -3,7 +3,7 @@ use parking_lot::RwLock;
 use std::fmt::Debug;
 use std::{borrow::Cow, sync::Weak};
 
-use bevy_mod_scripting_core::world::WorldPointer;
+use bevy_mod_scripting_core::world::{WorldPointer, WorldPointerGuard};
 
 use crate::{
     error::ReflectionError,
@@ -126,7 +126,7 @@ impl ReflectReference {
         // can we avoid the box ?
         let cloned = other.get(|s| s.clone_value())?;
 
-        self.get_mut(|s| s.apply(&*cloned))
+        self.write(cloned, |s, cloned| s.apply(&*cloned))
     }
 
     /// Unlike apply this method expects the other type to be identical. Does not allocate so is likely to be faster than apply, uses direct assignment.
@@ -136,7 +136,7 @@ impl ReflectReference {
         T: Reflect + Clone,
     {
         let other: T = other.get_typed(|s: &T| s.clone())?;
-        self.get_mut_typed(|s| *s = other)
+        self.set_val(other)
     }
 
     /// Version of [`Self::set`](TypedReflectReference) which directly accepts a `T` value
@@ -144,10 +144,45 @@ impl ReflectReference {
     where
         T: Reflect,
     {
-        self.get_mut_typed(|s| *s = other)
+        self.write(other, |s, other| *s.downcast_mut::<T>().unwrap() = other)
+    }
+
+    /// Writes the value to the referenced value with the given function.
+    ///
+    /// Read-only scripts cannot lock the world for writing, so their writes to world data
+    /// are recorded into their [`ScriptCommandQueue`](bevy_mod_scripting_core::world::ScriptCommandQueue)
+    /// and happen once the script finishes handling its events. Errors in such writes are logged instead of returned.
+    fn write<V: Send + 'static>(
+        &mut self,
+        value: V,
+        write: fn(&mut dyn Reflect, V),
+    ) -> Result<(), ReflectionError> {
+        let commands = match self.world_ptr.commands() {
+            Some(commands) if self.world_ptr.is_read_only() && !self.path.is_script_owned() => {
+                commands
+            }
+            _ => return self.get_mut(|s| write(s, value)),
+        };
+
+        let mut path = DeferredPath(self.path.clone());
+        commands.push(move |world: &mut World| {
+            // Safety: the world is only accessed through the guard until it is dropped
+            let world = unsafe { WorldPointerGuard::new(world) };
+            let DeferredPath(path) = &mut path;
+            if let Err(e) = path.get_mut(world.clone(), |s| write(s, value)) {
+                warn!("Deferred write to `{path}` failed: {e}");
+            }
+        });
+        Ok(())
     }
 }
 
+/// A reflection path moved into a deferred command.
+struct DeferredPath(ReflectionPath);
+
+/// Safety: see [`ReflectReference`], the path is only ever accessed through the world the command runs on.
+unsafe impl Send for DeferredPath {}
+
 /// A version of index for returning values instead of references
 pub trait ValueIndex<Idx> {
     type Output;
@@ -174,6 +209,11 @@ impl ValueIndex<Cow<'static, str>> for ReflectReference {
 /// A value representing a type which has no special UserData implementation,
 /// It exposes the much less convenient reflect interface of the underlying type.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "rune", derive(bevy_mod_scripting_rune::prelude::rune::Any))]
+#[cfg_attr(
+    feature = "rune",
+    rune(module = bevy_mod_scripting_rune::prelude::rune)
+)]
 pub struct ReflectedValue {
     pub(crate) ref_: ReflectReference,
 }
@@ -345,10 +345,23 @@ impl ReflectionPath {
         }
     }
 
+    /// Returns true if the base of this path is owned by a script rather than the world
+    pub fn is_script_owned(&self) -> bool {
+        matches!(self.base, ReflectBase::ScriptOwned { .. })
+    }
+
     pub fn get_mut<O, F>(&mut self, world_ptr: WorldPointer, f: F) -> Result<O, ReflectionError>
     where
         F: FnOnce(&mut dyn Reflect) -> O,
     {
+        if world_ptr.is_read_only() && !self.is_script_owned() {
+            return Err(ReflectionError::InsufficientProvenance {
+                path: self.to_string(),
+                msg: "Read-only scripts can only assign to world data, which is deferred, other mutations are not allowed"
+                    .to_owned(),
+            });
+        }
+
         match &self.base {
             ReflectBase::Component { comp, entity } => {
                 let mut g = world_ptr.write();
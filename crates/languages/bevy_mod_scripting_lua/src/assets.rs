@@ -1,16 +1,31 @@
 use bevy::{
-    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt},
+    asset::{
+        io::{Reader, Writer},
+        saver::{AssetSaver, SavedAsset},
+        Asset, AssetLoader, AsyncReadExt, AsyncWriteExt, LoadContext,
+    },
     reflect::TypePath,
     utils::BoxedFuture,
 };
 use bevy_mod_scripting_core::asset::CodeAsset;
+use serde::{Deserialize, Serialize};
+use tealr::mlu::mlua::{ChunkMode, Lua};
+
+#[cfg(feature = "teal")]
+use crate::teal::TealCompiler;
+#[cfg(feature = "teal")]
+use bevy::asset::io::file::FileAssetReader;
+#[cfg(feature = "teal")]
+use std::sync::Mutex;
 
 use anyhow::Error;
 
 #[derive(Asset, TypePath, Debug)]
-/// A lua code file in bytes
+/// A lua code file in bytes, either lua source or a precompiled lua chunk (see [`LuaBytecodeSaver`])
 pub struct LuaFile {
     pub bytes: Vec<u8>,
+    /// true if the bytes are a precompiled lua chunk rather than source code, see [`LuaLoaderSettings::bytecode`]
+    pub bytecode: bool,
 }
 
 impl CodeAsset for LuaFile {
@@ -19,129 +34,100 @@ impl CodeAsset for LuaFile {
     }
 }
 
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+/// Settings for loading lua scripts, stored in the `.meta` file of a script
+pub struct LuaLoaderSettings {
+    /// If true, the file holds a precompiled lua chunk.
+    ///
+    /// Only set by [`LuaBytecodeSaver`] for the files it outputs, files which are not marked like this
+    /// are always loaded as source code, so that arbitrary files cannot smuggle in lua bytecode.
+    pub bytecode: bool,
+}
+
 #[derive(Default)]
-/// Asset loader for lua scripts
-pub struct LuaLoader;
-
-fn old_lua_load<'a>(
-    bytes: &'a [u8],
-    load_context: &'a mut bevy::asset::LoadContext,
-) -> BoxedFuture<'a, Result<Vec<u8>, Error>> {
-    match load_context.path().extension().map(|s| s.to_str().unwrap()) {
-        #[cfg(all(feature = "teal", debug_assertions))]
-        Some("tl") => {
-            use bevy::asset::io::file::FileAssetReader;
-            use std::fs;
-            use std::path::PathBuf;
-            use std::process::Command;
-
-            let scripts_dir = &FileAssetReader::get_base_path()
-                .join("assets")
-                .join("scripts");
-
-            let temp_file_path = &std::env::temp_dir().join("bevy_mod_scripting.temp.lua");
-            bevy::prelude::info!("tl file path {}", scripts_dir.to_str().unwrap());
-            // optionally put the output in the /build folder
-            let build_dir_path: Option<PathBuf> =
-                if load_context.path().starts_with("scripts/build/") {
-                    Some(
-                        load_context
-                            .path()
-                            .strip_prefix("scripts/")
-                            .unwrap()
-                            .to_owned(),
-                    )
-                } else if load_context.path().starts_with("scripts/") {
-                    Some(
-                        PathBuf::from("build/")
-                            .join(load_context.path().strip_prefix("scripts/").unwrap()),
-                    )
-                } else {
-                    None
-                };
-
-            let full_path = &FileAssetReader::get_base_path()
-                .join("assets")
-                .join(load_context.path());
-            bevy::log::info!(
-                "tl check {} : {}",
-                full_path.to_str().unwrap(),
-                scripts_dir.to_str().unwrap()
-            );
-            if let Ok(e) = Command::new("tl")
-                .args(["check", full_path.to_str().unwrap()])
-                .current_dir(scripts_dir)
-                .status()
-            {
-                if !e.success() {
-                    return Box::pin(async move {
-                        Err(Error::msg(format!(
-                            "Teal file `{}` has errors",
-                            load_context.path().to_str().unwrap()
-                        )))
-                    });
-                }
-            } else {
-                fs::remove_file(temp_file_path).expect("Something went wrong running `tl check`");
-                panic!("Something went wrong running `tl check`");
-            }
+/// Asset loader for lua scripts.
+///
+/// With the `teal` feature `.tl` files are type checked and compiled into lua as they are loaded, see [`TealCompiler`].
+pub struct LuaLoader {
+    /// set up on the first teal file loaded
+    #[cfg(feature = "teal")]
+    teal: Mutex<Option<TealCompiler>>,
+}
 
-            if let Ok(e) = Command::new("tl")
-                .args([
-                    "gen",
-                    full_path.to_str().unwrap(),
-                    "-o",
-                    temp_file_path.to_str().unwrap(),
-                ])
-                .current_dir(scripts_dir)
-                .status()
-            {
-                if !e.success() {
-                    return Box::pin(async move {
-                        Err(Error::msg(format!(
-                            "Teal file `{}` could not be compiled!",
-                            load_context.path().to_str().unwrap()
-                        )))
-                    });
-                }
+#[cfg(feature = "teal")]
+impl LuaLoader {
+    /// Compiles a teal file into lua code.
+    ///
+    /// Declaration files are searched for in `assets/scripts`, including the global environment definition in `types/types.d.tl` if it exists.
+    /// In debug builds the compiled files are also written into `assets/scripts/build`, which `lua_path!` points to in release builds.
+    fn compile_teal(&self, source: &[u8], load_context: &LoadContext) -> Result<Vec<u8>, Error> {
+        let scripts_dir = &FileAssetReader::get_base_path()
+            .join("assets")
+            .join("scripts");
+
+        let mut teal = self.teal.lock().unwrap();
+        if teal.is_none() {
+            let global_env_def = scripts_dir
+                .join("types/types.d.tl")
+                .exists()
+                .then_some("types/types");
+            *teal = Some(TealCompiler::new(scripts_dir, global_env_def)?);
+        }
+        let compiler = teal.as_ref().unwrap();
+
+        let path = load_context.path();
+        let lua_code = compiler.compile(std::str::from_utf8(source)?, &path.to_string_lossy())?;
+
+        #[cfg(debug_assertions)]
+        if let Ok(relative) = path.strip_prefix("scripts/") {
+            let build_path = if relative.starts_with("build/") {
+                scripts_dir.join(relative)
             } else {
-                fs::remove_file(temp_file_path).expect("Something went wrong running `tl gen`");
-                panic!("Something went wrong running `tl gen`")
+                scripts_dir.join("build").join(relative)
             }
+            .with_extension("lua");
 
-            if let Some(mut build_dir_path) = build_dir_path {
-                build_dir_path = scripts_dir.join(build_dir_path);
-                let _ = fs::create_dir_all(build_dir_path.parent().unwrap());
-                let _ = fs::copy(temp_file_path, build_dir_path.with_extension("lua"));
+            if let Some(parent) = build_path.parent() {
+                let _ = std::fs::create_dir_all(parent);
             }
-
-            let lua_code =
-                fs::read_to_string(temp_file_path).expect("Could not find output lua file");
-            fs::remove_file(temp_file_path).unwrap();
-
-            Box::pin(async move { Ok(lua_code.as_bytes().into()) })
+            let _ = std::fs::write(build_path, &lua_code);
         }
 
-        _ => Box::pin(async move { Ok(bytes.into()) }),
+        Ok(lua_code.into_bytes())
     }
 }
+
 impl AssetLoader for LuaLoader {
     type Asset = LuaFile;
-    type Settings = ();
+    type Settings = LuaLoaderSettings;
     type Error = Error;
 
     fn load<'a>(
         &'a self,
-        reader: &'a mut Reader, //bytes: &'a [u8],
-        _settings: &'a (),
-        load_context: &'a mut bevy::asset::LoadContext,
-    ) -> bevy::asset::BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        reader: &'a mut Reader,
+        settings: &'a LuaLoaderSettings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
         bevy::prelude::info!("lua loader invoked: {:#}", load_context.asset_path());
         Box::pin(async move {
             let mut bytes = Vec::new();
             reader.read_to_end(&mut bytes).await?;
-            let bytes = old_lua_load(bytes.as_slice(), load_context).await?;
-            Ok(LuaFile { bytes }) //})
+
+            // teal files which were already compiled by the asset processor are left as is
+            #[cfg(feature = "teal")]
+            if load_context
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "tl")
+                && !settings.bytecode
+            {
+                bytes = self.compile_teal(&bytes, load_context)?;
+            }
+
+            Ok(LuaFile {
+                bytes,
+                bytecode: settings.bytecode,
+            })
         })
     }
 
@@ -154,3 +140,86 @@ impl AssetLoader for LuaLoader {
         &["lua"]
     }
 }
+
+#[derive(Default)]
+/// Asset saver compiling lua files into stripped lua bytecode, for use with Bevy's asset processor.
+///
+/// `LuaScriptHost` registers it as the default processor of `.lua` files (and `.tl` files with the `teal` feature) via [`LoadAndSave`](bevy::asset::processor::LoadAndSave),
+/// it only takes effect when the `AssetPlugin` runs in `AssetMode::Processed`.
+/// Stripped chunks carry no debug information, so errors raised by processed scripts lack line numbers.
+pub struct LuaBytecodeSaver;
+
+impl AssetSaver for LuaBytecodeSaver {
+    type Asset = LuaFile;
+    type Settings = ();
+    type OutputLoader = LuaLoader;
+    type Error = Error;
+
+    fn save<'a>(
+        &'a self,
+        writer: &'a mut Writer,
+        asset: SavedAsset<'a, Self::Asset>,
+        _settings: &'a (),
+    ) -> BoxedFuture<'a, Result<LuaLoaderSettings, Self::Error>> {
+        Box::pin(async move {
+            let asset = asset.get();
+            let bytecode = if asset.bytecode {
+                asset.bytes.clone()
+            } else {
+                // stripped chunks do not retain their name either
+                compile_bytecode(&asset.bytes, "lua file", true)?
+            };
+            writer.write_all(&bytecode).await?;
+            Ok(LuaLoaderSettings { bytecode: true })
+        })
+    }
+}
+
+/// Compiles lua source code into a lua chunk
+pub(crate) fn compile_bytecode(
+    source: &[u8],
+    name: &str,
+    strip: bool,
+) -> Result<Vec<u8>, tealr::mlu::mlua::Error> {
+    let lua = Lua::new();
+    let chunk = lua
+        .load(source)
+        .set_name(name)
+        .set_mode(ChunkMode::Text)
+        .into_function()?;
+    Ok(chunk.dump(strip))
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        asset::{ErasedLoadedAsset, LoadedAsset},
+        tasks::block_on,
+    };
+
+    use super::*;
+
+    #[test]
+    fn saved_files_are_marked_as_bytecode() {
+        let loaded: ErasedLoadedAsset = LoadedAsset::from(LuaFile {
+            bytes: b"value = 4".to_vec(),
+            bytecode: false,
+        })
+        .into();
+        let saved = SavedAsset::from_loaded(&loaded).unwrap();
+
+        let mut bytes = Vec::new();
+        let settings = block_on(LuaBytecodeSaver.save(&mut bytes, saved, &())).unwrap();
+        assert!(settings.bytecode);
+
+        let lua = Lua::new();
+        lua.load(&bytes).set_mode(ChunkMode::Binary).exec().unwrap();
+        assert_eq!(lua.globals().get::<_, i64>("value").unwrap(), 4);
+    }
+
+    #[test]
+    fn source_files_cannot_be_compiled_from_bytecode() {
+        let bytecode = compile_bytecode(b"value = 4", "test", true).unwrap();
+        assert!(compile_bytecode(&bytecode, "test", true).is_err());
+    }
+}
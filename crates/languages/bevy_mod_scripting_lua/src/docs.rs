@@ -2,16 +2,21 @@ use std::{
     env,
     fs::{self, File},
     io::Write,
-    process::Command,
 };
 
 //use bevy::asset::FileAssetIo;
 use bevy::asset::io::file::FileAssetReader;
-use bevy_mod_scripting_core::prelude::*;
+use bevy_mod_scripting_core::{docs::write_doc_file, prelude::*};
 use tealr::{TypeGenerator, TypeWalker};
 
+use crate::{
+    luals::{gen_luals_annotations, gen_luals_hooks},
+    reference::LuaApiReference,
+};
+
 pub type TypeWalkerBuilder = fn(TypeWalker) -> TypeWalker;
 
+#[cfg(feature = "teal")]
 static DEFAULT_DOC_CONFIG: fn(&str) -> String = |s| {
     format!(
         r#"
@@ -36,6 +41,9 @@ static DEFAULT_DOC_CONFIG: fn(&str) -> String = |s| {
     )
 };
 
+#[cfg(feature = "teal")]
+use std::process::Command;
+
 #[cfg(feature = "teal")]
 static DEFAULT_TEAL_CONFIG: &str = r#"
 return {
@@ -51,15 +59,17 @@ struct Fragment {
 pub struct LuaDocFragment {
     name: &'static str,
     walker: Vec<Fragment>,
+    hooks: Vec<HookSchema>,
 }
 
 /// A piece of lua documentation,
-/// Each piece is combined into one large documentation page, and also a single teal declaration file if the `teal` feature is enabled
+/// Each piece is combined into one large documentation page, a file of LuaLS annotations, and also a single teal declaration file if the `teal` feature is enabled
 impl LuaDocFragment {
     pub fn new(name: &'static str, f: TypeWalkerBuilder) -> Self {
         Self {
             name,
             walker: vec![Fragment { builder: f }],
+            hooks: Vec::default(),
         }
     }
 }
@@ -71,6 +81,12 @@ impl DocFragment for LuaDocFragment {
 
     fn merge(mut self, o: Self) -> Self {
         self.walker.extend(o.walker);
+        self.hooks.extend(o.hooks);
+        self
+    }
+
+    fn with_hooks(mut self, hooks: &[HookSchema]) -> Self {
+        self.hooks.extend_from_slice(hooks);
         self
     }
 
@@ -83,8 +99,12 @@ impl DocFragment for LuaDocFragment {
             .map(|v| v.into())
             .unwrap_or_else(|_e| script_asset_path.join("doc"));
 
-        fs::create_dir_all(script_doc_dir)
-            .expect("Could not create `.../assets/scripts/doc` directories");
+        fs::create_dir_all(script_doc_dir).map_err(|e| {
+            ScriptError::DocGenError(format!(
+                "Could not create `{}` directories: {e}",
+                script_doc_dir.display()
+            ))
+        })?;
 
         let docs_name = self.name().to_owned();
 
@@ -118,23 +138,60 @@ impl DocFragment for LuaDocFragment {
             })
             .map_err(|e| ScriptError::DocGenError(e.to_string()))?;
 
-        // generate doc config files if they don't exist
-        if !script_doc_dir.join("tealr_doc_gen_config.json").exists() {
-            let config_path = script_doc_dir.join("tealr_doc_gen_config.json");
-            File::create(config_path)
-                .and_then(|mut file| file.write_all(DEFAULT_DOC_CONFIG(&docs_name).as_bytes()))
-                .map_err(|e| ScriptError::DocGenError(e.to_string()))?;
-        }
-
-        // generate docs
-        Command::new("tealr_doc_gen")
-            .current_dir(script_doc_dir)
-            .args(["run"])
-            .status()
-            .map_err(|e| ScriptError::DocGenError(e.to_string()))?;
+        // generate LuaLS annotations, these do not need any external tools.
+        // they are kept out of the assets folder so they are not loaded or processed as scripts
+        let luals_dir = &env::var("SCRIPT_LUALS_DIR")
+            .map(|v| v.into())
+            .unwrap_or_else(|_e| FileAssetReader::get_base_path().join("luals"));
+        fs::create_dir_all(luals_dir).map_err(|e| {
+            ScriptError::DocGenError(format!(
+                "Could not create `{}` directories: {e}",
+                luals_dir.display()
+            ))
+        })?;
+
+        let mut annotations =
+            gen_luals_annotations(&tw).map_err(|e| ScriptError::DocGenError(e.to_string()))?;
+        annotations.push_str(&gen_luals_hooks(&self.hooks));
+        write_doc_file(&luals_dir.join(format!("{}.lua", docs_name)), &annotations)?;
+
+        // generate the API reference pages
+        let reference = LuaApiReference::from_walker(&tw)
+            .map_err(|e| ScriptError::DocGenError(e.to_string()))?
+            .with_hooks(&self.hooks);
+        write_doc_file(
+            &script_doc_dir.join(format!("{}.md", docs_name)),
+            &reference.to_markdown(&docs_name),
+        )?;
+        write_doc_file(
+            &script_doc_dir.join(format!("{}.html", docs_name)),
+            &reference.to_html(&docs_name),
+        )?;
 
         #[cfg(feature = "teal")]
         {
+            // generate doc config files if they don't exist
+            if !script_doc_dir.join("tealr_doc_gen_config.json").exists() {
+                let config_path = script_doc_dir.join("tealr_doc_gen_config.json");
+                File::create(config_path)
+                    .and_then(|mut file| file.write_all(DEFAULT_DOC_CONFIG(&docs_name).as_bytes()))
+                    .map_err(|e| ScriptError::DocGenError(e.to_string()))?;
+            }
+
+            // the teal definition file is generated by `tealr_doc_gen`
+            let status = Command::new("tealr_doc_gen")
+                .current_dir(script_doc_dir)
+                .args(["run"])
+                .status()
+                .map_err(|e| {
+                    ScriptError::DocGenError(format!("Could not run `tealr_doc_gen`: {e}"))
+                })?;
+            if !status.success() {
+                return Err(ScriptError::DocGenError(format!(
+                    "`tealr_doc_gen` failed with {status}"
+                )));
+            }
+
             // now manage the definition (d.tl) file
             let definition_directory = script_asset_path.join("types");
             fs::create_dir_all(&definition_directory).map_err(|e| {
@@ -1,23 +1,42 @@
 use crate::{
-    assets::{LuaFile, LuaLoader},
+    assets::{compile_bytecode, LuaBytecodeSaver, LuaFile, LuaLoader},
     docs::LuaDocFragment,
+    modules::{LuaAssetSearcher, LuaDependencies},
+};
+use bevy::{
+    asset::processor::LoadAndSave,
+    ecs::schedule::ScheduleLabel,
+    prelude::*,
+    tasks::{ComputeTaskPool, TaskPool},
+};
+use bevy_mod_scripting_core::{
+    prelude::*,
+    systems::*,
+    world::{WorldMutationMode, WorldPointer, WorldPointerGuard},
 };
-use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
-use bevy_mod_scripting_core::{prelude::*, systems::*, world::WorldPointerGuard};
 
+use std::collections::HashMap;
 use std::fmt;
 use std::marker::PhantomData;
-use std::sync::Mutex;
-use tealr::mlu::mlua::{prelude::*, Function};
+use std::sync::{Arc, Mutex};
+#[cfg(any(feature = "lua52", feature = "lua53", feature = "lua54"))]
+use tealr::mlu::mlua::ffi;
+use tealr::mlu::mlua::{prelude::*, ChunkMode, Function};
 
 pub mod assets;
 pub mod docs;
+pub mod luals;
+pub mod modules;
+pub mod reference;
+#[cfg(feature = "teal")]
+pub mod teal;
 pub mod util;
 pub use tealr;
 pub mod prelude {
     pub use crate::{
-        assets::{LuaFile, LuaLoader},
+        assets::{LuaBytecodeSaver, LuaFile, LuaLoader, LuaLoaderSettings},
         docs::{LuaDocFragment, TypeWalkerBuilder},
+        modules::LuaAssetSearcher,
         tealr::{
             self,
             mlu::{
@@ -27,6 +46,9 @@ pub mod prelude {
         },
         LuaEvent, LuaScriptHost,
     };
+
+    #[cfg(feature = "teal")]
+    pub use crate::teal::{TealCompiler, TealDiagnostic, TealError};
 }
 
 pub trait LuaArg: for<'lua> IntoLuaMulti<'lua> + Clone + Sync + Send + 'static {}
@@ -59,46 +81,80 @@ impl<A: LuaArg> ScriptEvent for LuaEvent<A> {
 
 #[derive(Resource)]
 /// Lua script host, enables Lua scripting.
-pub struct LuaScriptHost<A: LuaArg> {
-    _ph: PhantomData<A>,
+///
+/// Multiple Lua hosts can be registered by giving each a distinct label `L`,
+/// each host then only runs the scripts in `ScriptCollection<LuaFile, L>` components.
+///
+/// Script assets are compiled once into lua bytecode which is shared by every instance of the script,
+/// the compiled chunks are discarded whenever their asset changes.
+pub struct LuaScriptHost<A: LuaArg, L: ScriptLabel = ()> {
+    /// resolves `require` calls against `LuaFile` assets, installed on startup
+    pub module_searcher: Option<LuaAssetSearcher>,
+    /// the modules each script required, until taken by [`ScriptHost::take_script_dependencies`]
+    dependencies: HashMap<u32, LuaDependencies>,
+    /// the compiled chunk of each script asset
+    chunks: HashMap<AssetId<LuaFile>, Arc<[u8]>>,
+    _ph: PhantomData<(A, L)>,
 }
 
-impl<A: LuaArg> Default for LuaScriptHost<A> {
+impl<A: LuaArg, L: ScriptLabel> Default for LuaScriptHost<A, L> {
     fn default() -> Self {
         Self {
+            module_searcher: None,
+            dependencies: Default::default(),
+            chunks: Default::default(),
             _ph: Default::default(),
         }
     }
 }
 
-impl<A: LuaArg> ScriptHost for LuaScriptHost<A> {
+impl<A: LuaArg, L: ScriptLabel> ScriptHost for LuaScriptHost<A, L> {
     type ScriptContext = Mutex<Lua>;
     type APITarget = Mutex<Lua>;
     type ScriptEvent = LuaEvent<A>;
     type ScriptAsset = LuaFile;
     type DocTarget = LuaDocFragment;
+    type Label = L;
 
     fn register_with_app_in_set(app: &mut App, schedule: impl ScheduleLabel, set: impl SystemSet) {
+        // other lua hosts might have registered the asset already
+        if !app.world.contains_resource::<Assets<LuaFile>>() {
+            app.init_asset::<LuaFile>()
+                .init_asset_loader::<LuaLoader>()
+                .register_type::<Handle<LuaFile>>();
+
+            // precompiles scripts into bytecode, only used if the asset processor is enabled
+            type LuaProcessor = LoadAndSave<LuaLoader, LuaBytecodeSaver>;
+            app.register_asset_processor::<LuaProcessor>(LuaBytecodeSaver.into())
+                .set_default_asset_processor::<LuaProcessor>("lua");
+            #[cfg(feature = "teal")]
+            app.set_default_asset_processor::<LuaProcessor>("tl");
+        }
+
         app.add_priority_event::<Self::ScriptEvent>()
-            .init_asset::<LuaFile>()
-            .init_asset_loader::<LuaLoader>()
             .init_resource::<CachedScriptState<Self>>()
-            .init_resource::<ScriptContexts<Self::ScriptContext>>()
+            .init_resource::<ScriptContexts<Self::ScriptContext, L>>()
             .init_resource::<APIProviders<Self>>()
-            .register_type::<ScriptCollection<Self::ScriptAsset>>()
-            .register_type::<Script<Self::ScriptAsset>>()
-            .register_type::<Handle<LuaFile>>()
+            .register_type::<ScriptCollection<Self::ScriptAsset, L>>()
+            .register_type::<Script<Self::ScriptAsset, L>>()
             // handle script insertions removal first
             // then update their contexts later on script asset changes
             .add_systems(
                 schedule,
                 (
+                    Self::sync_assets,
                     script_add_synchronizer::<Self>,
                     script_remove_synchronizer::<Self>,
                     script_hot_reload_handler::<Self>,
                 )
                     .chain()
                     .in_set(set),
+            )
+            .add_systems(
+                Startup,
+                |mut host: ResMut<Self>, asset_server: Res<AssetServer>| {
+                    host.module_searcher = Some(LuaAssetSearcher::new(asset_server.clone()));
+                },
             );
     }
 
@@ -108,29 +164,38 @@ impl<A: LuaArg> ScriptHost for LuaScriptHost<A> {
         script_data: &ScriptData,
         providers: &mut APIProviders<Self>,
     ) -> Result<Self::ScriptContext, ScriptError> {
-        #[cfg(feature = "unsafe_lua_modules")]
-        let lua = unsafe { Lua::unsafe_new() };
-        #[cfg(not(feature = "unsafe_lua_modules"))]
-        let lua = Lua::new();
-
-        // init lua api before loading script
-        let mut lua = Mutex::new(lua);
-        providers.attach_all(&mut lua)?;
-
-        lua.get_mut()
-            .map_err(|e| ScriptError::FailedToLoad {
+        let chunk = compile_bytecode(script, script_data.name, false).map_err(|e| {
+            ScriptError::FailedToLoad {
                 script: script_data.name.to_owned(),
                 msg: e.to_string(),
-            })?
-            .load(script)
-            .set_name(script_data.name)
-            .exec()
-            .map_err(|e| ScriptError::FailedToLoad {
-                script: script_data.name.to_owned(),
-                msg: e.to_string(),
-            })?;
+            }
+        })?;
+        self.load_chunk(&chunk, script_data, providers)
+    }
 
-        Ok(lua)
+    fn load_script_asset(
+        &mut self,
+        asset: AssetId<Self::ScriptAsset>,
+        script: &[u8],
+        script_data: &ScriptData,
+        providers: &mut APIProviders<Self>,
+    ) -> Result<Self::ScriptContext, ScriptError> {
+        let chunk = match self.chunks.get(&asset) {
+            Some(chunk) => chunk.clone(),
+            None => {
+                // debug information is kept so that errors point at the right lines
+                let chunk: Arc<[u8]> = compile_bytecode(script, script_data.name, false)
+                    .map_err(|e| ScriptError::FailedToLoad {
+                        script: script_data.name.to_owned(),
+                        msg: e.to_string(),
+                    })?
+                    .into();
+                self.chunks.insert(asset, chunk.clone());
+                chunk
+            }
+        };
+
+        self.load_chunk(&chunk, script_data, providers)
     }
 
     fn setup_script(
@@ -142,6 +207,32 @@ impl<A: LuaArg> ScriptHost for LuaScriptHost<A> {
         providers.setup_all(script_data, ctx)
     }
 
+    fn take_script_dependencies(
+        &mut self,
+        script_data: &ScriptData,
+    ) -> Vec<AssetId<Self::ScriptAsset>> {
+        self.dependencies
+            .remove(&script_data.sid)
+            .map(|deps| deps.lock().unwrap().iter().map(Handle::id).collect())
+            .unwrap_or_default()
+    }
+
+    /// The arity of functions is only reported from lua 5.2 onwards and not for variadic functions
+    fn script_functions(&self, ctx: &Self::ScriptContext) -> Vec<ScriptFunction> {
+        let ctx = ctx.lock().expect("Could not get lock on script context");
+        let arity = function_arity(&ctx);
+        ctx.globals()
+            .pairs::<String, LuaValue>()
+            .filter_map(|pair| match pair {
+                Ok((name, LuaValue::Function(f))) => Some(ScriptFunction {
+                    name,
+                    arity: arity.as_ref().and_then(|arity| arity.call(f).ok().flatten()),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn handle_events<'a>(
         &mut self,
         world: &mut World,
@@ -149,49 +240,313 @@ impl<A: LuaArg> ScriptHost for LuaScriptHost<A> {
         ctxs: impl Iterator<Item = (ScriptData<'a>, &'a mut Self::ScriptContext)>,
         providers: &mut APIProviders<Self>,
     ) {
+        let mode = world
+            .get_resource::<WorldMutationMode>()
+            .copied()
+            .unwrap_or_default();
+
         // safety:
         // - we have &mut World access
         // - we do not use the original reference again anywhere in this function
         let world = unsafe { WorldPointerGuard::new(world) };
 
         ctxs.for_each(|(script_data, ctx)| {
+            let script_world = world.with_mode(mode);
             providers
-                .setup_runtime_all(world.clone(), &script_data, ctx)
+                .setup_runtime_all(script_world.clone(), &script_data, ctx)
                 .expect("Could not setup script runtime");
 
             let ctx = ctx.get_mut().expect("Poison error in context");
 
-            // event order is preserved, but scripts can't rely on any temporal
-            // guarantees when it comes to other scripts callbacks,
-            // at least for now.
-            let globals = ctx.globals();
-            for event in events {
-                // check if this script should handle this event
-                if !event.recipients().is_recipient(&script_data) {
-                    continue;
-                }
+            let errors = Self::call_hooks(ctx, &script_data, events);
+            Self::report_errors(&world, errors);
 
-                let f: Function = match globals.raw_get(event.hook_name.clone()) {
-                    Ok(f) => f,
-                    Err(_) => continue, // not subscribed to this event
-                };
+            // apply any structural changes the script deferred
+            script_world.apply_deferred();
+        });
+    }
 
-                if let Err(error) = f.call::<_, ()>(event.args.clone()) {
-                    let mut world = world.write();
-                    let mut state: CachedScriptState<Self> = world.remove_resource().unwrap();
+    fn handle_events_parallel<'a>(
+        &mut self,
+        world: &mut World,
+        events: &[Self::ScriptEvent],
+        ctxs: Vec<(ScriptData<'a>, &'a mut Self::ScriptContext)>,
+        providers: &mut APIProviders<Self>,
+    ) {
+        // safety:
+        // - we have &mut World access
+        // - we do not use the original reference again anywhere in this function
+        let world = unsafe { WorldPointerGuard::new(world) };
 
-                    let (_, mut error_wrt, _) = state.event_state.get_mut(&mut world);
+        let mut ctxs = ctxs
+            .into_iter()
+            .map(|(script_data, ctx)| {
+                let script_world = world.read_only();
+                providers
+                    .setup_runtime_all(script_world.clone(), &script_data, ctx)
+                    .expect("Could not setup script runtime");
+                (script_data, ctx, script_world)
+            })
+            .collect::<Vec<_>>();
 
-                    let error = ScriptError::RuntimeError {
-                        script: script_data.name.to_owned(),
-                        msg: error.to_string(),
-                    };
+        // each script owns its own lua state and can only take read locks on the world,
+        // so they can safely run side by side
+        let errors = ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+            for (script_data, ctx, _) in ctxs.iter_mut() {
+                scope.spawn(async move {
+                    let ctx = ctx.get_mut().expect("Poison error in context");
+                    Self::call_hooks(ctx, script_data, events)
+                });
+            }
+        });
 
-                    error!("{}", error);
-                    error_wrt.send(ScriptErrorEvent { error });
-                    world.insert_resource(state);
+        // apply the structural changes in a deterministic order once every script is done
+        for (_, _, script_world) in &ctxs {
+            script_world.apply_deferred();
+        }
+
+        Self::report_errors(&world, errors.into_iter().flatten().collect());
+    }
+}
+
+impl<A: LuaArg, L: ScriptLabel> LuaScriptHost<A, L> {
+    /// Creates a new lua state with the API attached and runs the given lua bytecode in it
+    fn load_chunk(
+        &mut self,
+        chunk: &[u8],
+        script_data: &ScriptData,
+        providers: &mut APIProviders<Self>,
+    ) -> Result<Mutex<Lua>, ScriptError> {
+        #[cfg(feature = "unsafe_lua_modules")]
+        let lua = unsafe { Lua::unsafe_new() };
+        #[cfg(not(feature = "unsafe_lua_modules"))]
+        let lua = Lua::new();
+
+        // init lua api before loading script
+        let mut lua = Mutex::new(lua);
+        providers.attach_all(&mut lua)?;
+
+        let lua_mut = lua.get_mut().map_err(|e| ScriptError::FailedToLoad {
+            script: script_data.name.to_owned(),
+            msg: e.to_string(),
+        })?;
+
+        // installed before the script runs, so that requires at the top level are recorded
+        // even if the script fails to load because a module isn't loaded yet
+        if let Some(searcher) = &self.module_searcher {
+            let dependencies = LuaDependencies::default();
+            searcher
+                .install(lua_mut, dependencies.clone())
+                .map_err(|e| ScriptError::FailedToLoad {
+                    script: script_data.name.to_owned(),
+                    msg: e.to_string(),
+                })?;
+            self.dependencies.insert(script_data.sid, dependencies);
+        }
+
+        lua_mut
+            .load(chunk)
+            .set_name(script_data.name)
+            .set_mode(ChunkMode::Binary)
+            .exec()
+            .map_err(|e| ScriptError::FailedToLoad {
+                script: script_data.name.to_owned(),
+                msg: e.to_string(),
+            })?;
+
+        Ok(lua)
+    }
+
+    /// Calls the hooks subscribed to the given events on a single script, returning any errors raised
+    fn call_hooks(ctx: &Lua, script_data: &ScriptData, events: &[LuaEvent<A>]) -> Vec<ScriptError> {
+        // event order is preserved, but scripts can't rely on any temporal
+        // guarantees when it comes to other scripts callbacks,
+        // at least for now.
+        let globals = ctx.globals();
+        let mut errors = Vec::default();
+        for event in events {
+            // check if this script should handle this event
+            if !event.recipients().is_recipient(script_data) {
+                continue;
+            }
+
+            let f: Function = match globals.raw_get(event.hook_name.clone()) {
+                Ok(f) => f,
+                Err(_) => continue, // not subscribed to this event
+            };
+
+            if let Err(error) = f.call::<_, ()>(event.args.clone()) {
+                errors.push(ScriptError::RuntimeError {
+                    script: script_data.name.to_owned(),
+                    msg: error.to_string(),
+                });
+            }
+        }
+        errors
+    }
+
+    /// Discards the compiled chunks of changed assets, picking up the chunks precompiled by the asset processor,
+    /// and keeps the modules required by scripts in sync with their assets
+    fn sync_assets(
+        mut host: ResMut<Self>,
+        mut events: EventReader<AssetEvent<LuaFile>>,
+        assets: Res<Assets<LuaFile>>,
+    ) {
+        for event in events.read() {
+            if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+                host.chunks.remove(id);
+            }
+
+            if let AssetEvent::Added { id } | AssetEvent::Modified { id } = event {
+                let Some(asset) = assets.get(*id) else {
+                    continue;
+                };
+                if asset.bytecode {
+                    host.chunks.insert(*id, asset.bytes.as_slice().into());
+                }
+                if let Some(searcher) = &host.module_searcher {
+                    searcher.update(*id, asset);
                 }
             }
+        }
+    }
+
+    fn report_errors(world: &WorldPointer, errors: Vec<ScriptError>) {
+        if errors.is_empty() {
+            return;
+        }
+
+        let mut world = world.write();
+        let mut state: CachedScriptState<Self> = world.remove_resource().unwrap();
+
+        let (_, mut error_wrt, _) = state.event_state.get_mut(&mut world);
+
+        for error in errors {
+            error!("{}", error);
+            error_wrt.send(ScriptErrorEvent { error });
+        }
+        world.insert_resource(state);
+    }
+}
+
+/// Creates a function returning the number of parameters of the lua function it is given, or `nil` for variadic functions.
+///
+/// Lua 5.1 and LuaJIT do not expose the number of parameters of functions, `None` is returned for them.
+fn function_arity(lua: &Lua) -> Option<Function<'_>> {
+    #[cfg(any(feature = "lua52", feature = "lua53", feature = "lua54"))]
+    {
+        // safety: `nparams` only reads the debug info of its argument
+        unsafe { lua.create_c_function(nparams) }.ok()
+    }
+    #[cfg(not(any(feature = "lua52", feature = "lua53", feature = "lua54")))]
+    {
+        let _ = lua;
+        None
+    }
+}
+
+/// Pushes the number of parameters of the function passed as the first argument, as reported by `debug.getinfo(f, "u")`
+#[cfg(any(feature = "lua52", feature = "lua53", feature = "lua54"))]
+unsafe extern "C-unwind" fn nparams(state: *mut ffi::lua_State) -> std::os::raw::c_int {
+    let mut ar: ffi::lua_Debug = std::mem::zeroed();
+    // `>` pops the function off the top of the stack
+    ffi::lua_pushvalue(state, 1);
+    if ffi::lua_getinfo(state, c">u".as_ptr(), &mut ar) != 0 && ar.isvararg == 0 {
+        ffi::lua_pushinteger(state, ar.nparams.into());
+    } else {
+        ffi::lua_pushnil(state);
+    }
+    1
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn script_data(sid: u32) -> ScriptData<'static> {
+        ScriptData {
+            sid,
+            entity: Entity::from_raw(0),
+            name: "test.lua",
+        }
+    }
+
+    fn global(ctx: &Mutex<Lua>, name: &str) -> i64 {
+        ctx.lock().unwrap().globals().get(name).unwrap()
+    }
+
+    #[test]
+    fn compiled_chunks_are_shared_until_the_asset_changes() {
+        let mut world = World::new();
+        world.init_resource::<Assets<LuaFile>>();
+        world.init_resource::<Events<AssetEvent<LuaFile>>>();
+        world.insert_resource(LuaScriptHost::<()>::default());
+
+        let id = Handle::<LuaFile>::weak_from_u128(1).id();
+        let mut providers = APIProviders::default();
+        let mut load = |world: &mut World, source: &[u8]| {
+            let mut host = world.resource_mut::<LuaScriptHost<()>>();
+            let ctx = host
+                .load_script_asset(id, source, &script_data(0), &mut providers)
+                .unwrap();
+            global(&ctx, "value")
+        };
+
+        assert_eq!(load(&mut world, b"value = 1"), 1);
+        // the chunk compiled from the first source is reused
+        assert_eq!(load(&mut world, b"value = 2"), 1);
+
+        world.send_event(AssetEvent::<LuaFile>::Modified { id });
+        world.run_system_once(LuaScriptHost::<()>::sync_assets);
+        assert_eq!(load(&mut world, b"value = 2"), 2);
+    }
+
+    #[test]
+    fn only_processed_files_are_loaded_as_bytecode() {
+        let bytecode = compile_bytecode(b"value = 3", "test", true).unwrap();
+
+        let mut world = World::new();
+        world.init_resource::<Events<AssetEvent<LuaFile>>>();
+        world.insert_resource(LuaScriptHost::<()>::default());
+        world.init_resource::<Assets<LuaFile>>();
+        let handle = world.resource_mut::<Assets<LuaFile>>().add(LuaFile {
+            bytes: bytecode.clone(),
+            bytecode: true,
         });
+        world.send_event(AssetEvent::<LuaFile>::Added { id: handle.id() });
+        world.run_system_once(LuaScriptHost::<()>::sync_assets);
+
+        let mut providers = APIProviders::default();
+        let mut host = world.resource_mut::<LuaScriptHost<()>>();
+        let ctx = host
+            .load_script_asset(handle.id(), &bytecode, &script_data(0), &mut providers)
+            .unwrap();
+        assert_eq!(global(&ctx, "value"), 3);
+
+        // files which are not marked as bytecode are never loaded as such
+        let other = Handle::<LuaFile>::weak_from_u128(2).id();
+        assert!(host
+            .load_script_asset(other, &bytecode, &script_data(1), &mut providers)
+            .is_err());
+    }
+
+    #[cfg(any(feature = "lua52", feature = "lua53", feature = "lua54"))]
+    #[test]
+    fn function_arity_counts_parameters() {
+        let lua = Lua::new();
+        lua.load("function two(a, b) end function variadic(a, ...) end")
+            .exec()
+            .unwrap();
+        let arity = function_arity(&lua).unwrap();
+        let arity_of = |name: &str| -> Option<usize> {
+            arity
+                .call(lua.globals().get::<_, Function>(name).unwrap())
+                .unwrap()
+        };
+
+        assert_eq!(arity_of("two"), Some(2));
+        assert_eq!(arity_of("variadic"), None);
     }
 }
@@ -0,0 +1,300 @@
+use std::fmt::Write;
+
+use bevy_mod_scripting_core::schema::HookSchema;
+use serde_json::Value;
+use tealr::TypeWalker;
+
+/// Renders LuaLS (sumneko) annotations for every type and global instance processed by the type walker,
+/// the result is a `---@meta` file which the language server reads as definitions only.
+///
+/// The annotations are generated from the walker's serialized form, the same data `tealr_doc_gen` consumes.
+/// Meta methods have no equivalent annotation and are left out.
+pub fn gen_luals_annotations(tw: &TypeWalker) -> Result<String, serde_json::Error> {
+    Ok(render(&serde_json::to_value(tw)?))
+}
+
+/// Renders LuaLS annotations for the hooks scripts can define, each one as a documented global function
+/// so that the language server shows the expected parameters of hooks defined by scripts.
+pub fn gen_luals_hooks(hooks: &[HookSchema]) -> String {
+    let mut out = String::new();
+    for hook in hooks {
+        let _ = writeln!(out);
+        write_doc(&mut out, hook.docs.as_deref().unwrap_or_default());
+        for arg in &hook.args {
+            let _ = writeln!(out, "---@param {} {}", arg.name, arg.ty);
+        }
+        let args = hook
+            .args
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "function {}({}) end", hook.name, args.join(", "));
+    }
+    out
+}
+
+fn render(walker: &Value) -> String {
+    let mut out = String::from("---@meta\n");
+
+    for ty in array(walker, "given_types") {
+        if let Some(record) = ty.get("Record") {
+            render_record(&mut out, record);
+        } else if let Some(enumeration) = ty.get("Enum") {
+            render_enum(&mut out, enumeration);
+        }
+    }
+
+    for instance in array(walker, "global_instances_off") {
+        let Some(name) = instance.get("name").and_then(name_of) else {
+            continue;
+        };
+        let _ = writeln!(out);
+        write_doc(&mut out, str_of(instance, "doc"));
+        let _ = writeln!(
+            out,
+            "---@type {}\n{name} = nil",
+            instance.get("ty").map(lua_type).unwrap_or_else(any)
+        );
+    }
+
+    out
+}
+
+fn render_record(out: &mut String, record: &Value) {
+    let Some(class) = record.get("ty").map(lua_type) else {
+        return;
+    };
+    let _ = writeln!(out);
+    write_doc(out, str_of(record, "type_doc"));
+    let _ = writeln!(out, "---@class {class}");
+    for field in array(record, "fields")
+        .chain(array(record, "static_fields"))
+        .filter_map(|f| Some((f.get("name").and_then(name_of)?, f.get("ty")?)))
+    {
+        let (name, ty) = field;
+        let _ = write!(out, "---@field {name} {}", lua_type(ty));
+        let doc = doc_of(record, &name);
+        if !doc.is_empty() {
+            let _ = write!(out, " {}", doc.replace('\n', " "));
+        }
+        let _ = writeln!(out);
+    }
+    let _ = writeln!(out, "{class} = {{}}");
+
+    let methods = ["methods", "mut_methods"];
+    let functions = ["functions", "mut_functions"];
+    for (kind, is_method) in methods
+        .iter()
+        .map(|k| (k, true))
+        .chain(functions.iter().map(|k| (k, false)))
+    {
+        for function in array(record, kind) {
+            if function.get("is_meta_method").and_then(Value::as_bool) == Some(true) {
+                continue;
+            }
+            let Some(name) = function.get("name").and_then(name_of) else {
+                continue;
+            };
+            let signature = function.get("signature").and_then(|s| s.get("Function"));
+            let _ = writeln!(out);
+            write_doc(out, doc_of(record, &name));
+            render_function(out, &class, &name, signature, is_method);
+        }
+    }
+}
+
+fn render_function(
+    out: &mut String,
+    class: &str,
+    name: &str,
+    signature: Option<&Value>,
+    is_method: bool,
+) {
+    let mut params = signature.map(|s| array(s, "params")).into_iter().flatten();
+    // the receiver is part of the signature of methods
+    if is_method {
+        params.next();
+    }
+
+    let mut names = Vec::default();
+    for (i, param) in params.enumerate() {
+        let ty = param.get("ty");
+        let variadic = ty.and_then(|t| t.get("Variadic"));
+        let param_name = match variadic {
+            Some(_) => "...".to_owned(),
+            None => param
+                .get("param_name")
+                .and_then(name_of)
+                .unwrap_or_else(|| format!("arg{}", i + 1)),
+        };
+        let ty = variadic.or(ty).map(lua_type).unwrap_or_else(any);
+        let _ = writeln!(out, "---@param {param_name} {ty}");
+        names.push(param_name);
+    }
+
+    for ret in signature.map(|s| array(s, "returns")).into_iter().flatten() {
+        let _ = writeln!(out, "---@return {}", lua_type(ret));
+    }
+
+    let separator = if is_method { ":" } else { "." };
+    let _ = writeln!(
+        out,
+        "function {class}{separator}{name}({}) end",
+        names.join(", ")
+    );
+}
+
+fn render_enum(out: &mut String, enumeration: &Value) {
+    let Some(name) = enumeration
+        .get("name")
+        .and_then(name_of)
+        .or_else(|| enumeration.get("ty").map(lua_type))
+    else {
+        return;
+    };
+
+    let _ = writeln!(out);
+    write_doc(out, str_of(enumeration, "type_doc"));
+    let _ = writeln!(out, "---@alias {name}");
+    for variant in array(enumeration, "variants").filter_map(name_of) {
+        let _ = writeln!(out, "---| \"{variant}\"");
+    }
+}
+
+/// Converts a serialized `tealr::Type` to a LuaLS type expression
+pub(crate) fn lua_type(ty: &Value) -> String {
+    if let Some(single) = ty.get("Single") {
+        return single.get("name").and_then(name_of).unwrap_or_else(any);
+    }
+    if let Some(function) = ty.get("Function") {
+        let params = array(function, "params")
+            .enumerate()
+            .map(|(i, p)| {
+                let name = p
+                    .get("param_name")
+                    .and_then(name_of)
+                    .unwrap_or_else(|| format!("arg{}", i + 1));
+                let ty = p.get("ty").map(lua_type).unwrap_or_else(any);
+                format!("{name}: {ty}")
+            })
+            .collect::<Vec<_>>();
+        let returns = array(function, "returns").map(lua_type).collect::<Vec<_>>();
+        return match returns.is_empty() {
+            true => format!("fun({})", params.join(", ")),
+            false => format!("fun({}): {}", params.join(", "), returns.join(", ")),
+        };
+    }
+    if let Some(map) = ty.get("Map") {
+        let key = map.get("key").map(lua_type).unwrap_or_else(any);
+        let value = map.get("value").map(lua_type).unwrap_or_else(any);
+        return format!("table<{key}, {value}>");
+    }
+    if let Some(variants) = ty.get("Or").and_then(Value::as_array) {
+        return variants.iter().map(lua_type).collect::<Vec<_>>().join("|");
+    }
+    if let Some(inner) = ty.get("Array") {
+        let inner = lua_type(inner);
+        return match inner.contains(['|', ' ']) {
+            true => format!("({inner})[]"),
+            false => format!("{inner}[]"),
+        };
+    }
+    if let Some(types) = ty.get("Tuple").and_then(Value::as_array) {
+        return types.iter().map(lua_type).collect::<Vec<_>>().join(", ");
+    }
+    if let Some(inner) = ty.get("Variadic") {
+        return format!("{}...", lua_type(inner));
+    }
+    any()
+}
+
+fn any() -> String {
+    "any".to_owned()
+}
+
+/// Names are either serialized as strings or as their raw bytes
+pub(crate) fn name_of(name: &Value) -> Option<String> {
+    match name {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(bytes) => bytes
+            .iter()
+            .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect::<Option<Vec<_>>>()
+            .and_then(|bytes| String::from_utf8(bytes).ok()),
+        _ => None,
+    }
+}
+
+pub(crate) fn array<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+/// The documentation of a member of a record
+pub(crate) fn doc_of<'a>(record: &'a Value, name: &str) -> &'a str {
+    record
+        .get("documentation")
+        .and_then(|d| d.get(name))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+pub(crate) fn str_of<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+fn write_doc(out: &mut String, doc: &str) {
+    for line in doc.lines() {
+        let _ = writeln!(out, "---{line}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn single(name: &str) -> Value {
+        json!({ "Single": { "name": name, "kind": "External" } })
+    }
+
+    #[test]
+    fn records_and_globals_are_annotated() {
+        let walker = json!({
+            "given_types": [{
+                "Record": {
+                    "ty": single("Vec2"),
+                    "type_doc": "A 2-dimensional vector.",
+                    "fields": [{ "name": "x", "ty": single("number") }],
+                    "methods": [{
+                        "name": "dot",
+                        "is_meta_method": false,
+                        "signature": { "Function": {
+                            "params": [
+                                { "param_name": null, "ty": single("Vec2") },
+                                { "param_name": "other", "ty": single("Vec2") }
+                            ],
+                            "returns": [single("number")]
+                        }}
+                    }],
+                    "documentation": { "dot": "Computes the dot product." }
+                }
+            }],
+            "global_instances_off": [{ "name": "world", "ty": single("World"), "doc": "The world" }]
+        });
+
+        let annotations = render(&walker);
+        assert!(annotations.starts_with("---@meta"));
+        assert!(
+            annotations.contains("---A 2-dimensional vector.\n---@class Vec2\n---@field x number")
+        );
+        assert!(annotations.contains(
+            "---Computes the dot product.\n---@param other Vec2\n---@return number\nfunction Vec2:dot(other) end"
+        ));
+        assert!(annotations.contains("---The world\n---@type World\nworld = nil"));
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    asset::{AssetPath, AssetServer, LoadState},
+    prelude::*,
+};
+use bevy_mod_scripting_core::prelude::AssetModules;
+use tealr::mlu::mlua::{prelude::*, ChunkMode, IntoLuaMulti};
+
+use crate::assets::LuaFile;
+
+/// The modules required by a single script, shared with the searcher installed into its lua state
+pub type LuaDependencies = Arc<Mutex<Vec<Handle<LuaFile>>>>;
+
+/// A lua package searcher serving `LuaFile` assets, installed by `LuaScriptHost` into the `package.searchers` of every script.
+///
+/// `require("utils.math")` resolves to the `scripts/utils/math.lua` asset relative to the `base_path`,
+/// with the `teal` feature enabled `scripts/utils/math.tl` is searched as well.
+/// Modules are requested from the asset server the first time they are required,
+/// scripts which are loaded before their modules are available fail to load and are reloaded once the modules arrive.
+/// Each lua state caches the modules it required in `package.loaded` as usual.
+#[derive(Clone)]
+pub struct LuaAssetSearcher {
+    base_path: PathBuf,
+    /// the source of each module along with the mode it is loaded in, see [`LuaFile::bytecode`]
+    modules: AssetModules<LuaFile, (Arc<[u8]>, ChunkMode)>,
+}
+
+impl LuaAssetSearcher {
+    pub fn new(asset_server: AssetServer) -> Self {
+        Self {
+            base_path: PathBuf::from("scripts"),
+            modules: AssetModules::new(asset_server),
+        }
+    }
+
+    /// Sets the path module names are relative to, `scripts` by default
+    pub fn with_base_path(mut self, base_path: impl Into<PathBuf>) -> Self {
+        self.base_path = base_path.into();
+        self
+    }
+
+    /// The asset paths searched for the given module name, in order
+    pub fn asset_paths(&self, name: &str) -> Vec<AssetPath<'static>> {
+        module_asset_paths(&self.base_path, name)
+    }
+
+    /// Updates the source of the module stored in the given asset, does nothing if no script requires it
+    pub fn update(&self, id: AssetId<LuaFile>, file: &LuaFile) {
+        let mode = if file.bytecode {
+            ChunkMode::Binary
+        } else {
+            ChunkMode::Text
+        };
+        self.modules.update(id, (file.bytes.as_slice().into(), mode));
+    }
+
+    /// Installs the searcher into the given lua state, right after the `package.preload` searcher.
+    ///
+    /// The handles of the modules required by the state are recorded in `dependencies`,
+    /// including modules which are not loaded yet.
+    pub fn install(&self, lua: &Lua, dependencies: LuaDependencies) -> LuaResult<()> {
+        let searcher = self.clone();
+        let search = lua.create_function(move |lua, name: String| {
+            let mut not_found = String::new();
+
+            for path in searcher.asset_paths(&name) {
+                // not every candidate exists
+                let Some(handle) = searcher.modules.load(&path) else {
+                    continue;
+                };
+                if searcher.modules.asset_server().load_state(handle.id()) == LoadState::Failed {
+                    continue;
+                }
+
+                {
+                    let mut dependencies = dependencies.lock().unwrap();
+                    if !dependencies.contains(&handle) {
+                        dependencies.push(handle);
+                    }
+                }
+
+                match searcher.modules.source(&path) {
+                    Some((source, mode)) => {
+                        let loader = lua
+                            .load(&*source)
+                            .set_name(path.to_string())
+                            .set_mode(mode)
+                            .into_function()?;
+                        return (loader, path.to_string()).into_lua_multi(lua);
+                    }
+                    None => not_found.push_str(&format!("\n\tasset '{path}' is not loaded yet")),
+                }
+            }
+
+            not_found.into_lua_multi(lua)
+        })?;
+
+        let package: LuaTable = lua.globals().get("package")?;
+        // `package.loaders` in lua 5.1
+        let searchers: LuaTable = package
+            .get("searchers")
+            .or_else(|_| package.get("loaders"))?;
+        searchers.raw_insert(2, search)
+    }
+}
+
+/// Converts a module name such as `utils.math` to the asset paths it could be stored in
+fn module_asset_paths(base_path: &Path, name: &str) -> Vec<AssetPath<'static>> {
+    let path = base_path.join(name.replace('.', "/"));
+
+    #[cfg(feature = "teal")]
+    let extensions = ["lua", "tl"];
+    #[cfg(not(feature = "teal"))]
+    let extensions = ["lua"];
+
+    extensions
+        .into_iter()
+        .map(|ext| AssetPath::from(path.with_extension(ext)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn module_names_map_to_asset_paths() {
+        let paths = module_asset_paths(Path::new("scripts"), "utils.math");
+        assert_eq!(
+            paths[0],
+            AssetPath::from(PathBuf::from("scripts/utils/math.lua"))
+        );
+    }
+
+    #[test]
+    fn required_modules_are_served_from_assets() {
+        let dir = std::env::temp_dir().join("bevy_mod_scripting_lua_searcher");
+        std::fs::create_dir_all(dir.join("scripts/utils")).unwrap();
+        std::fs::write(dir.join("scripts/utils/math.lua"), "").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin {
+                file_path: dir.to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+        ))
+        .init_asset::<LuaFile>();
+
+        let searcher = LuaAssetSearcher::new(app.world.resource::<AssetServer>().clone());
+        let lua = Lua::new();
+        let dependencies = LuaDependencies::default();
+        searcher.install(&lua, dependencies.clone()).unwrap();
+
+        // missing modules are not requested from the asset server
+        assert!(lua.load("require('utils.missing')").exec().is_err());
+        assert!(dependencies.lock().unwrap().is_empty());
+
+        // modules which are not loaded yet are recorded so the script can be reloaded once they are
+        let error = lua.load("require('utils.math')").exec().unwrap_err();
+        assert!(error.to_string().contains("is not loaded yet"));
+        let handle = dependencies.lock().unwrap()[0].clone();
+
+        searcher.update(
+            handle.id(),
+            &LuaFile {
+                bytes: b"return { value = 1 }".to_vec(),
+                bytecode: false,
+            },
+        );
+        let value: i64 = lua.load("return require('utils.math').value").eval().unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(dependencies.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,375 @@
+use std::fmt::Write;
+
+use bevy_mod_scripting_core::{
+    docs::{HtmlPage, HtmlSection},
+    schema::HookSchema,
+};
+use serde_json::Value;
+use tealr::TypeWalker;
+
+use crate::luals::{array, doc_of, lua_type, name_of, str_of};
+
+/// A documented field, function or enum variant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LuaMemberDoc {
+    /// The member as written in scripts, i.e. `Vec2:dot(other: Vec2): number` or `x: number`
+    pub signature: String,
+    pub docs: String,
+}
+
+/// Documentation of a single type exposed to scripts
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LuaTypeDoc {
+    pub name: String,
+    pub docs: String,
+    pub fields: Vec<LuaMemberDoc>,
+    pub functions: Vec<LuaMemberDoc>,
+    pub meta_methods: Vec<LuaMemberDoc>,
+}
+
+/// Documentation of a global instance, such as `world`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LuaGlobalDoc {
+    pub name: String,
+    pub ty: String,
+    pub docs: String,
+}
+
+/// The API reference of every type and global instance processed by a `TypeWalker`,
+/// rendered without any external tools into a Markdown or a self-contained HTML page.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LuaApiReference {
+    pub types: Vec<LuaTypeDoc>,
+    pub globals: Vec<LuaGlobalDoc>,
+    /// The functions scripts can define to be called by the host, see `ScriptHooks`
+    pub hooks: Vec<LuaMemberDoc>,
+}
+
+impl LuaApiReference {
+    pub fn from_walker(tw: &TypeWalker) -> Result<Self, serde_json::Error> {
+        Ok(Self::from_value(&serde_json::to_value(tw)?))
+    }
+
+    /// Reads the serialized form of a `TypeWalker`, types are sorted by name
+    fn from_value(walker: &Value) -> Self {
+        let mut types = array(walker, "given_types")
+            .filter_map(|ty| match (ty.get("Record"), ty.get("Enum")) {
+                (Some(record), _) => record_doc(record),
+                (_, Some(enumeration)) => enum_doc(enumeration),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let globals = array(walker, "global_instances_off")
+            .filter_map(|instance| {
+                Some(LuaGlobalDoc {
+                    name: instance.get("name").and_then(name_of)?,
+                    ty: instance
+                        .get("ty")
+                        .map(lua_type)
+                        .unwrap_or_else(|| "any".to_owned()),
+                    docs: str_of(instance, "doc").to_owned(),
+                })
+            })
+            .collect();
+
+        Self {
+            types,
+            globals,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the hooks declared for the host to the reference
+    pub fn with_hooks(mut self, hooks: &[HookSchema]) -> Self {
+        self.hooks.extend(hooks.iter().map(|hook| LuaMemberDoc {
+            signature: hook.signature(),
+            docs: hook.docs.clone().unwrap_or_default(),
+        }));
+        self
+    }
+
+    /// Renders the reference as a single Markdown page
+    pub fn to_markdown(&self, title: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {title}\n");
+
+        if !self.globals.is_empty() {
+            let _ = writeln!(out, "## Globals\n");
+            for global in &self.globals {
+                let _ = writeln!(out, "```lua\n{}: {}\n```\n", global.name, global.ty);
+                write_markdown_docs(&mut out, &global.docs);
+            }
+        }
+
+        if !self.hooks.is_empty() {
+            let _ = writeln!(out, "## Hooks\n");
+            for hook in &self.hooks {
+                let _ = writeln!(out, "```lua\nfunction {}\n```\n", hook.signature);
+                write_markdown_docs(&mut out, &hook.docs);
+            }
+        }
+
+        if !self.types.is_empty() {
+            let _ = writeln!(out, "## Types\n");
+        }
+        for ty in &self.types {
+            let _ = writeln!(out, "### {}\n", ty.name);
+            write_markdown_docs(&mut out, &ty.docs);
+            for (heading, members) in ty.sections() {
+                let _ = writeln!(out, "#### {heading}\n");
+                for member in members {
+                    let _ = writeln!(out, "```lua\n{}\n```\n", member.signature);
+                    write_markdown_docs(&mut out, &member.docs);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Renders the reference as a single self-contained HTML page
+    pub fn to_html(&self, title: &str) -> String {
+        let mut page = HtmlPage::new(title);
+
+        if !self.globals.is_empty() {
+            let mut section = HtmlSection::new("globals", "Globals");
+            for global in &self.globals {
+                section
+                    .code(&format!("{}: {}", global.name, global.ty))
+                    .docs(global.docs.lines());
+            }
+            page.section(section);
+        }
+
+        if !self.hooks.is_empty() {
+            let mut section = HtmlSection::new("hooks", "Hooks");
+            for hook in &self.hooks {
+                section
+                    .code(&format!("function {}", hook.signature))
+                    .docs(hook.docs.lines());
+            }
+            page.section(section);
+        }
+
+        for ty in &self.types {
+            let mut section = HtmlSection::new(&format!("type.{}", ty.name), &ty.name);
+            section.docs(ty.docs.lines());
+            for (heading, members) in ty.sections() {
+                section.subheading(heading);
+                for member in members {
+                    section.code(&member.signature).docs(member.docs.lines());
+                }
+            }
+            page.section(section);
+        }
+
+        page.render()
+    }
+}
+
+impl LuaTypeDoc {
+    /// The non-empty groups of members along with their headings
+    fn sections(&self) -> impl Iterator<Item = (&'static str, &Vec<LuaMemberDoc>)> {
+        [
+            ("Fields", &self.fields),
+            ("Functions", &self.functions),
+            ("Metamethods", &self.meta_methods),
+        ]
+        .into_iter()
+        .filter(|(_, members)| !members.is_empty())
+    }
+}
+
+fn record_doc(record: &Value) -> Option<LuaTypeDoc> {
+    let name = record.get("ty").map(lua_type)?;
+    let mut doc = LuaTypeDoc {
+        docs: str_of(record, "type_doc").to_owned(),
+        ..Default::default()
+    };
+
+    for field in array(record, "fields").chain(array(record, "static_fields")) {
+        let Some(field_name) = field.get("name").and_then(name_of) else {
+            continue;
+        };
+        let ty = field
+            .get("ty")
+            .map(lua_type)
+            .unwrap_or_else(|| "any".to_owned());
+        doc.fields.push(LuaMemberDoc {
+            signature: format!("{field_name}: {ty}"),
+            docs: doc_of(record, &field_name).to_owned(),
+        });
+    }
+
+    let kinds = [
+        ("methods", true, false),
+        ("mut_methods", true, false),
+        ("functions", false, false),
+        ("mut_functions", false, false),
+        ("meta_method", true, true),
+        ("meta_method_mut", true, true),
+        ("meta_function", false, true),
+        ("meta_function_mut", false, true),
+    ];
+    for (kind, is_method, is_meta) in kinds {
+        for function in array(record, kind) {
+            let Some(function_name) = function.get("name").and_then(name_of) else {
+                continue;
+            };
+            let signature = function.get("signature").and_then(|s| s.get("Function"));
+            let member = LuaMemberDoc {
+                signature: function_signature(&name, &function_name, signature, is_method),
+                docs: doc_of(record, &function_name).to_owned(),
+            };
+
+            let is_meta =
+                is_meta || function.get("is_meta_method").and_then(Value::as_bool) == Some(true);
+            match is_meta {
+                true => doc.meta_methods.push(member),
+                false => doc.functions.push(member),
+            }
+        }
+    }
+
+    doc.name = name;
+    Some(doc)
+}
+
+fn enum_doc(enumeration: &Value) -> Option<LuaTypeDoc> {
+    let name = enumeration
+        .get("name")
+        .and_then(name_of)
+        .or_else(|| enumeration.get("ty").map(lua_type))?;
+
+    Some(LuaTypeDoc {
+        name,
+        docs: str_of(enumeration, "type_doc").to_owned(),
+        fields: array(enumeration, "variants")
+            .filter_map(name_of)
+            .map(|variant| LuaMemberDoc {
+                signature: format!("\"{variant}\""),
+                docs: String::default(),
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+/// i.e. `Vec2:dot(other: Vec2): number`, the receiver of methods is left out of the parameters
+fn function_signature(
+    class: &str,
+    name: &str,
+    signature: Option<&Value>,
+    is_method: bool,
+) -> String {
+    let params = signature
+        .map(|s| array(s, "params"))
+        .into_iter()
+        .flatten()
+        .skip(usize::from(is_method))
+        .enumerate()
+        .map(|(i, param)| {
+            let ty = param
+                .get("ty")
+                .map(lua_type)
+                .unwrap_or_else(|| "any".to_owned());
+            let param_name = param
+                .get("param_name")
+                .and_then(name_of)
+                .unwrap_or_else(|| format!("arg{}", i + 1));
+            format!("{param_name}: {ty}")
+        })
+        .collect::<Vec<_>>();
+    let returns = signature
+        .map(|s| array(s, "returns"))
+        .into_iter()
+        .flatten()
+        .map(lua_type)
+        .collect::<Vec<_>>();
+
+    let separator = if is_method { ":" } else { "." };
+    let mut out = format!("{class}{separator}{name}({})", params.join(", "));
+    if !returns.is_empty() {
+        let _ = write!(out, ": {}", returns.join(", "));
+    }
+    out
+}
+
+fn write_markdown_docs(out: &mut String, docs: &str) {
+    if !docs.is_empty() {
+        let _ = writeln!(out, "{docs}\n");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn single(name: &str) -> Value {
+        json!({ "Single": { "name": name, "kind": "External" } })
+    }
+
+    #[test]
+    fn types_are_rendered_with_their_members() {
+        let walker = json!({
+            "given_types": [{
+                "Record": {
+                    "ty": single("Vec2"),
+                    "type_doc": "A 2-dimensional vector.",
+                    "fields": [{ "name": "x", "ty": single("number") }],
+                    "methods": [{
+                        "name": "dot",
+                        "is_meta_method": false,
+                        "signature": { "Function": {
+                            "params": [
+                                { "param_name": null, "ty": single("Vec2") },
+                                { "param_name": "other", "ty": single("Vec2") }
+                            ],
+                            "returns": [single("number")]
+                        }}
+                    }],
+                    "meta_method": [{
+                        "name": "__tostring",
+                        "is_meta_method": true,
+                        "signature": { "Function": {
+                            "params": [{ "param_name": null, "ty": single("Vec2") }],
+                            "returns": [single("string")]
+                        }}
+                    }],
+                    "documentation": { "dot": "Computes the <dot> product." }
+                }
+            }],
+            "global_instances_off": [{ "name": "world", "ty": single("World"), "doc": "The world" }]
+        });
+
+        let hooks = [HookSchema::new("on_update")
+            .with_arg("dt", "number")
+            .with_docs("Called every frame")];
+
+        let reference = LuaApiReference::from_value(&walker).with_hooks(&hooks);
+        assert_eq!(
+            reference.types[0].functions[0].signature,
+            "Vec2:dot(other: Vec2): number"
+        );
+        assert_eq!(
+            reference.types[0].meta_methods[0].signature,
+            "Vec2:__tostring(): string"
+        );
+
+        let markdown = reference.to_markdown("API");
+        assert!(markdown.contains("### Vec2\n\nA 2-dimensional vector."));
+        assert!(markdown.contains("#### Fields\n\n```lua\nx: number\n```"));
+        assert!(markdown.contains("world: World"));
+        assert!(markdown.contains(
+            "## Hooks\n\n```lua\nfunction on_update(dt: number)\n```\n\nCalled every frame"
+        ));
+
+        let html = reference.to_html("API");
+        assert!(html.contains("id=\"type.Vec2\""));
+        assert!(html.contains("Computes the &lt;dot&gt; product."));
+    }
+}
@@ -0,0 +1,160 @@
+use std::{fmt, path::Path};
+
+use tealr::mlu::mlua::{prelude::*, Function, Table};
+
+/// The teal compiler, vendored from <https://github.com/teal-language/tl> (see `vendor/README.md` for its version)
+const TL: &str = include_str!("../vendor/tl.lua");
+
+/// Compiles a single teal file, returning the generated lua code along with any errors reported
+const COMPILE: &str = r#"
+local tl, global_env_def = ...
+return function(source, name)
+    local env = tl.init_env(false, nil, nil, global_env_def and { global_env_def } or nil)
+    local output, result = tl.gen(source, env)
+
+    local errors = {}
+    for _, kind in ipairs({ "syntax_errors", "type_errors" }) do
+        for _, err in ipairs(result and result[kind] or {}) do
+            table.insert(errors, {
+                filename = err.filename or name,
+                line = err.y,
+                column = err.x,
+                msg = err.msg,
+            })
+        end
+    end
+    return output, errors
+end
+"#;
+
+/// A single error reported by the teal compiler
+#[derive(Debug, Clone)]
+pub struct TealDiagnostic {
+    pub filename: String,
+    pub line: usize,
+    pub column: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for TealDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.filename, self.line, self.column, self.msg
+        )
+    }
+}
+
+/// The errors which prevented a teal file from compiling
+#[derive(Debug)]
+pub enum TealError {
+    /// The file has syntax or type errors
+    Diagnostics(Vec<TealDiagnostic>),
+    /// The compiler itself failed
+    Lua(LuaError),
+}
+
+impl fmt::Display for TealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TealError::Diagnostics(diagnostics) => {
+                write!(f, "Teal file has {} error(s):", diagnostics.len())?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n\t{diagnostic}")?;
+                }
+                Ok(())
+            }
+            TealError::Lua(e) => write!(f, "Teal compiler failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for TealError {}
+
+impl From<LuaError> for TealError {
+    fn from(e: LuaError) -> Self {
+        TealError::Lua(e)
+    }
+}
+
+/// The teal compiler (`tl.lua`), embedded into the binary and loaded as a module into its own lua state.
+///
+/// Teal modules and declaration files required by the compiled files (including the global environment definition)
+/// are searched for in the given directory, the same way `tl` searches the directory it is run from.
+pub struct TealCompiler {
+    lua: Lua,
+}
+
+impl TealCompiler {
+    /// Sets up the compiler, `global_env_def` is the module declaring the global environment of scripts (i.e. `types/types`) if there is one
+    pub fn new(search_path: &Path, global_env_def: Option<&str>) -> LuaResult<Self> {
+        let lua = Lua::new();
+
+        let package: Table = lua.globals().get("package")?;
+        package.set(
+            "path",
+            search_path.join("?.lua").to_string_lossy().into_owned(),
+        )?;
+
+        let tl: Table = lua.load(TL).set_name("tl").eval()?;
+
+        let compile: Function = lua
+            .load(COMPILE)
+            .set_name("compile teal")
+            .call((tl, global_env_def))?;
+        lua.set_named_registry_value("compile", compile)?;
+
+        Ok(Self { lua })
+    }
+
+    /// Type checks the given teal source and compiles it into lua code
+    pub fn compile(&self, source: &str, name: &str) -> Result<String, TealError> {
+        let compile: Function = self.lua.named_registry_value("compile")?;
+        let (output, errors): (Option<String>, Vec<Table>) = compile.call((source, name))?;
+
+        let diagnostics = errors
+            .into_iter()
+            .map(|e| {
+                Ok(TealDiagnostic {
+                    filename: e.get("filename")?,
+                    line: e.get("line")?,
+                    column: e.get("column")?,
+                    msg: e.get("msg")?,
+                })
+            })
+            .collect::<LuaResult<Vec<_>>>()?;
+
+        match output {
+            Some(output) if diagnostics.is_empty() => Ok(output),
+            _ => Err(TealError::Diagnostics(diagnostics)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn teal_is_compiled_into_lua() {
+        let compiler = TealCompiler::new(Path::new("."), None).unwrap();
+        let lua = compiler
+            .compile("local x: integer = 1\nreturn x", "test.tl")
+            .unwrap();
+        assert_eq!(Lua::new().load(&lua).eval::<i64>().unwrap(), 1);
+    }
+
+    #[test]
+    fn type_errors_are_reported_with_their_location() {
+        let compiler = TealCompiler::new(Path::new("."), None).unwrap();
+        let Err(TealError::Diagnostics(diagnostics)) =
+            compiler.compile("local x: integer = 1\nlocal y: string = x", "test.tl")
+        else {
+            panic!("expected a type error");
+        };
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 2);
+    }
+}
@@ -2,7 +2,8 @@ use std::collections::HashMap;
 
 use arg::Arg;
 use bevy_mod_scripting_common::{input::*, utils::doc_attribute_to_string_lit};
-use syn::{parse_macro_input, DeriveInput, Variant};
+use convert_case::{Case, Casing};
+use syn::{parse_macro_input, DeriveInput, Index, Member, Variant};
 use syn::{
     parse_quote, spanned::Spanned, AttrStyle, Attribute, Field, Meta, Path, Token, TraitItemFn,
 };
@@ -43,19 +44,43 @@ fn convert_field_to_lua_accessor(
         .unwrap_or_else(|| format_ident!("_{}", idx));
     let field_type = &field.ty;
     let attrs = &field.attrs;
-    let mut setter_args: Option<proc_macro2::TokenStream> = None;
-    if let Some(attr) = attrs.iter().find(|attr| attr.meta.path().is_ident("lua")) {
+    let setter_arg_attrs = field_argument_attribute(field)?;
+    let trait_item_method: TraitItemFn = if is_setter {
+        parse_quote! {
+            #[lua(kind="FieldSetterMethod", raw)]
+            #(#attrs)*
+            fn #field_name (&mut self, lua: &Lua, #setter_arg_attrs other: #field_type);
+        }
+    } else {
+        parse_quote! {
+            #[lua(kind="FieldGetterMethod", raw)]
+            #(#attrs)*
+            fn #field_name (&self, lua: &Lua) -> #field_type;
+        }
+    };
+
+    Ok(trait_item_method)
+}
+
+/// Converts the `lua(output(..))` meta of a field into a `proxy(..)` attribute for arguments taking the value of the field
+fn field_argument_attribute(field: &Field) -> darling::Result<Option<Attribute>> {
+    let mut argument_meta: Option<proc_macro2::TokenStream> = None;
+    if let Some(attr) = field
+        .attrs
+        .iter()
+        .find(|attr| attr.meta.path().is_ident("lua"))
+    {
         attr.parse_nested_meta(|nested| {
             if nested.path.is_ident("output") {
                 nested.parse_nested_meta(|nested| {
-                    setter_args = Some(nested.input.parse()?);
+                    argument_meta = Some(nested.input.parse()?);
                     Ok(())
                 })?
             }
             Ok(())
         })?;
     }
-    let setter_arg_attrs = setter_args.map(|tokens| Attribute {
+    Ok(argument_meta.map(|tokens| Attribute {
         pound_token: Token![#](field.span()),
         style: AttrStyle::Outer,
         bracket_token: Default::default(),
@@ -64,22 +89,96 @@ fn convert_field_to_lua_accessor(
             delimiter: syn::MacroDelimiter::Paren(Default::default()),
             tokens,
         }),
-    });
-    let trait_item_method: TraitItemFn = if is_setter {
-        parse_quote! {
-            #[lua(kind="FieldSetterMethod", raw)]
-            #(#attrs)*
-            fn #field_name (&mut self, lua: &Lua, #setter_arg_attrs other: #field_type);
+    }))
+}
+
+/// Converts the variants of an enum into TraitItemFn representations of:
+/// - a static constructor for each variant named after it, i.e. `MyEnum.Variant(field_a, field_b)`
+/// - a `variant_name` method returning the name of the variant of the value
+/// - a getter method for each field of each variant named `<variant>_<field>` in snake case, returning nil for values of other variants
+///
+/// Variants with skipped fields cannot be constructed, and getters return copies of the fields
+fn convert_variants_to_lua_functions(
+    proxied_type_path: &Path,
+    variants: &[Variant],
+) -> darling::Result<Vec<TraitItemFn>> {
+    let mut out: Vec<TraitItemFn> = Vec::default();
+
+    for variant in variants {
+        let variant_ident = &variant.ident;
+        let variant_name = variant_ident.to_string().to_case(Case::Snake);
+        let mut is_constructible = true;
+        let mut constructor_args = Vec::default();
+        let mut constructor_members = Vec::default();
+
+        for (idx, field) in variant.fields.iter().enumerate() {
+            if FunctionAttributes::from_attributes(&field.attrs)?
+                .skip
+                .is_present()
+            {
+                is_constructible = false;
+                continue;
+            }
+
+            let (member, member_name) = match &field.ident {
+                Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+                None => (Member::Unnamed(Index::from(idx)), idx.to_string()),
+            };
+            let arg_name = field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("_{}", idx));
+            let field_type = &field.ty;
+            let field_attrs = &field.attrs;
+            let arg_attrs = field_argument_attribute(field)?;
+            let getter_name = format_ident!("{variant_name}_{member_name}", span = field.span());
+
+            out.push(parse_quote! {
+                #[lua(kind="Method")]
+                #(#field_attrs)*
+                fn #getter_name(&self) -> Option<#field_type> {
+                    match self {
+                        #proxied_type_path::#variant_ident { #member: value, .. } => Some(value.clone()),
+                        _ => None,
+                    }
+                }
+            });
+
+            constructor_args.push(quote_spanned!(field.span()=> #arg_attrs #arg_name: #field_type));
+            constructor_members.push(quote_spanned!(field.span()=> #member: #arg_name));
         }
-    } else {
-        parse_quote! {
-            #[lua(kind="FieldGetterMethod", raw)]
-            #(#attrs)*
-            fn #field_name (&self, lua: &Lua) -> #field_type;
+
+        if is_constructible {
+            let docs = variant.attrs.iter().filter(|a| a.path().is_ident("doc"));
+            out.push(parse_quote! {
+                #[lua(kind="Function", output(proxy))]
+                #(#docs)*
+                fn #variant_ident(#(#constructor_args),*) -> #proxied_type_path {
+                    #proxied_type_path::#variant_ident { #(#constructor_members),* }
+                }
+            });
         }
-    };
+    }
 
-    Ok(trait_item_method)
+    if !variants.is_empty() {
+        let variant_arms = variants.iter().map(|variant| {
+            let variant_ident = &variant.ident;
+            let variant_name = variant_ident.to_string();
+            quote_spanned!(variant.span()=> #proxied_type_path::#variant_ident { .. } => #variant_name,)
+        });
+        out.push(parse_quote! {
+            #[lua(kind="Method")]
+            /// Returns the name of the variant of this value
+            fn variant_name(&self) -> String {
+                let name = match self {
+                    #(#variant_arms)*
+                };
+                name.to_owned()
+            }
+        });
+    }
+
+    Ok(out)
 }
 
 /// Removes functions from the list and matches them up based on composite ID's into a unified struct
@@ -363,9 +462,11 @@ pub fn impl_lua_proxy(input: TokenStream) -> TokenStream {
         .map(doc_attribute_to_string_lit)
         .map(|tkns| quote_spanned!(derive_input.span()=>methods.document_type(#tkns);));
 
+    let mut functions = meta.functions.0;
+
     // generate method equivalents for each field, i.e. unify fields and methods as both can be represented as functions
-    let field_methods: Vec<TraitItemFn> = match meta.data {
-        darling::ast::Data::<Variant, Field>::Struct(fields) => {
+    let field_methods: Vec<TraitItemFn> = match ProxyData::from(meta.data) {
+        ProxyData::Struct { fields } => {
             let mut out: Vec<_> = Default::default();
             let mut errors = darling::Error::accumulator();
 
@@ -396,15 +497,20 @@ pub fn impl_lua_proxy(input: TokenStream) -> TokenStream {
 
             out
         }
-        _ => panic!("Enums or Unions are not supported"),
+        // enum fields depend on the variant so they are exposed through methods instead
+        ProxyData::Enum { variants } => {
+            match convert_variants_to_lua_functions(&proxied_type_path, &variants) {
+                Ok(variant_functions) => functions.extend(variant_functions),
+                Err(e) => return e.write_errors().into(),
+            }
+            Vec::default()
+        }
     };
 
     let mut errors = darling::Error::accumulator();
 
     // generate both tealr documentation and instantiations of functions and field getters/setters
-    let mut methods = meta
-        .functions
-        .0
+    let mut methods = functions
         .into_iter()
         .filter_map(|v| {
             errors
@@ -526,9 +632,9 @@ pub fn impl_lua_proxy(input: TokenStream) -> TokenStream {
 #[cfg(test)]
 mod test {
 
-    use crate::function::FunctionAttributes;
+    use crate::{build_function, convert_variants_to_lua_functions, function::FunctionAttributes};
     use darling::FromAttributes;
-    use syn::TraitItemFn;
+    use syn::{parse_quote, TraitItemFn};
 
     #[test]
     fn test_parse_function_attributes_parses() {
@@ -540,4 +646,28 @@ mod test {
 
         FunctionAttributes::from_attributes(&trait_fn.attrs).unwrap();
     }
+
+    #[test]
+    fn test_enum_variants_convert_to_functions() {
+        let proxied_type_path: syn::Path = parse_quote!(bevy::input::gamepad::GamepadAxisType);
+        let variants: Vec<syn::Variant> = vec![
+            parse_quote!(LeftStickX),
+            parse_quote!(Other(u8)),
+            parse_quote!(Custom {
+                #[lua(skip)]
+                id: ReflectedValue
+            }),
+        ];
+
+        let functions = convert_variants_to_lua_functions(&proxied_type_path, &variants).unwrap();
+        let names = functions
+            .iter()
+            .map(|f| f.sig.ident.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["LeftStickX", "other_0", "Other", "variant_name"]);
+
+        for function in functions {
+            build_function(&proxied_type_path, function).unwrap();
+        }
+    }
 }
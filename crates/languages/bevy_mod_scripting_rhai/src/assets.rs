@@ -4,6 +4,8 @@ use bevy::{
 };
 
 use bevy_mod_scripting_core::prelude::*;
+use rhai::Engine;
+use serde::{Deserialize, Serialize};
 
 #[derive(Asset, Debug, TypePath)]
 /// A rhai code file in bytes
@@ -17,23 +19,41 @@ impl CodeAsset for RhaiFile {
     }
 }
 
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+/// Settings for loading rhai scripts, set them in the `.meta` file of a script or with `AssetServer::load_with_settings`
+pub struct RhaiLoaderSettings {
+    /// If true, the script is compiled as it is loaded so that syntax errors fail the asset load.
+    ///
+    /// This only validates the script, `RhaiScriptHost` always compiles scripts with its own engine.
+    /// The loader compiles with a plain `Engine`, so scripts relying on custom syntax or operators
+    /// registered with the host's engine fail to load with this set.
+    pub compile: bool,
+}
+
 #[derive(Default)]
-/// Asset loader for lua scripts
+/// Asset loader for rhai scripts
 pub struct RhaiLoader;
 
 impl AssetLoader for RhaiLoader {
     type Asset = RhaiFile;
-    type Settings = ();
+    type Settings = RhaiLoaderSettings;
     type Error = anyhow::Error;
     fn load<'a>(
         &'a self,
         reader: &'a mut Reader,
-        _: &'a Self::Settings,
-        _: &'a mut LoadContext,
+        settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
     ) -> bevy::asset::BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
         Box::pin(async move {
             let mut bytes = Vec::new();
             reader.read_to_end(&mut bytes).await?;
+
+            if settings.compile {
+                Engine::new()
+                    .compile(std::str::from_utf8(&bytes)?)
+                    .map_err(|e| anyhow::anyhow!("{}: {e}", load_context.asset_path()))?;
+            }
+
             Ok(RhaiFile { bytes })
         })
     }
@@ -42,3 +62,50 @@ impl AssetLoader for RhaiLoader {
         &["rhai"]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bevy::{asset::LoadState, prelude::*};
+
+    use super::*;
+
+    /// Loads the given script with the given settings, returning whether loading succeeded
+    fn load(source: &str, compile: bool) -> bool {
+        let dir = std::env::temp_dir().join(format!("bevy_mod_scripting_rhai_loader_{compile}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("script.rhai"), source).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin {
+                file_path: dir.to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+        ))
+        .init_asset::<RhaiFile>()
+        .init_asset_loader::<RhaiLoader>();
+
+        let handle: Handle<RhaiFile> = app
+            .world
+            .resource::<AssetServer>()
+            .load_with_settings("script.rhai", move |s: &mut RhaiLoaderSettings| {
+                s.compile = compile
+            });
+
+        loop {
+            app.update();
+            match app.world.resource::<AssetServer>().load_state(&handle) {
+                LoadState::Loaded => return true,
+                LoadState::Failed => return false,
+                _ => std::thread::sleep(std::time::Duration::from_millis(1)),
+            }
+        }
+    }
+
+    #[test]
+    fn syntax_errors_fail_the_load_only_if_compiling() {
+        assert!(load("let x = ;", false));
+        assert!(!load("let x = ;", true));
+    }
+}
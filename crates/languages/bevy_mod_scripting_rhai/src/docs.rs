@@ -1,17 +1,231 @@
-use bevy_mod_scripting_core::prelude::*;
+use std::{env, fmt::Write, fs};
 
-pub struct RhaiDocFragment;
+use bevy::asset::io::file::FileAssetReader;
+use bevy_mod_scripting_core::{docs::write_doc_file, prelude::*};
+use rhai::Engine;
+use serde_json::Value;
+
+/// Registers the API of a single provider with the engine used for documentation generation,
+/// this is usually the same thing `APIProvider::attach_api` does.
+pub type EngineBuilder = fn(&mut Engine) -> Result<(), ScriptError>;
+
+/// A piece of rhai documentation,
+/// each piece is attached to a single engine which is then used to generate one Markdown page and a set of `.d.rhai` definition files
+pub struct RhaiDocFragment {
+    name: &'static str,
+    builders: Vec<EngineBuilder>,
+    hooks: Vec<HookSchema>,
+}
+
+impl RhaiDocFragment {
+    pub fn new(name: &'static str, f: EngineBuilder) -> Self {
+        Self {
+            name,
+            builders: vec![f],
+            hooks: Vec::default(),
+        }
+    }
+
+    /// Builds a fresh engine with the API of every merged fragment attached
+    pub fn build_engine(&self) -> Result<Engine, ScriptError> {
+        let mut engine = Engine::new();
+        for builder in &self.builders {
+            builder(&mut engine)?;
+        }
+        Ok(engine)
+    }
+}
 
 impl DocFragment for RhaiDocFragment {
-    fn merge(self, _o: Self) -> Self {
-        todo!()
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn merge(mut self, o: Self) -> Self {
+        self.builders.extend(o.builders);
+        self.hooks.extend(o.hooks);
+        self
+    }
+
+    fn with_hooks(mut self, hooks: &[HookSchema]) -> Self {
+        self.hooks.extend_from_slice(hooks);
+        self
     }
 
     fn gen_docs(self) -> Result<(), ScriptError> {
-        todo!()
+        let script_asset_path = &FileAssetReader::get_base_path()
+            .join("assets")
+            .join("scripts");
+
+        let script_doc_dir = &env::var("SCRIPT_DOC_DIR")
+            .map(|v| v.into())
+            .unwrap_or_else(|_e| script_asset_path.join("doc"));
+
+        fs::create_dir_all(script_doc_dir).map_err(|e| {
+            ScriptError::DocGenError(format!(
+                "Could not create `{}` directories: {e}",
+                script_doc_dir.display()
+            ))
+        })?;
+
+        let engine = self.build_engine()?;
+
+        // generate markdown docs
+        let mut markdown = gen_markdown(&engine, self.name)?;
+        write_hooks(&mut markdown, &self.hooks);
+        let markdown_path = script_doc_dir.join(format!("{}.md", self.name));
+        write_doc_file(&markdown_path, &markdown)?;
+
+        // generate definition files for the rhai language server, standard packages are included
+        // so that built-in functions autocomplete as well
+        let definition_directory = script_asset_path.join("types");
+        engine
+            .definitions()
+            .with_headers(true)
+            .write_to_dir(&definition_directory)
+            .map_err(|e| {
+                ScriptError::DocGenError(format!(
+                    "Could not write definition files to `{}`: {e}",
+                    definition_directory.display()
+                ))
+            })
     }
+}
 
-    fn name(&self) -> &'static str {
-        todo!()
+/// Renders the functions registered with the engine (excluding the standard packages) as a single Markdown page
+pub fn gen_markdown(engine: &Engine, title: &str) -> Result<String, ScriptError> {
+    let json = engine
+        .gen_fn_metadata_to_json(false)
+        .map_err(|e| ScriptError::DocGenError(e.to_string()))?;
+    let metadata: Value =
+        serde_json::from_str(&json).map_err(|e| ScriptError::DocGenError(e.to_string()))?;
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# {title}\n");
+    write_module(&mut out, &metadata, "");
+    Ok(out)
+}
+
+/// Writes the hooks scripts can define, these are not part of the engine's metadata
+pub fn write_hooks(out: &mut String, hooks: &[HookSchema]) {
+    if hooks.is_empty() {
+        return;
+    }
+
+    let _ = writeln!(out, "## Hooks\n");
+    for hook in hooks {
+        let _ = writeln!(out, "```rust\nfn {}\n```\n", hook.signature());
+        if let Some(docs) = &hook.docs {
+            let _ = writeln!(out, "{docs}\n");
+        }
+    }
+}
+
+/// Writes the functions of a module in the metadata json followed by its sub-modules
+fn write_module(out: &mut String, module: &Value, path: &str) {
+    if let Some(functions) = module.get("functions").and_then(Value::as_array) {
+        if !functions.is_empty() {
+            let heading = if path.is_empty() { "Functions" } else { path };
+            let _ = writeln!(out, "## {heading}\n");
+        }
+
+        for f in functions {
+            let Some(signature) = f.get("signature").and_then(Value::as_str) else {
+                continue;
+            };
+            let _ = writeln!(out, "```rust\nfn {signature}\n```\n");
+
+            let docs = f
+                .get("docComments")
+                .and_then(Value::as_array)
+                .map(|d| d.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+                .unwrap_or_default();
+            if !docs.is_empty() {
+                let _ = writeln!(out, "{}\n", strip_doc_comments(&docs));
+            }
+        }
+    }
+
+    if let Some(modules) = module.get("modules").and_then(Value::as_object) {
+        for (name, module) in modules {
+            let path = if path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{path}::{name}")
+            };
+            write_module(out, module, &path);
+        }
+    }
+}
+
+/// Removes the comment markers from rhai doc comments, i.e. `/// docs` or `/** docs */`
+fn strip_doc_comments(docs: &[&str]) -> String {
+    docs.iter()
+        .flat_map(|d| d.lines())
+        .map(|line| {
+            let line = line.trim();
+            let line = line
+                .strip_prefix("///")
+                .or_else(|| line.strip_prefix("/**"))
+                .unwrap_or(line);
+            let line = line.strip_suffix("*/").unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merged_fragments_attach_every_api() {
+        let fragment = RhaiDocFragment::new("Game", |engine| {
+            engine.register_fn("spawn", || 1_i64);
+            Ok(())
+        })
+        .merge(RhaiDocFragment::new("Other", |engine| {
+            engine.register_fn("despawn", |_entity: i64| true);
+            Ok(())
+        }));
+
+        assert_eq!(fragment.name(), "Game");
+
+        let engine = fragment.build_engine().unwrap();
+        let markdown = gen_markdown(&engine, "Game").unwrap();
+
+        assert!(markdown.starts_with("# Game"));
+        assert!(markdown.contains("fn spawn()"));
+        assert!(markdown.contains("fn despawn(_: i64)"));
+        // standard packages are left out of the docs
+        assert!(!markdown.contains("fn abs("));
+    }
+
+    #[test]
+    fn hooks_are_documented() {
+        let mut markdown = String::new();
+        write_hooks(
+            &mut markdown,
+            &[HookSchema::new("on_level")
+                .with_arg("level", "i64")
+                .with_docs("Called when a level is loaded")],
+        );
+
+        assert_eq!(
+            markdown,
+            "## Hooks\n\n```rust\nfn on_level(level: i64)\n```\n\nCalled when a level is loaded\n\n"
+        );
+    }
+
+    #[test]
+    fn doc_comment_markers_are_stripped() {
+        assert_eq!(
+            strip_doc_comments(&["/// Spawns an entity", "/// and returns it"]),
+            "Spawns an entity\nand returns it"
+        );
+        assert_eq!(strip_doc_comments(&["/** Despawns */"]), "Despawns");
     }
 }
@@ -1,33 +1,62 @@
 use crate::{
     assets::{RhaiFile, RhaiLoader},
     docs::RhaiDocFragment,
+    modules::RhaiAssetModuleResolver,
+};
+use bevy::{
+    ecs::schedule::ScheduleLabel,
+    prelude::*,
+    tasks::{ComputeTaskPool, TaskPool},
+};
+use bevy_mod_scripting_core::{
+    hooks::ScriptFunction,
+    prelude::*,
+    systems::*,
+    world::{WorldMutationMode, WorldPointer, WorldPointerGuard},
 };
-use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
-use bevy_mod_scripting_core::{prelude::*, systems::*, world::WorldPointerGuard};
 use rhai::*;
-use std::marker::PhantomData;
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
 
 pub mod assets;
 pub mod docs;
+pub mod modules;
+pub mod state;
 pub use rhai;
 pub mod prelude {
     pub use crate::{
-        assets::{RhaiFile, RhaiLoader},
-        docs::RhaiDocFragment,
-        RhaiContext, RhaiEvent, RhaiScriptHost,
+        assets::{RhaiFile, RhaiLoader, RhaiLoaderSettings},
+        docs::{EngineBuilder, RhaiDocFragment},
+        modules::RhaiAssetModuleResolver,
+        state::{RhaiScriptState, RhaiVariable},
+        RhaiArgs, RhaiContext, RhaiEvent, RhaiScriptHost,
     };
     pub use rhai;
     pub use rhai::{Engine, FuncArgs};
 }
 
+/// Rhai script host, enables Rhai scripting.
+///
+/// Multiple Rhai hosts can be registered by giving each a distinct label `L`,
+/// each host then only runs the scripts in `ScriptCollection<RhaiFile, L>` components.
+///
+/// Each script asset is compiled once, every instance of the script shares its AST while keeping its own `Scope`.
+/// The AST of an asset is discarded whenever the asset changes.
 #[derive(Resource)]
-pub struct RhaiScriptHost<A: FuncArgs + Send> {
+pub struct RhaiScriptHost<A: RhaiArgs, L: ScriptLabel = ()> {
     pub engine: Engine,
-    _ph: PhantomData<A>,
+    /// if true, hot reloaded scripts keep their `state` map and top-level variables, see [`RhaiScriptState`](crate::state::RhaiScriptState)
+    pub persist_state_on_reload: bool,
+    /// resolves `import` statements against `RhaiFile` assets, installed on startup
+    pub module_resolver: Option<RhaiAssetModuleResolver>,
+    /// the modules each script imported when it was last loaded, until taken by [`ScriptHost::take_script_dependencies`]
+    dependencies: HashMap<u32, Vec<AssetId<RhaiFile>>>,
+    /// the compiled AST of each script asset
+    asts: HashMap<AssetId<RhaiFile>, Arc<AST>>,
+    _ph: PhantomData<(A, L)>,
 }
 
 #[allow(deprecated)]
-impl<A: FuncArgs + Send> Default for RhaiScriptHost<A> {
+impl<A: RhaiArgs, L: ScriptLabel> Default for RhaiScriptHost<A, L> {
     fn default() -> Self {
         let mut e = Engine::new();
         // prevent shadowing of `state`,`world` and `entity` in variable in scripts
@@ -37,55 +66,114 @@ impl<A: FuncArgs + Send> Default for RhaiScriptHost<A> {
 
         Self {
             engine: e,
+            persist_state_on_reload: false,
+            module_resolver: None,
+            dependencies: Default::default(),
+            asts: Default::default(),
             _ph: Default::default(),
         }
     }
 }
 
 pub struct RhaiContext {
-    pub ast: AST,
+    /// the AST of the script, shared by every instance of the script until it runs for the first time
+    pub ast: Arc<AST>,
     pub scope: Scope<'static>,
 }
 
+impl RhaiContext {
+    /// Clears the top-level statements of the script, leaving only function definitions
+    /// and `import` statements so that imported modules remain available to hooks
+    pub fn clear_statements(&mut self) {
+        if self.is_initialized() {
+            return;
+        }
+
+        let imports = self
+            .ast
+            .statements()
+            .iter()
+            .filter(|stmt| matches!(stmt, Stmt::Import(..)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut ast = AST::new(imports, self.ast.shared_lib().clone());
+        if let Some(source) = self.ast.source() {
+            ast.set_source(source.to_owned());
+        }
+        self.ast = Arc::new(ast);
+    }
+
+    /// Returns true if the top-level statements of the script were cleared, i.e. the script has already run once
+    pub fn is_initialized(&self) -> bool {
+        self.ast
+            .statements()
+            .iter()
+            .all(|stmt| matches!(stmt, Stmt::Import(..)))
+    }
+}
+
+/// The arguments of a [`RhaiEvent`], implemented for all [`FuncArgs`].
+///
+/// Unlike [`FuncArgs`] the conversion into rhai values may fail, in which case the hook is not called.
+pub trait RhaiArgs: Clone + Send + Sync + 'static {
+    fn into_args(self) -> Result<Vec<Dynamic>, Box<EvalAltResult>>;
+}
+
+impl<T: FuncArgs + Clone + Send + Sync + 'static> RhaiArgs for T {
+    fn into_args(self) -> Result<Vec<Dynamic>, Box<EvalAltResult>> {
+        let mut args = Vec::default();
+        self.parse(&mut args);
+        Ok(args)
+    }
+}
+
 #[derive(Clone, Event)]
 /// A Rhai Hook. The result of creating this event will be
 /// a call to the lua script with the hook_name and the given arguments
-pub struct RhaiEvent<A: FuncArgs + Clone + 'static> {
+pub struct RhaiEvent<A: RhaiArgs> {
     pub hook_name: String,
     pub args: A,
     pub recipients: Recipients,
 }
 
-impl<A: FuncArgs + Clone + Send + Sync + 'static> ScriptEvent for RhaiEvent<A> {
+impl<A: RhaiArgs> ScriptEvent for RhaiEvent<A> {
     fn recipients(&self) -> &crate::Recipients {
         &self.recipients
     }
 }
 
-impl<A: FuncArgs + Send + Clone + Sync + 'static> ScriptHost for RhaiScriptHost<A> {
+impl<A: RhaiArgs, L: ScriptLabel> ScriptHost
+    for RhaiScriptHost<A, L>
+{
     type ScriptContext = RhaiContext;
     type ScriptEvent = RhaiEvent<A>;
     type ScriptAsset = RhaiFile;
     type APITarget = Engine;
     type DocTarget = RhaiDocFragment;
+    type Label = L;
 
     fn register_with_app_in_set(
         app: &mut bevy::prelude::App,
         schedule: impl ScheduleLabel,
         set: impl SystemSet,
     ) {
+        // other rhai hosts might have registered the asset already
+        if !app.world.contains_resource::<Assets<RhaiFile>>() {
+            app.init_asset::<RhaiFile>()
+                .init_asset_loader::<RhaiLoader>()
+                .register_type::<Handle<RhaiFile>>();
+        }
+
         app.add_priority_event::<Self::ScriptEvent>()
-            .init_asset::<RhaiFile>()
-            .init_asset_loader::<RhaiLoader>()
             .init_resource::<CachedScriptState<Self>>()
-            .init_resource::<ScriptContexts<Self::ScriptContext>>()
+            .init_resource::<ScriptContexts<Self::ScriptContext, L>>()
             .init_resource::<APIProviders<Self>>()
-            .register_type::<ScriptCollection<Self::ScriptAsset>>()
-            .register_type::<Script<Self::ScriptAsset>>()
-            .register_type::<Handle<RhaiFile>>()
+            .register_type::<ScriptCollection<Self::ScriptAsset, L>>()
+            .register_type::<Script<Self::ScriptAsset, L>>()
             .add_systems(
                 schedule,
                 (
+                    Self::sync_assets,
                     script_add_synchronizer::<Self>,
                     script_remove_synchronizer::<Self>,
                     script_hot_reload_handler::<Self>,
@@ -96,7 +184,14 @@ impl<A: FuncArgs + Send + Clone + Sync + 'static> ScriptHost for RhaiScriptHost<
             // setup engine
             .add_systems(
                 Startup,
-                |mut providers: ResMut<APIProviders<Self>>, mut host: ResMut<Self>| {
+                |mut providers: ResMut<APIProviders<Self>>,
+                 mut host: ResMut<Self>,
+                 asset_server: Res<AssetServer>| {
+                    // installed before the API so that providers can replace it
+                    let resolver = RhaiAssetModuleResolver::new(asset_server.clone());
+                    host.engine.set_module_resolver(resolver.clone());
+                    host.module_resolver = Some(resolver);
+
                     providers
                         .attach_all(&mut host.engine)
                         .expect("Error in adding api's for rhai");
@@ -119,7 +214,131 @@ impl<A: FuncArgs + Send + Clone + Sync + 'static> ScriptHost for RhaiScriptHost<
         script_data: &ScriptData,
         _: &mut APIProviders<Self>,
     ) -> Result<Self::ScriptContext, ScriptError> {
-        let mut scope = Scope::new();
+        let ast = Arc::new(self.compile(script, script_data)?);
+        self.load_ast(ast, script_data)
+    }
+
+    fn load_script_asset(
+        &mut self,
+        asset: AssetId<Self::ScriptAsset>,
+        script: &[u8],
+        script_data: &ScriptData,
+        _: &mut APIProviders<Self>,
+    ) -> Result<Self::ScriptContext, ScriptError> {
+        let ast = match self.asts.get(&asset) {
+            Some(ast) => ast.clone(),
+            None => {
+                let ast = Arc::new(self.compile(script, script_data)?);
+                self.asts.insert(asset, ast.clone());
+                ast
+            }
+        };
+
+        self.load_ast(ast, script_data)
+    }
+
+    fn take_script_dependencies(
+        &mut self,
+        script_data: &ScriptData,
+    ) -> Vec<AssetId<Self::ScriptAsset>> {
+        self.dependencies
+            .remove(&script_data.sid)
+            .unwrap_or_default()
+    }
+
+    fn script_functions(&self, ctx: &Self::ScriptContext) -> Vec<ScriptFunction> {
+        ctx.ast
+            .iter_functions()
+            .map(|f| ScriptFunction {
+                name: f.name.to_owned(),
+                arity: Some(f.params.len()),
+            })
+            .collect()
+    }
+
+    fn reload_context(&mut self, old: Self::ScriptContext, new: &mut Self::ScriptContext) {
+        if self.persist_state_on_reload {
+            new.restore_state(old.save_state());
+        }
+    }
+
+    fn handle_events<'a>(
+        &mut self,
+        world: &mut World,
+        events: &[Self::ScriptEvent],
+        ctxs: impl Iterator<Item = (ScriptData<'a>, &'a mut Self::ScriptContext)>,
+        providers: &mut APIProviders<Self>,
+    ) {
+        let mode = world
+            .get_resource::<WorldMutationMode>()
+            .copied()
+            .unwrap_or_default();
+
+        // safety:
+        // - we have &mut World access
+        // - we do not use the original reference again anywhere in this function
+        let world = unsafe { WorldPointerGuard::new(world) };
+
+        ctxs.for_each(|(fd, ctx)| {
+            let script_world = world.with_mode(mode);
+            providers
+                .setup_runtime_all(script_world.clone(), &fd, ctx)
+                .expect("Failed to setup script runtime");
+
+            // make the world reachable for argument conversions
+            let errors = script_world.enter(|| self.call_hooks(ctx, &fd, events));
+            Self::report_errors(&world, errors);
+
+            // apply any structural changes the script deferred
+            script_world.apply_deferred();
+        });
+    }
+
+    fn handle_events_parallel<'a>(
+        &mut self,
+        world: &mut World,
+        events: &[Self::ScriptEvent],
+        ctxs: Vec<(ScriptData<'a>, &'a mut Self::ScriptContext)>,
+        providers: &mut APIProviders<Self>,
+    ) {
+        // safety:
+        // - we have &mut World access
+        // - we do not use the original reference again anywhere in this function
+        let world = unsafe { WorldPointerGuard::new(world) };
+
+        let mut ctxs = ctxs
+            .into_iter()
+            .map(|(fd, ctx)| {
+                let script_world = world.read_only();
+                providers
+                    .setup_runtime_all(script_world.clone(), &fd, ctx)
+                    .expect("Failed to setup script runtime");
+                (fd, ctx, script_world)
+            })
+            .collect::<Vec<_>>();
+
+        // the engine is shared immutably, each script has its own scope and can only take
+        // read locks on the world, so they can safely run side by side
+        let host = &*self;
+        let errors = ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+            for (fd, ctx, script_world) in ctxs.iter_mut() {
+                let script_world = &*script_world;
+                scope.spawn(async move { script_world.enter(|| host.call_hooks(ctx, fd, events)) });
+            }
+        });
+
+        // apply the structural changes in a deterministic order once every script is done
+        for (_, _, script_world) in &ctxs {
+            script_world.apply_deferred();
+        }
+
+        Self::report_errors(&world, errors.into_iter().flatten().collect());
+    }
+}
+
+impl<A: RhaiArgs, L: ScriptLabel> RhaiScriptHost<A, L> {
+    /// Compiles a script with the host's engine
+    fn compile(&self, script: &[u8], script_data: &ScriptData) -> Result<AST, ScriptError> {
         let mut ast = self
             .engine
             .compile(
@@ -134,6 +353,33 @@ impl<A: FuncArgs + Send + Clone + Sync + 'static> ScriptHost for RhaiScriptHost<
             })?;
 
         ast.set_source(script_data.name);
+        Ok(ast)
+    }
+
+    /// Creates a new script context with the given AST, checking the modules it imports are available
+    fn load_ast(
+        &mut self,
+        ast: Arc<AST>,
+        script_data: &ScriptData,
+    ) -> Result<RhaiContext, ScriptError> {
+        let mut scope = Scope::new();
+
+        if let Some(resolver) = &self.module_resolver {
+            let (handles, missing) = resolver.dependencies(&self.engine, &ast);
+            self.dependencies
+                .insert(script_data.sid, handles.iter().map(Handle::id).collect());
+
+            // the script is reloaded once its modules are loaded
+            if !missing.is_empty() {
+                return Err(ScriptError::FailedToLoad {
+                    script: script_data.name.to_owned(),
+                    msg: format!(
+                        "Imported modules are not loaded yet: {}",
+                        missing.join(", ")
+                    ),
+                });
+            }
+        }
 
         // persistent state for scripts
         scope.push("state", Map::new());
@@ -141,62 +387,134 @@ impl<A: FuncArgs + Send + Clone + Sync + 'static> ScriptHost for RhaiScriptHost<
         Ok(RhaiContext { ast, scope })
     }
 
-    fn handle_events<'a>(
-        &mut self,
-        world: &mut World,
-        events: &[Self::ScriptEvent],
-        ctxs: impl Iterator<Item = (ScriptData<'a>, &'a mut Self::ScriptContext)>,
-        providers: &mut APIProviders<Self>,
-    ) {
-        // safety:
-        // - we have &mut World access
-        // - we do not use the original reference again anywhere in this function
-        let world = unsafe { WorldPointerGuard::new(world) };
-
-        ctxs.for_each(|(fd, ctx)| {
-            providers
-                .setup_runtime_all(world.clone(), &fd, ctx)
-                .expect("Failed to setup script runtime");
+    /// Calls the hooks subscribed to the given events on a single script, returning any errors raised
+    fn call_hooks(
+        &self,
+        ctx: &mut RhaiContext,
+        fd: &ScriptData,
+        events: &[RhaiEvent<A>],
+    ) -> Vec<ScriptError> {
+        let mut errors = Vec::default();
+        for event in events.iter() {
+            // check if this script should handle this event
+            if !event.recipients().is_recipient(fd) {
+                continue;
+            };
 
-            for event in events.iter() {
-                // check if this script should handle this event
-                if !event.recipients().is_recipient(&fd) {
+            let args = match event.args.clone().into_args() {
+                Ok(args) => args,
+                Err(e) => {
+                    errors.push(ScriptError::RuntimeError {
+                        script: fd.name.to_string(),
+                        msg: format!("Could not convert the arguments of `{}`: {e}", event.hook_name),
+                    });
                     continue;
-                };
-
-                match self.engine.call_fn(
-                    &mut ctx.scope,
-                    &ctx.ast,
-                    &event.hook_name,
-                    event.args.clone(),
-                ) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        let mut world = world.write();
-                        let mut state: CachedScriptState<Self> = world.remove_resource().unwrap();
-
-                        match *e {
-                            EvalAltResult::ErrorFunctionNotFound(..) => {}
-                            _ => {
-                                let (_, mut error_wrt, _) = state.event_state.get_mut(&mut world);
-
-                                let error = ScriptError::RuntimeError {
-                                    script: fd.name.to_string(),
-                                    msg: e.to_string(),
-                                };
-                                error!("{}", error);
-                                error_wrt.send(ScriptErrorEvent { error });
-                            }
-                        }
-
-                        world.insert_resource(state);
+                }
+            };
+
+            if let Err(e) =
+                self.engine
+                    .call_fn::<()>(&mut ctx.scope, &ctx.ast, &event.hook_name, args)
+            {
+                match *e {
+                    EvalAltResult::ErrorFunctionNotFound(..) => {}
+                    _ => errors.push(ScriptError::RuntimeError {
+                        script: fd.name.to_string(),
+                        msg: e.to_string(),
+                    }),
+                }
+            };
+        }
+
+        // executing this at the end here means we execute global statements exactly once,
+        // only the import statements are kept and re-executed on each call
+        ctx.clear_statements();
+
+        errors
+    }
+
+    /// Discards the ASTs of changed assets and keeps the modules imported by scripts in sync with their assets
+    fn sync_assets(
+        mut host: ResMut<Self>,
+        mut events: EventReader<AssetEvent<RhaiFile>>,
+        assets: Res<Assets<RhaiFile>>,
+    ) {
+        for event in events.read() {
+            match event {
+                AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                    host.asts.remove(id);
+                    if let (Some(resolver), Some(asset)) = (&host.module_resolver, assets.get(*id)) {
+                        resolver.update(*id, &asset.bytes);
                     }
-                };
+                }
+                AssetEvent::Removed { id } => {
+                    host.asts.remove(id);
+                }
+                _ => {}
             }
+        }
+    }
 
-            // executing this at the end here means we execute global statements exactly once
-            // all this method call does is set a variable on the AST to NONE so should not affect performance
-            ctx.ast.clear_statements();
-        });
+    fn report_errors(world: &WorldPointer, errors: Vec<ScriptError>) {
+        if errors.is_empty() {
+            return;
+        }
+
+        let mut world = world.write();
+        let mut state: CachedScriptState<Self> = world.remove_resource().unwrap();
+
+        let (_, mut error_wrt, _) = state.event_state.get_mut(&mut world);
+
+        for error in errors {
+            error!("{}", error);
+            error_wrt.send(ScriptErrorEvent { error });
+        }
+        world.insert_resource(state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn asts_are_compiled_by_the_host_and_shared_until_the_asset_changes() {
+        let mut host = RhaiScriptHost::<()>::default();
+        host.engine
+            .register_custom_operator("plus", 160)
+            .unwrap()
+            .register_fn("plus", |a: INT, b: INT| a + b);
+
+        let mut world = World::new();
+        world.init_resource::<Assets<RhaiFile>>();
+        world.init_resource::<Events<AssetEvent<RhaiFile>>>();
+        world.insert_resource(host);
+
+        let id = Handle::<RhaiFile>::weak_from_u128(1).id();
+        let script_data = ScriptData {
+            sid: 0,
+            entity: Entity::from_raw(0),
+            name: "test.rhai",
+        };
+        let mut providers = APIProviders::default();
+        let mut load = |world: &mut World, source: &str| {
+            world
+                .resource_mut::<RhaiScriptHost<()>>()
+                .load_script_asset(id, source.as_bytes(), &script_data, &mut providers)
+                .unwrap()
+                .ast
+        };
+
+        // custom syntax of the host's engine is available
+        let first = load(&mut world, "let x = 1 plus 2;");
+        let second = load(&mut world, "let x = 3;");
+        assert!(Arc::ptr_eq(&first, &second));
+
+        world.send_event(AssetEvent::<RhaiFile>::Modified { id });
+        world.run_system_once(RhaiScriptHost::<()>::sync_assets);
+        let third = load(&mut world, "let x = 3;");
+        assert!(!Arc::ptr_eq(&first, &third));
     }
 }
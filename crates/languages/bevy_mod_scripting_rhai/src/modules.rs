@@ -0,0 +1,202 @@
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use bevy::{
+    asset::{AssetPath, AssetServer},
+    prelude::*,
+};
+use bevy_mod_scripting_core::prelude::AssetModules;
+use rhai::{
+    module_resolvers::ModuleResolver, ASTNode, Engine, EvalAltResult, Expr, Module, Position,
+    Scope, Shared, Stmt, AST,
+};
+
+use crate::assets::RhaiFile;
+
+/// A module resolver serving `RhaiFile` assets, installed by `RhaiScriptHost` on startup.
+///
+/// `import "utils/math" as math;` resolves to the `scripts/utils/math.rhai` asset, relative to the `base_path`.
+/// Imported modules are requested from the asset server the first time a script depending on them is loaded,
+/// scripts which are loaded before their modules are available fail to load and are reloaded once the modules arrive.
+#[derive(Clone)]
+pub struct RhaiAssetModuleResolver {
+    base_path: PathBuf,
+    /// the source of each module along with the module it evaluates to
+    modules: AssetModules<RhaiFile, Arc<str>, Shared<Module>>,
+}
+
+impl RhaiAssetModuleResolver {
+    pub fn new(asset_server: AssetServer) -> Self {
+        Self {
+            base_path: PathBuf::from("scripts"),
+            modules: AssetModules::new(asset_server),
+        }
+    }
+
+    /// Sets the path import paths are relative to, `scripts` by default
+    pub fn with_base_path(mut self, base_path: impl Into<PathBuf>) -> Self {
+        self.base_path = base_path.into();
+        self
+    }
+
+    /// The asset path of the given import path
+    pub fn asset_path(&self, path: &str) -> AssetPath<'static> {
+        let mut path = self.base_path.join(path);
+        if path.extension().is_none() {
+            path.set_extension("rhai");
+        }
+        AssetPath::from(path)
+    }
+
+    /// Updates the source of the module stored in the given asset and discards all evaluated modules,
+    /// does nothing if no script imports it
+    pub fn update(&self, id: AssetId<RhaiFile>, bytes: &[u8]) {
+        self.modules
+            .update(id, String::from_utf8_lossy(bytes).into());
+    }
+
+    /// Collects the modules the given script imports, directly or through other modules.
+    ///
+    /// Returns the handles of all of them along with the import paths of the modules which are not loaded yet,
+    /// only `import` statements with constant string paths are considered.
+    pub fn dependencies(&self, engine: &Engine, ast: &AST) -> (Vec<Handle<RhaiFile>>, Vec<String>) {
+        let mut handles = Vec::default();
+        let mut missing = Vec::default();
+        let mut visited = HashSet::new();
+        let mut queue = imports(ast);
+
+        while let Some(path) = queue.pop() {
+            if !visited.insert(path.clone()) {
+                continue;
+            }
+
+            let asset_path = self.asset_path(&path);
+            // modules which do not exist are reported once they are imported
+            let Some(handle) = self.modules.load(&asset_path) else {
+                continue;
+            };
+            handles.push(handle);
+
+            match self.modules.source(&asset_path) {
+                Some(source) => {
+                    // modules which fail to compile are reported once they are imported
+                    if let Ok(module_ast) = engine.compile(&*source) {
+                        queue.extend(imports(&module_ast));
+                    }
+                }
+                None => missing.push(path),
+            }
+        }
+
+        (handles, missing)
+    }
+}
+
+impl ModuleResolver for RhaiAssetModuleResolver {
+    fn resolve(
+        &self,
+        engine: &Engine,
+        _source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        let asset_path = self.asset_path(path);
+
+        if let Some(module) = self.modules.compiled(&asset_path) {
+            return Ok(module);
+        }
+
+        let source = self
+            .modules
+            .source(&asset_path)
+            .ok_or_else(|| Box::new(EvalAltResult::ErrorModuleNotFound(path.to_owned(), pos)))?;
+
+        let mut ast = engine
+            .compile(&*source)
+            .map_err(|e| Box::new(EvalAltResult::ErrorInModule(path.to_owned(), e.into(), pos)))?;
+        ast.set_source(asset_path.to_string());
+
+        let module: Shared<Module> = Module::eval_ast_as_new(Scope::new(), &ast, engine)
+            .map_err(|e| Box::new(EvalAltResult::ErrorInModule(path.to_owned(), e, pos)))?
+            .into();
+
+        self.modules.set_compiled(&asset_path, module.clone());
+
+        Ok(module)
+    }
+}
+
+/// The constant paths of the `import` statements in the given AST
+fn imports(ast: &AST) -> Vec<String> {
+    let mut imports = Vec::default();
+    ast.walk(&mut |path: &[ASTNode]| {
+        if let Some(ASTNode::Stmt(Stmt::Import(import, ..))) = path.last() {
+            if let Expr::StringConstant(path, ..) = &import.0 {
+                imports.push(path.to_string());
+            }
+        }
+        true
+    });
+    imports
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_constant_import_paths_are_collected() {
+        let ast = Engine::new()
+            .compile(
+                r#"
+                import "utils/math" as math;
+                let name = "dynamic";
+                import name as dynamic;
+                fn update() { import "utils/strings" as strings; }
+                "#,
+            )
+            .unwrap();
+
+        let mut paths = imports(&ast);
+        paths.sort();
+        assert_eq!(paths, vec!["utils/math", "utils/strings"]);
+    }
+
+    #[test]
+    fn changed_modules_are_reloaded_by_their_importers() {
+        let dir = std::env::temp_dir().join("bevy_mod_scripting_rhai_module_chain");
+        std::fs::create_dir_all(dir.join("scripts")).unwrap();
+        let middle = r#"import "leaf" as leaf; export const VALUE = leaf::VALUE;"#;
+        std::fs::write(dir.join("scripts/middle.rhai"), middle).unwrap();
+        std::fs::write(dir.join("scripts/leaf.rhai"), "export const VALUE = 1;").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin {
+                file_path: dir.to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+        ))
+        .init_asset::<RhaiFile>()
+        .init_asset_loader::<crate::assets::RhaiLoader>();
+
+        let resolver = RhaiAssetModuleResolver::new(app.world.resource::<AssetServer>().clone());
+        let mut engine = Engine::new();
+        engine.set_module_resolver(resolver.clone());
+
+        let middle_handle = resolver.modules.load(&resolver.asset_path("middle")).unwrap();
+        let leaf_handle = resolver.modules.load(&resolver.asset_path("leaf")).unwrap();
+        resolver.update(middle_handle.id(), middle.as_bytes());
+        resolver.update(leaf_handle.id(), b"export const VALUE = 1;");
+
+        let script = r#"import "middle" as middle; middle::VALUE"#;
+        assert_eq!(engine.eval::<i64>(script).unwrap(), 1);
+
+        resolver.update(leaf_handle.id(), b"export const VALUE = 2;");
+        assert_eq!(engine.eval::<i64>(script).unwrap(), 2);
+    }
+}
@@ -0,0 +1,202 @@
+use rhai::{Array, Dynamic, Map};
+use serde::{Deserialize, Serialize};
+
+use crate::RhaiContext;
+
+/// A single top-level variable of a script
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RhaiVariable {
+    pub name: String,
+    pub value: Dynamic,
+    pub constant: bool,
+}
+
+/// The persistent state of a Rhai script, i.e. its `state` map along with any other top-level variables.
+///
+/// Can be serialized with serde in order to carry scripted state across sessions (save games),
+/// and restored into a freshly loaded context of the same script with [`RhaiContext::restore_state`].
+///
+/// Values of custom rust types (such as `world` or `entity`) cannot be serialized and are not saved,
+/// these are expected to be provided again by the API providers once the context is rebuilt.
+/// Such values nested in arrays and maps are left out of them as well.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RhaiScriptState {
+    pub variables: Vec<RhaiVariable>,
+    /// true if the top-level statements of the script were executed before saving
+    pub initialized: bool,
+}
+
+impl RhaiContext {
+    /// Captures the `state` map and top-level variables of this script,
+    /// only the innermost variable of each name is saved if any were shadowed
+    pub fn save_state(&self) -> RhaiScriptState {
+        let mut variables: Vec<RhaiVariable> = Vec::default();
+        for (name, constant, value) in self.scope.iter_raw() {
+            let Some(value) = strip_variants(value.flatten_clone()) else {
+                continue;
+            };
+
+            variables.retain(|v| v.name != name);
+            variables.push(RhaiVariable {
+                name: name.to_owned(),
+                value,
+                constant,
+            });
+        }
+
+        RhaiScriptState {
+            variables,
+            initialized: self.is_initialized(),
+        }
+    }
+
+    /// Restores previously saved script state into this context, replacing all variables with the same name
+    /// (constant or not) with the restored ones.
+    ///
+    /// If the statements of the saved script were already executed, the statements of this context are cleared
+    /// so that they do not re-initialize the restored variables, new top-level variables should hence be kept in `state`.
+    pub fn restore_state(&mut self, state: RhaiScriptState) {
+        for var in state.variables {
+            while self.scope.remove::<Dynamic>(&var.name).is_some() {}
+
+            if var.constant {
+                self.scope.push_constant_dynamic(var.name, var.value);
+            } else {
+                self.scope.push_dynamic(var.name, var.value);
+            }
+        }
+
+        if state.initialized {
+            self.clear_statements();
+        }
+    }
+}
+
+/// Leaves out values of custom rust types, which cannot be serialized, from the given value and any arrays and maps in it.
+///
+/// Returns `None` if the value itself is of a custom type.
+fn strip_variants(value: Dynamic) -> Option<Dynamic> {
+    let value = value.flatten();
+    if value.is_variant() {
+        None
+    } else if value.is_array() {
+        let array: Array = value
+            .cast::<Array>()
+            .into_iter()
+            .filter_map(strip_variants)
+            .collect();
+        Some(array.into())
+    } else if value.is_map() {
+        let map: Map = value
+            .cast::<Map>()
+            .into_iter()
+            .filter_map(|(k, v)| strip_variants(v).map(|v| (k, v)))
+            .collect();
+        Some(map.into())
+    } else {
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use rhai::{Engine, Scope};
+
+    use super::*;
+
+    fn load(engine: &Engine, script: &str) -> RhaiContext {
+        let mut scope = Scope::new();
+        scope.push("state", Map::new());
+        RhaiContext {
+            ast: Arc::new(engine.compile(script).unwrap()),
+            scope,
+        }
+    }
+
+    #[test]
+    fn state_survives_serialization_into_a_new_context() {
+        let engine = Engine::new();
+        let script = "let counter = 5; const NAME = \"player\"; state.health = 2;";
+
+        let mut ctx = load(&engine, script);
+        engine.run_ast_with_scope(&mut ctx.scope, &ctx.ast).unwrap();
+        ctx.clear_statements();
+
+        let json = serde_json::to_string(&ctx.save_state()).unwrap();
+        let state: RhaiScriptState = serde_json::from_str(&json).unwrap();
+
+        let mut ctx = load(&engine, script);
+        ctx.restore_state(state);
+
+        assert!(ctx.is_initialized());
+        assert_eq!(ctx.scope.get_value::<i64>("counter"), Some(5));
+        assert_eq!(ctx.scope.is_constant("NAME"), Some(true));
+        let state = ctx.scope.get_value::<Map>("state").unwrap();
+        assert_eq!(state["health"].as_int(), Ok(2));
+    }
+
+    #[test]
+    fn statements_are_kept_if_the_script_never_ran() {
+        let engine = Engine::new();
+        let ctx = load(&engine, "let counter = 5;");
+        let state = ctx.save_state();
+        assert!(!state.initialized);
+
+        let mut ctx = load(&engine, "let counter = 5;");
+        ctx.restore_state(state);
+        assert!(!ctx.is_initialized());
+    }
+
+    #[derive(Clone)]
+    struct Handle;
+
+    #[test]
+    fn nested_custom_types_are_not_saved() {
+        let mut engine = Engine::new();
+        engine
+            .register_type::<Handle>()
+            .register_fn("handle", || Handle);
+        let mut ctx = load(
+            &engine,
+            "let handles = [handle(), 1]; state.handle = handle(); state.inner = #{ handle: handle(), hp: 3 };",
+        );
+        engine.run_ast_with_scope(&mut ctx.scope, &ctx.ast).unwrap();
+
+        let json = serde_json::to_string(&ctx.save_state()).unwrap();
+        let state: RhaiScriptState = serde_json::from_str(&json).unwrap();
+
+        let mut ctx = load(&engine, "");
+        ctx.restore_state(state);
+        let handles = ctx.scope.get_value::<Array>("handles").unwrap();
+        assert_eq!(handles.len(), 1);
+        assert_eq!(handles[0].as_int(), Ok(1));
+        let state = ctx.scope.get_value::<Map>("state").unwrap();
+        assert!(!state.contains_key("handle"));
+        let inner = state["inner"].clone_cast::<Map>();
+        assert!(!inner.contains_key("handle"));
+        assert_eq!(inner["hp"].as_int(), Ok(3));
+    }
+
+    #[test]
+    fn restored_constants_replace_existing_variables() {
+        let engine = Engine::new();
+        let mut ctx = load(&engine, "");
+        ctx.scope.push("LIMIT", 1_i64);
+        ctx.scope.push_constant("LIMIT", 2_i64);
+
+        ctx.restore_state(RhaiScriptState {
+            variables: vec![RhaiVariable {
+                name: "LIMIT".to_owned(),
+                value: Dynamic::from(3_i64),
+                constant: true,
+            }],
+            initialized: true,
+        });
+
+        assert_eq!(ctx.scope.iter().filter(|(name, ..)| *name == "LIMIT").count(), 1);
+        assert_eq!(ctx.scope.is_constant("LIMIT"), Some(true));
+        assert_eq!(ctx.scope.get_value::<i64>("LIMIT"), Some(3));
+    }
+}
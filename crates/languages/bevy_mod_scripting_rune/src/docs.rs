@@ -1,17 +1,362 @@
-use bevy_mod_scripting_core::prelude::*;
+use std::{env, fmt::Write, fs};
 
-pub struct RuneDocFragment;
+use bevy::asset::io::file::FileAssetReader;
+use bevy_mod_scripting_core::{
+    docs::{write_doc_file, HtmlPage, HtmlSection},
+    prelude::*,
+};
+use rune::{
+    compile::Named,
+    module::{FunctionMetaData, InstallWith},
+    runtime::TypeOf,
+    ContextError, Module,
+};
+
+/// Documentation of a single function exposed to scripts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuneFunctionDoc {
+    /// The signature as written in scripts, i.e. `get_component(self, entity, comp_type)`
+    pub signature: String,
+    pub docs: Vec<String>,
+}
+
+/// Documentation of a type exposed to scripts along with its associated functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuneTypeDoc {
+    pub name: String,
+    pub docs: Vec<String>,
+    pub functions: Vec<RuneFunctionDoc>,
+}
+
+/// The metadata of a function annotated with `#[rune::function]`, which captures its doc comments and parameter names
+pub type FunctionMeta = fn() -> rune::alloc::Result<FunctionMetaData>;
+
+/// Documentation of the items installed into a Rune [`rune::Module`].
+///
+/// Rune keeps the metadata of installed items private, so API providers install their items through here,
+/// documenting each function with the doc comments and parameter names captured by its `#[rune::function]` attribute:
+///
+/// ```rust,ignore
+/// /// Spawns a new entity
+/// #[rune::function(instance)]
+/// fn spawn(world: &World) -> Entity {
+///     world.spawn()
+/// }
+///
+/// docs.associated_function::<World>(&mut module, spawn)?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuneDocs {
+    pub functions: Vec<RuneFunctionDoc>,
+    pub types: Vec<RuneTypeDoc>,
+    /// The functions scripts can define to be called by the host, see `ScriptHooks`
+    pub hooks: Vec<RuneFunctionDoc>,
+}
+
+impl RuneDocs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Installs the type `T` into the module with the given documentation, installing the same type twice appends the doc lines
+    pub fn ty<T: TypeOf + Named + InstallWith>(
+        &mut self,
+        module: &mut Module,
+        docs: &'static [&'static str],
+    ) -> Result<(), ContextError> {
+        module.ty::<T>()?.docs(docs)?;
+        self.type_entry(&T::BASE_NAME)
+            .docs
+            .extend(docs.iter().map(|d| d.to_string()));
+        Ok(())
+    }
+
+    /// Installs a free function annotated with `#[rune::function]` into the module
+    pub fn function(&mut self, module: &mut Module, meta: FunctionMeta) -> Result<(), ContextError> {
+        let doc = function_doc(meta)?;
+        module.function_meta(meta)?;
+        self.functions.push(doc);
+        Ok(())
+    }
+
+    /// Installs a function annotated with `#[rune::function]` which is associated with the type `T` into the module
+    pub fn associated_function<T: Named + ?Sized>(
+        &mut self,
+        module: &mut Module,
+        meta: FunctionMeta,
+    ) -> Result<(), ContextError> {
+        let doc = function_doc(meta)?;
+        module.function_meta(meta)?;
+        self.type_entry(&T::BASE_NAME).functions.push(doc);
+        Ok(())
+    }
+
+    /// Merges documentation of another set of modules into this one, types with the same name are combined
+    pub fn merge(&mut self, o: Self) {
+        self.functions.extend(o.functions);
+        self.hooks.extend(o.hooks);
+        for ty in o.types {
+            let entry = self.type_entry(&ty.name);
+            entry.docs.extend(ty.docs);
+            entry.functions.extend(ty.functions);
+        }
+    }
+
+    fn type_entry(&mut self, name: &str) -> &mut RuneTypeDoc {
+        let idx = match self.types.iter().position(|t| t.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.types.push(RuneTypeDoc {
+                    name: name.to_owned(),
+                    docs: Default::default(),
+                    functions: Default::default(),
+                });
+                self.types.len() - 1
+            }
+        };
+        &mut self.types[idx]
+    }
+
+    /// Types sorted by name, so the output does not depend on the order providers were added in
+    fn sorted_types(&self) -> Vec<&RuneTypeDoc> {
+        let mut types = self.types.iter().collect::<Vec<_>>();
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+        types
+    }
+
+    /// Renders the documentation as a single Markdown page
+    pub fn to_markdown(&self, title: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {title}\n");
+
+        if !self.functions.is_empty() {
+            let _ = writeln!(out, "## Functions\n");
+            for f in &self.functions {
+                write_markdown_function(&mut out, f);
+            }
+        }
+
+        if !self.hooks.is_empty() {
+            let _ = writeln!(out, "## Hooks\n");
+            for f in &self.hooks {
+                write_markdown_function(&mut out, f);
+            }
+        }
+
+        let types = self.sorted_types();
+        if !types.is_empty() {
+            let _ = writeln!(out, "## Types\n");
+            for ty in types {
+                let _ = writeln!(out, "### {}\n", ty.name);
+                write_markdown_docs(&mut out, &ty.docs);
+                for f in &ty.functions {
+                    write_markdown_function(&mut out, f);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Renders the documentation as a single self-contained HTML page
+    pub fn to_html(&self, title: &str) -> String {
+        let mut page = HtmlPage::new(title);
+
+        if !self.functions.is_empty() {
+            let mut section = HtmlSection::new("functions", "Functions");
+            for f in &self.functions {
+                write_html_function(&mut section, f);
+            }
+            page.section(section);
+        }
+
+        if !self.hooks.is_empty() {
+            let mut section = HtmlSection::new("hooks", "Hooks");
+            for f in &self.hooks {
+                write_html_function(&mut section, f);
+            }
+            page.section(section);
+        }
+
+        for ty in self.sorted_types() {
+            let mut section = HtmlSection::new(&format!("type.{}", ty.name), &ty.name);
+            section.docs(&ty.docs);
+            for f in &ty.functions {
+                write_html_function(&mut section, f);
+            }
+            page.section(section);
+        }
+
+        page.render()
+    }
+}
+
+/// Documents a function with the name, parameters and doc comments recorded by `#[rune::function]`
+fn function_doc(meta: FunctionMeta) -> Result<RuneFunctionDoc, ContextError> {
+    let meta = meta()?;
+    Ok(RuneFunctionDoc {
+        signature: format!("{}({})", meta.name, meta.arguments.join(", ")),
+        docs: meta
+            .docs
+            .iter()
+            // doc comments keep the space following `///`
+            .map(|d| d.strip_prefix(' ').unwrap_or(d).to_owned())
+            .collect(),
+    })
+}
+
+fn write_markdown_docs(out: &mut String, docs: &[String]) {
+    if !docs.is_empty() {
+        let _ = writeln!(out, "{}\n", docs.join("\n"));
+    }
+}
+
+fn write_markdown_function(out: &mut String, f: &RuneFunctionDoc) {
+    let _ = writeln!(out, "```rust\nfn {}\n```\n", f.signature);
+    write_markdown_docs(out, &f.docs);
+}
+
+fn write_html_function(section: &mut HtmlSection, f: &RuneFunctionDoc) {
+    section.code(&format!("fn {}", f.signature)).docs(&f.docs);
+}
+
+/// A piece of rune documentation,
+/// each piece is combined into one Markdown and one HTML page named after the first fragment
+pub struct RuneDocFragment {
+    name: &'static str,
+    docs: RuneDocs,
+}
+
+impl RuneDocFragment {
+    pub fn new(name: &'static str, docs: RuneDocs) -> Self {
+        Self { name, docs }
+    }
+
+    pub fn docs(&self) -> &RuneDocs {
+        &self.docs
+    }
+}
 
 impl DocFragment for RuneDocFragment {
-    fn merge(self, _o: Self) -> Self {
-        todo!()
+    fn merge(mut self, o: Self) -> Self {
+        self.docs.merge(o.docs);
+        self
+    }
+
+    fn with_hooks(mut self, hooks: &[HookSchema]) -> Self {
+        self.docs.hooks.extend(hooks.iter().map(|hook| {
+            RuneFunctionDoc {
+                signature: hook.signature(),
+                docs: hook
+                    .docs
+                    .iter()
+                    .flat_map(|d| d.lines())
+                    .map(str::to_owned)
+                    .collect(),
+            }
+        }));
+        self
     }
 
     fn gen_docs(self) -> Result<(), ScriptError> {
-        todo!()
+        let script_doc_dir = &env::var("SCRIPT_DOC_DIR")
+            .map(|v| v.into())
+            .unwrap_or_else(|_e| {
+                FileAssetReader::get_base_path()
+                    .join("assets")
+                    .join("scripts")
+                    .join("doc")
+            });
+
+        fs::create_dir_all(script_doc_dir).map_err(|e| {
+            ScriptError::DocGenError(format!(
+                "Could not create `{}` directories: {e}",
+                script_doc_dir.display()
+            ))
+        })?;
+
+        write_doc_file(
+            &script_doc_dir.join(format!("{}.md", self.name)),
+            &self.docs.to_markdown(self.name),
+        )?;
+        write_doc_file(
+            &script_doc_dir.join(format!("{}.html", self.name)),
+            &self.docs.to_html(self.name),
+        )
     }
 
     fn name(&self) -> &'static str {
-        todo!()
+        self.name
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(rune::Any)]
+    struct World;
+
+    /// Spawns an entity
+    #[rune::function(instance)]
+    fn spawn(_world: &World) -> u32 {
+        0
+    }
+
+    /// Despawns <entity>
+    #[rune::function(instance)]
+    fn despawn(_world: &World, entity: u32) -> u32 {
+        entity
+    }
+
+    /// Returns the world
+    #[rune::function]
+    fn world() -> World {
+        World
+    }
+
+    #[test]
+    fn merged_fragments_combine_types_with_the_same_name() {
+        let mut module = Module::new();
+        let mut a = RuneDocs::new();
+        a.ty::<World>(&mut module, &["The game world"]).unwrap();
+        a.associated_function::<World>(&mut module, spawn).unwrap();
+        a.function(&mut module, world).unwrap();
+
+        let mut b = RuneDocs::new();
+        b.associated_function::<World>(&mut Module::new(), despawn)
+            .unwrap();
+
+        let mut context = rune::Context::new();
+        context.install(module).unwrap();
+
+        let docs = RuneDocFragment::new("Game", a)
+            .merge(RuneDocFragment::new("Other", b))
+            .docs
+            .clone();
+
+        assert_eq!(docs.types.len(), 1);
+        assert_eq!(docs.types[0].functions.len(), 2);
+
+        let markdown = docs.to_markdown("Game");
+        assert!(markdown.contains("### World"));
+        assert!(markdown.contains("fn world()\n```\n\nReturns the world"));
+        assert!(markdown.contains("fn despawn(self, entity)"));
+        assert!(!markdown.contains("## Hooks"));
+
+        let html = docs.to_html("Game");
+        assert!(html.contains("id=\"type.World\""));
+        assert!(html.contains("Despawns &lt;entity&gt;"));
+    }
+
+    #[test]
+    fn hooks_are_documented() {
+        let docs = RuneDocFragment::new("Game", RuneDocs::new())
+            .with_hooks(&[HookSchema::new("on_update").with_docs("Called every frame")])
+            .docs
+            .clone();
+
+        let markdown = docs.to_markdown("Game");
+        assert!(markdown.contains("## Hooks\n\n```rust\nfn on_update()\n```\n\nCalled every frame"));
     }
 }
@@ -1,25 +1,27 @@
-use std::{marker::PhantomData, sync::Arc};
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
 
 use bevy::prelude::*;
 use bevy_mod_scripting_core::{
     prelude::*,
     systems::{self, CachedScriptState},
-    world::{WorldPointer, WorldPointerGuard},
+    world::{WorldMutationMode, WorldPointer, WorldPointerGuard},
 };
-use prelude::{RuneDocFragment, RuneFile, RuneLoader};
+use prelude::{RuneAssetModules, RuneDocFragment, RuneFile, RuneLoader};
 use rune::{
-    runtime::{Args, RuntimeContext, VmError, VmResult},
+    runtime::{debug::DebugArgs, Args, RuntimeContext, VmError},
     Context, Diagnostics, Source, Sources, Unit, Vm,
 };
 
 mod assets;
 mod docs;
+mod modules;
 
 pub mod prelude {
     pub use crate::{
         assets::{RuneFile, RuneLoader},
-        docs::RuneDocFragment,
-        RuneArgs, RuneEvent, RuneScriptContext, RuneScriptHost,
+        docs::{FunctionMeta, RuneDocFragment, RuneDocs, RuneFunctionDoc, RuneTypeDoc},
+        modules::{RuneAssetModules, RuneAssetSourceLoader},
+        current_entity, with_current_entity, RuneArgs, RuneEvent, RuneScriptContext, RuneScriptHost,
     };
     pub use rune::{self, runtime::Args, Context};
 }
@@ -49,6 +51,33 @@ impl<A: RuneArgs> ScriptEvent for RuneEvent<A> {
     }
 }
 
+thread_local! {
+    static CURRENT_ENTITY: std::cell::Cell<Option<Entity>> = const { std::cell::Cell::new(None) };
+}
+
+/// Returns the entity of the script executing on the current thread, if any.
+///
+/// Rune has no notion of globals, so API providers can use this together with [`WorldPointer::current`]
+/// to expose the script's entity and world to scripts.
+pub fn current_entity() -> Option<Entity> {
+    CURRENT_ENTITY.with(|current| current.get())
+}
+
+/// Runs the given closure with the given entity set as the one returned by [`current_entity`],
+/// the previously set entity is restored afterwards.
+pub fn with_current_entity<O>(entity: Entity, f: impl FnOnce() -> O) -> O {
+    struct Restore(Option<Entity>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT_ENTITY.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(CURRENT_ENTITY.with(|current| current.replace(Some(entity))));
+    f()
+}
+
 /// A cached Rune Vm used to execute units.
 struct RuneVm(Vm);
 
@@ -69,19 +98,43 @@ pub struct RuneScriptContext {
 
 #[derive(Resource)]
 /// Rune script host. Enables Rune scripting.
-pub struct RuneScriptHost<A: RuneArgs> {
-    _ph: PhantomData<A>,
+///
+/// Multiple Rune hosts can be registered by giving each a distinct label `L`,
+/// each host then only runs the scripts in `ScriptCollection<RuneFile, L>` components.
+pub struct RuneScriptHost<A: RuneArgs, L: ScriptLabel = ()> {
+    /// resolves `mod` declarations against `RuneFile` assets, installed on startup
+    pub modules: Option<RuneAssetModules>,
+    /// the modules each script declared when it was last compiled, until taken by [`ScriptHost::take_script_dependencies`]
+    dependencies: HashMap<u32, Vec<AssetId<RuneFile>>>,
+    _ph: PhantomData<(A, L)>,
 }
 
-impl<A: RuneArgs> Default for RuneScriptHost<A> {
+impl<A: RuneArgs, L: ScriptLabel> Default for RuneScriptHost<A, L> {
     fn default() -> Self {
         Self {
+            modules: None,
+            dependencies: Default::default(),
             _ph: Default::default(),
         }
     }
 }
 
-impl<A: RuneArgs> RuneScriptHost<A> {
+impl<A: RuneArgs, L: ScriptLabel> RuneScriptHost<A, L> {
+    /// Keeps the modules declared by scripts in sync with their assets
+    fn sync_assets(
+        host: Res<Self>,
+        mut events: EventReader<AssetEvent<RuneFile>>,
+        assets: Res<Assets<RuneFile>>,
+    ) {
+        for event in events.read() {
+            if let AssetEvent::Added { id } | AssetEvent::Modified { id } = event {
+                if let (Some(modules), Some(asset)) = (&host.modules, assets.get(*id)) {
+                    modules.update(*id, &asset.bytes);
+                }
+            }
+        }
+    }
+
     /// Helper function to handle errors from a Rune virtual machine.
     ///
     #[cold]
@@ -103,7 +156,7 @@ impl<A: RuneArgs> RuneScriptHost<A> {
     }
 }
 
-impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
+impl<A: RuneArgs, L: ScriptLabel> ScriptHost for RuneScriptHost<A, L> {
     type ScriptContext = RuneScriptContext;
 
     type ScriptEvent = RuneEvent<A>;
@@ -114,20 +167,26 @@ impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
 
     type DocTarget = RuneDocFragment;
 
+    type Label = L;
+
     fn register_with_app_in_set(
         app: &mut App,
         schedule: impl bevy::ecs::schedule::ScheduleLabel,
         set: impl SystemSet,
     ) {
+        // other rune hosts might have registered the asset already
+        if !app.world.contains_resource::<Assets<RuneFile>>() {
+            app.init_asset::<RuneFile>()
+                .init_asset_loader::<RuneLoader>()
+                .register_type::<Handle<RuneFile>>();
+        }
+
         app.add_priority_event::<Self::ScriptEvent>()
-            .init_asset::<RuneFile>()
-            .init_asset_loader::<RuneLoader>()
             .init_resource::<CachedScriptState<Self>>()
-            .init_resource::<ScriptContexts<Self::ScriptContext>>()
+            .init_resource::<ScriptContexts<Self::ScriptContext, L>>()
             .init_resource::<APIProviders<Self>>()
-            .register_type::<ScriptCollection<Self::ScriptAsset>>()
-            .register_type::<Script<Self::ScriptAsset>>()
-            .register_type::<Handle<RuneFile>>()
+            .register_type::<ScriptCollection<Self::ScriptAsset, L>>()
+            .register_type::<Script<Self::ScriptAsset, L>>()
             // Add a cached Vm as a non-send resource.
             .insert_non_send_resource(RuneVm::default())
             // handle script insertions removal first
@@ -135,12 +194,19 @@ impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
             .add_systems(
                 schedule,
                 (
+                    Self::sync_assets,
                     systems::script_add_synchronizer::<Self>,
                     systems::script_remove_synchronizer::<Self>,
                     systems::script_hot_reload_handler::<Self>,
                 )
                     .chain()
                     .in_set(set),
+            )
+            .add_systems(
+                Startup,
+                |mut host: ResMut<Self>, asset_server: Res<AssetServer>| {
+                    host.modules = Some(RuneAssetModules::new(asset_server.clone()));
+                },
             );
     }
 
@@ -161,9 +227,11 @@ impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
         let mut sources = Sources::new();
         sources
             .insert(
-                Source::new(
+                // modules are resolved relative to the path of the script
+                Source::with_path(
                     script_data.name,
                     std::str::from_utf8(script).expect("Slice is not UTF-8"),
+                    script_data.name,
                 )
                 .map_err(|msg| ScriptError::FailedToLoad {
                     script: script_data.name.into(),
@@ -175,10 +243,33 @@ impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
                 msg: msg.to_string(),
             })?;
 
-        let result = rune::prepare(&mut sources)
+        let mut source_loader = self.modules.as_ref().map(RuneAssetModules::source_loader);
+
+        let mut prepare = rune::prepare(&mut sources)
             .with_context(&context)
-            .with_diagnostics(&mut diagnostics)
-            .build();
+            .with_diagnostics(&mut diagnostics);
+        if let Some(source_loader) = &mut source_loader {
+            prepare = prepare.with_source_loader(source_loader);
+        }
+        let result = prepare.build();
+
+        if let Some(source_loader) = source_loader {
+            self.dependencies.insert(
+                script_data.sid,
+                source_loader.dependencies.iter().map(Handle::id).collect(),
+            );
+
+            // the script is reloaded once its modules are loaded
+            if !source_loader.missing.is_empty() {
+                return Err(ScriptError::FailedToLoad {
+                    script: script_data.name.into(),
+                    msg: format!(
+                        "Declared modules are not loaded yet: {}",
+                        source_loader.missing.join(", ")
+                    ),
+                });
+            }
+        }
 
         if !diagnostics.is_empty() {
             let mut writer = rune::termcolor::Buffer::no_color();
@@ -216,6 +307,40 @@ impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
         providers.setup_all(script_data, ctx)
     }
 
+    fn take_script_dependencies(
+        &mut self,
+        script_data: &ScriptData,
+    ) -> Vec<AssetId<Self::ScriptAsset>> {
+        self.dependencies
+            .remove(&script_data.sid)
+            .unwrap_or_default()
+    }
+
+    /// Functions are only reported if the unit was compiled with debug info, which is the default
+    fn script_functions(&self, ctx: &Self::ScriptContext) -> Vec<ScriptFunction> {
+        let Some(debug_info) = ctx.unit.debug_info() else {
+            return Vec::default();
+        };
+
+        debug_info
+            .functions
+            .values()
+            .filter_map(|signature| {
+                let name = signature.path.to_string();
+                // only top level functions can be called as hooks
+                if name.contains("::") {
+                    return None;
+                }
+                let arity = match &signature.args {
+                    DebugArgs::EmptyArgs => None,
+                    DebugArgs::TupleArgs(n) => Some(*n),
+                    DebugArgs::Named(names) => Some(names.len()),
+                };
+                Some(ScriptFunction { name, arity })
+            })
+            .collect()
+    }
+
     fn handle_events<'a>(
         &mut self,
         world: &mut World,
@@ -225,6 +350,10 @@ impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
     ) {
         // Grab the cached Vm.
         let RuneVm(mut vm) = world.remove_non_send_resource::<RuneVm>().unwrap(/* invariant */);
+        let mode = world
+            .get_resource::<WorldMutationMode>()
+            .copied()
+            .unwrap_or_default();
 
         {
             // Safety:
@@ -234,8 +363,9 @@ impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
             let world = unsafe { WorldPointerGuard::new(world) };
 
             ctxs.for_each(|(script_data, ctx)| {
+                let script_world = world.with_mode(mode);
                 providers
-                    .setup_runtime_all(world.clone(), &script_data, ctx)
+                    .setup_runtime_all(script_world.clone(), &script_data, ctx)
                     .expect("Could not setup script runtime");
 
                 for event in events {
@@ -247,19 +377,21 @@ impl<A: RuneArgs> ScriptHost for RuneScriptHost<A> {
                     *vm.context_mut() = Arc::clone(&ctx.runtime_context);
                     *vm.unit_mut() = Arc::clone(&ctx.unit);
 
-                    let mut exec = match vm.execute([event.hook_name.as_str()], event.args.clone())
-                    {
-                        Ok(exec) => exec,
-                        Err(error) => {
-                            Self::handle_rune_error(world.clone(), error, &script_data);
-                            continue;
-                        }
-                    };
+                    // make the world and entity reachable for argument conversions and API providers
+                    let result = script_world.enter(|| {
+                        with_current_entity(script_data.entity, || {
+                            vm.execute([event.hook_name.as_str()], event.args.clone())
+                                .and_then(|mut exec| exec.complete().into_result())
+                        })
+                    });
 
-                    if let VmResult::Err(error) = exec.complete() {
+                    if let Err(error) = result {
                         Self::handle_rune_error(world.clone(), error, &script_data);
                     }
                 }
+
+                // apply any structural changes the script deferred
+                script_world.apply_deferred();
             });
 
             // explictly release the pointer to world.
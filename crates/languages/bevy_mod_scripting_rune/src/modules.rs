@@ -0,0 +1,192 @@
+use std::{path::Path, sync::Arc};
+
+use bevy::{
+    asset::{AssetPath, AssetServer, LoadState},
+    prelude::*,
+};
+use bevy_mod_scripting_core::prelude::AssetModules;
+use rune::{
+    ast::Spanned,
+    compile::{self, ComponentRef, Item, SourceLoader},
+    Source,
+};
+
+use crate::assets::RuneFile;
+
+/// The `RuneFile` assets scripts declared as modules, shared by every script of a `RuneScriptHost`.
+///
+/// Module declarations resolve like they do with Rune's `FileSourceLoader`, relative to the script's name:
+/// `mod utils;` in `scripts/game.rune` resolves to the `scripts/utils/mod.rune` asset, or `scripts/utils.rune` if the former does not exist.
+/// Modules are requested from the asset server the first time a script declaring them is compiled,
+/// scripts which are compiled before their modules are available fail to load and are reloaded once the modules arrive.
+#[derive(Clone)]
+pub struct RuneAssetModules {
+    modules: AssetModules<RuneFile, Arc<str>>,
+}
+
+impl RuneAssetModules {
+    pub fn new(asset_server: AssetServer) -> Self {
+        Self {
+            modules: AssetModules::new(asset_server),
+        }
+    }
+
+    /// Creates a source loader for compiling a single script
+    pub fn source_loader(&self) -> RuneAssetSourceLoader<'_> {
+        RuneAssetSourceLoader {
+            modules: self,
+            dependencies: Default::default(),
+            missing: Default::default(),
+        }
+    }
+
+    /// Updates the source of the module stored in the given asset, does nothing if no script declares it
+    pub fn update(&self, id: AssetId<RuneFile>, bytes: &[u8]) {
+        self.modules
+            .update(id, String::from_utf8_lossy(bytes).into());
+    }
+}
+
+/// Loads the sources of the modules declared by a single script, recording which modules it depends on
+pub struct RuneAssetSourceLoader<'a> {
+    modules: &'a RuneAssetModules,
+    /// every module the script depends on, including ones which are not loaded yet
+    pub dependencies: Vec<Handle<RuneFile>>,
+    /// the modules which are not loaded yet
+    pub missing: Vec<String>,
+}
+
+impl SourceLoader for RuneAssetSourceLoader<'_> {
+    fn load(&mut self, root: &Path, item: &Item, span: &dyn Spanned) -> compile::Result<Source> {
+        let Some(candidates) = module_asset_paths(root, item) else {
+            return Err(compile::Error::msg(
+                span,
+                format!(
+                    "Cannot resolve module `{item}` relative to `{}`",
+                    root.display()
+                ),
+            ));
+        };
+
+        for path in candidates {
+            // not every candidate exists
+            let Some(handle) = self.modules.modules.load(&path) else {
+                continue;
+            };
+            if self.modules.modules.asset_server().load_state(handle.id()) == LoadState::Failed {
+                continue;
+            }
+
+            if !self.dependencies.contains(&handle) {
+                self.dependencies.push(handle);
+            }
+
+            if let Some(source) = self.modules.modules.source(&path) {
+                let name = path.to_string();
+                return Source::with_path(&name, &*source, path.path())
+                    .map_err(|e| compile::Error::msg(span, e));
+            }
+        }
+
+        self.missing.push(item.to_string());
+        Err(compile::Error::msg(
+            span,
+            format!("Module `{item}` is not loaded yet"),
+        ))
+    }
+}
+
+/// The asset paths the module with the given item could be stored in relative to the root script,
+/// in the order Rune's `FileSourceLoader` searches them. Modules use the same extension as the root script.
+fn module_asset_paths(root: &Path, item: &Item) -> Option<[AssetPath<'static>; 2]> {
+    let extension = root.extension()?.to_str()?.to_owned();
+    let mut base = root.parent()?.to_path_buf();
+    for component in item {
+        match component {
+            ComponentRef::Str(name) => base.push(name),
+            _ => return None,
+        }
+    }
+
+    Some([
+        AssetPath::from(base.join("mod").with_extension(&extension)),
+        AssetPath::from(base.with_extension(&extension)),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use rune::compile::ItemBuf;
+
+    use super::*;
+
+    #[test]
+    fn modules_resolve_relative_to_the_root_script() {
+        let item = ItemBuf::with_item(["utils", "math"]).unwrap();
+        let paths = module_asset_paths(Path::new("scripts/game.rune"), &item).unwrap();
+
+        assert_eq!(
+            paths[0],
+            AssetPath::from(PathBuf::from("scripts/utils/math/mod.rune"))
+        );
+        assert_eq!(
+            paths[1],
+            AssetPath::from(PathBuf::from("scripts/utils/math.rune"))
+        );
+    }
+
+    /// Compiles the given script with a source loader, returning the value of its `main` function
+    fn compile(loader: &mut RuneAssetSourceLoader, script: &str) -> Option<i64> {
+        let context = rune::Context::with_default_modules().unwrap();
+        let mut sources = rune::Sources::new();
+        sources
+            .insert(Source::with_path("scripts/game.rune", script, "scripts/game.rune").unwrap())
+            .unwrap();
+        let unit = rune::prepare(&mut sources)
+            .with_context(&context)
+            .with_source_loader(loader)
+            .build()
+            .ok()?;
+
+        let mut vm = rune::Vm::new(
+            std::sync::Arc::new(context.runtime().unwrap()),
+            std::sync::Arc::new(unit),
+        );
+        let output = vm.call(["main"], ()).unwrap();
+        Some(rune::from_value(output).unwrap())
+    }
+
+    #[test]
+    fn declared_modules_are_loaded_from_assets() {
+        let dir = std::env::temp_dir().join("bevy_mod_scripting_rune_modules");
+        std::fs::create_dir_all(dir.join("scripts/utils")).unwrap();
+        std::fs::write(dir.join("scripts/utils/mod.rune"), "").unwrap();
+        std::fs::write(dir.join("scripts/utils.rune"), "").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin {
+                file_path: dir.to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+        ))
+        .init_asset::<RuneFile>();
+        let modules = RuneAssetModules::new(app.world.resource::<AssetServer>().clone());
+
+        let script = "mod utils; pub fn main() { utils::value() }";
+
+        // the script cannot be compiled until its module is loaded
+        let mut loader = modules.source_loader();
+        assert_eq!(compile(&mut loader, script), None);
+        assert_eq!(loader.missing, vec!["utils".to_owned()]);
+        let handle = loader.dependencies[0].clone();
+
+        modules.update(handle.id(), b"pub fn value() { 1 }");
+        let mut loader = modules.source_loader();
+        assert_eq!(compile(&mut loader, script), Some(1));
+        assert_eq!(loader.dependencies, vec![handle]);
+    }
+}
@@ -30,7 +30,7 @@ fn main() -> std::io::Result<()> {
         .register_foreign_lua_type::<Option<bool>>()
         .register_foreign_lua_type::<Option<Vec<bool>>>()
         .add_script_host::<LuaScriptHost<()>>(PostUpdate)
-        .add_api_provider::<LuaScriptHost<()>>(Box::new(LuaBevyAPIProvider))
+        .add_api_provider::<LuaScriptHost<()>>(Box::new(LuaBevyAPIProvider::default()))
         .add_api_provider::<LuaScriptHost<()>>(Box::new(LuaCoreBevyAPIProvider))
         .add_systems(Startup,
             |world: &mut World| {
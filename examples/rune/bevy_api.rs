@@ -0,0 +1,87 @@
+use bevy::app::AppExit;
+
+use bevy::prelude::*;
+use bevy_mod_scripting::{api::rune::bevy::RuneBevyAPIProvider, prelude::*};
+
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct MyComponent {
+    vec2: Vec2,
+    usize: usize,
+    f32: f32,
+    string: String,
+}
+
+fn main() -> std::io::Result<()> {
+    let mut app = App::new();
+
+    app.add_plugins(DefaultPlugins)
+        .add_plugins(ScriptingPlugin)
+        .register_type::<MyComponent>()
+        // this system set handles addition and removal of script contexts, we can safely use `CoreSet::PostUpdate`
+        .add_script_host::<RuneScriptHost<()>>(PostUpdate)
+        .add_api_provider::<RuneScriptHost<()>>(Box::new(RuneBevyAPIProvider))
+        .add_systems(Update, |world: &mut World| {
+            let entity = world
+                .spawn(())
+                .insert(MyComponent {
+                    vec2: Vec2::new(1.0, 2.0),
+                    usize: 5,
+                    f32: 6.7,
+                    string: "Hello".to_owned(),
+                })
+                .id();
+
+            // run script
+            world.resource_scope(|world, mut host: Mut<RuneScriptHost<()>>| {
+                host.run_one_shot(
+                    r#"
+                        pub fn once() {
+                            // rune has no globals, the world and the entity this script is attached to
+                            // are retrieved via functions instead
+                            let world = world();
+                            println!("{}", world);
+
+                            // we first retrieve ID's for our component and resource by their short name (long name/full path also work)
+                            let my_component_type = world.get_type_by_name("MyComponent")?;
+
+                            // then ask the world to give us a reference to `MyComponent` on the entity we just spawned
+                            // resources work the same way, but we use `get_resource` instead of `get_component`
+                            // the comp object is resolved to a `bevy_script_api::script_ref::ReflectedValue`,
+                            // whose fields are accessed by indexing
+                            let comp = world.get_component(entity(), my_component_type)?;
+
+                            println!("Before script: {}", comp);
+
+                            comp["usize"] = 2;
+                            comp["f32"] = comp["f32"] * 2.0;
+                            comp["vec2"]["x"] = 3.0;
+                            comp["string"] = `${comp["string"]} from rune`;
+
+                            println!("After script: {}", comp);
+
+                            for (entity, comp) in world.query([my_component_type]).iter() {
+                                println!("{:?} has {}", entity, comp["string"]);
+                            }
+                        }
+                        "#
+                    .as_bytes(),
+                    "script.rune",
+                    entity,
+                    world,
+                    RuneEvent {
+                        hook_name: "once".to_owned(),
+                        args: (),
+                        recipients: Recipients::All,
+                    },
+                )
+                .expect("Something went wrong in the script!");
+            });
+
+            world.send_event(AppExit);
+        });
+
+    app.run();
+
+    Ok(())
+}
@@ -9,7 +9,7 @@ fn main() {
         .add_plugins(ScriptingPlugin)
         .add_plugins(AssetPlugin::default());
 
-    static INVALID_ARGUMENT_WARNING: &str = "Expected one of: 'lua','rhai' as arguments";
+    static INVALID_ARGUMENT_WARNING: &str = "Expected one of: 'lua','rhai','rune' as arguments";
 
     let lang = args.get(1).expect(INVALID_ARGUMENT_WARNING);
 
@@ -18,14 +18,29 @@ fn main() {
             #[cfg(all(feature = "lua", feature = "lua_script_api"))]
             app.add_script_host::<LuaScriptHost<()>>(PostUpdate)
                 .add_api_provider::<LuaScriptHost<()>>(Box::new(LuaCoreBevyAPIProvider))
-                .add_api_provider::<LuaScriptHost<()>>(Box::new(LuaBevyAPIProvider))
+                .add_api_provider::<LuaScriptHost<()>>(Box::new(LuaBevyAPIProvider::default()))
                 .update_documentation::<LuaScriptHost<()>>();
 
             #[cfg(any(not(feature = "lua"), not(feature = "lua_script_api")))]
             println!("Re-run with the following features enabled: `lua`,`lua_script_api`")
         }
         "rhai" => {
-            println!("Rhai documentation generation is not supported yet");
+            #[cfg(all(feature = "rhai", feature = "rhai_script_api"))]
+            app.add_script_host::<RhaiScriptHost<()>>(PostUpdate)
+                .add_api_provider::<RhaiScriptHost<()>>(Box::new(RhaiBevyAPIProvider))
+                .update_documentation::<RhaiScriptHost<()>>();
+
+            #[cfg(any(not(feature = "rhai"), not(feature = "rhai_script_api")))]
+            println!("Re-run with the following features enabled: `rhai`,`rhai_script_api`")
+        }
+        "rune" => {
+            #[cfg(all(feature = "rune", feature = "rune_script_api"))]
+            app.add_script_host::<RuneScriptHost<()>>(PostUpdate)
+                .add_api_provider::<RuneScriptHost<()>>(Box::new(RuneBevyAPIProvider))
+                .update_documentation::<RuneScriptHost<()>>();
+
+            #[cfg(any(not(feature = "rune"), not(feature = "rune_script_api")))]
+            println!("Re-run with the following features enabled: `rune`,`rune_script_api`")
         }
         _ => println!("{}", INVALID_ARGUMENT_WARNING),
     }
@@ -27,9 +27,18 @@ pub mod rhai {
 #[cfg(feature = "rune")]
 pub mod rune {
     pub use bevy_mod_scripting_rune::*;
+
+    #[cfg(feature = "rune_script_api")]
+    pub mod api {
+        pub use bevy_script_api::rune::*;
+    }
 }
 
-#[cfg(any(feature = "lua_script_api", feature = "rhai_script_api"))]
+#[cfg(any(
+    feature = "lua_script_api",
+    feature = "rhai_script_api",
+    feature = "rune_script_api"
+))]
 pub mod api {
     pub use bevy_script_api::*;
 }
@@ -46,6 +55,10 @@ pub mod prelude {
     #[cfg(feature = "rune")]
     pub use bevy_mod_scripting_rune::prelude::*;
 
-    #[cfg(any(feature = "lua_script_api", feature = "rhai_script_api"))]
+    #[cfg(any(
+        feature = "lua_script_api",
+        feature = "rhai_script_api",
+        feature = "rune_script_api"
+    ))]
     pub use bevy_script_api::prelude::*;
 }